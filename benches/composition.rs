@@ -0,0 +1,27 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use num_bigint::BigUint;
+use paired_binary::{InitialPattern, Propagator};
+
+fn propagator(values: &[u64], n_base_bits: usize) -> Propagator {
+    Propagator::new(InitialPattern::new(values.iter().copied().map(BigUint::from).collect(), n_base_bits).unwrap())
+}
+
+/// Composing 2^17 (about 131k) components from a slice and from an iterator.
+fn compose_many_components(c: &mut Criterion) {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let n_bits = 4 << 17;
+    let member = p.nth_member(n_bits, &(p.count_members(n_bits).unwrap() / 3u32)).unwrap();
+    let components: Vec<BigUint> = p.decompose_to_base(&member, n_bits).unwrap();
+
+    let mut group = c.benchmark_group("compose_131k_components");
+    group.sample_size(10);
+    group.bench_function("compose_from_base", |b| b.iter(|| p.compose_from_base(black_box(&components)).unwrap()));
+    group.bench_function("compose_from_base_unchecked", |b| b.iter(|| p.compose_from_base_unchecked(black_box(&components))));
+    group.bench_function("compose_from_iter", |b| {
+        b.iter_batched(|| components.clone(), |components| p.compose_from_iter(components.into_iter()).unwrap(), BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compose_many_components);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+use paired_binary::{InitialPattern, Propagator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+fn propagator(values: &[u64], n_base_bits: usize) -> Propagator {
+    Propagator::new(InitialPattern::new(values.iter().copied().map(BigUint::from).collect(), n_base_bits).unwrap())
+}
+
+/// The 4-bit components of an `n_bits`-wide `x`, most significant first.
+fn nibbles(x: &BigUint, n_bits: usize) -> impl Iterator<Item = BigUint> {
+    let mut bytes = x.to_bytes_le();
+    bytes.resize(n_bits / 8, 0);
+    bytes.into_iter().rev().flat_map(|byte| [byte >> 4, byte & 0xF]).map(BigUint::from)
+}
+
+/// Decomposes `x` into 4-bit components, checking each against `base` with
+/// `HashSet::contains`, the lookup the sorted values replaced.
+fn decompose_with_hash_set(base: &HashSet<BigUint>, x: &BigUint, n_bits: usize) -> Option<Vec<BigUint>> {
+    nibbles(x, n_bits).map(|component| base.contains(&component).then_some(component)).collect()
+}
+
+/// Checks every 4-bit component of `x` with `HashSet::contains`.
+fn is_member_with_hash_set(base: &HashSet<BigUint>, x: &BigUint, n_bits: usize) -> bool {
+    nibbles(x, n_bits).all(|component| base.contains(&component))
+}
+
+/// Decomposing members of 64k and 1M bits over a 4-bit base.
+fn deep_decompose(c: &mut Criterion) {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let base: &HashSet<BigUint> = &p.initial_pattern().s_base_values.iter().cloned().collect();
+    let mut group = c.benchmark_group("decompose_deep_base_4");
+    group.sample_size(10);
+    for n_bits in [1usize << 16, 1 << 20] {
+        let member = p.nth_member(n_bits, &(p.count_members(n_bits).unwrap() / 2u32)).unwrap();
+        group.bench_with_input(BenchmarkId::new("decompose_to_base", n_bits), &member, |b, member| {
+            b.iter(|| p.decompose_to_base(black_box(member), n_bits).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decompose_to_base_unchecked", n_bits), &member, |b, member| {
+            b.iter(|| p.decompose_to_base_unchecked(black_box(member), n_bits))
+        });
+        // The previous `decompose_to_base` verbatim: a membership pass through
+        // `decompose_to_base_streaming`, then a second pass cloning each component.
+        group.bench_with_input(BenchmarkId::new("check_then_decompose", n_bits), &member, |b, member| {
+            b.iter(|| {
+                let mut components = Vec::new();
+                p.decompose_to_base_streaming(black_box(member), n_bits, |component| {
+                    components.push(component.clone());
+                    ControlFlow::Continue(())
+                })
+                .unwrap();
+                components
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("hash_set_lookup", n_bits), &member, |b, member| {
+            b.iter(|| decompose_with_hash_set(base, black_box(member), n_bits).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Only the component lookups of the same decompositions, without collecting the
+/// components.
+fn deep_component_lookup(c: &mut Criterion) {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let base: &HashSet<BigUint> = &p.initial_pattern().s_base_values.iter().cloned().collect();
+    let mut group = c.benchmark_group("component_lookup_base_4");
+    group.sample_size(10);
+    for n_bits in [1usize << 16, 1 << 20] {
+        let member = p.nth_member(n_bits, &(p.count_members(n_bits).unwrap() / 2u32)).unwrap();
+        group.bench_with_input(BenchmarkId::new("is_member", n_bits), &member, |b, member| {
+            b.iter(|| p.is_member(black_box(member), n_bits).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("hash_set_lookup", n_bits), &member, |b, member| {
+            b.iter(|| is_member_with_hash_set(base, black_box(member), n_bits))
+        });
+    }
+    group.finish();
+}
+
+/// 10k decompositions of 256-bit members, each into a fresh vector and all into one
+/// reused buffer.
+fn repeated_decompose(c: &mut Criterion) {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let mut rng = StdRng::seed_from_u64(5);
+    let members: Vec<BigUint> = (0..10_000).map(|_| p.generate_random_s_n_member(256, &mut rng).unwrap()).collect();
+    let mut group = c.benchmark_group("decompose_10k_at_256_bits");
+    group.sample_size(10);
+    group.bench_function("decompose_to_base", |b| {
+        b.iter(|| {
+            for member in black_box(&members) {
+                black_box(p.decompose_to_base(member, 256).unwrap());
+            }
+        })
+    });
+    group.bench_function("decompose_to_base_into", |b| {
+        let mut out = Vec::new();
+        b.iter(|| {
+            for member in black_box(&members) {
+                p.decompose_to_base_into(member, 256, &mut out).unwrap();
+                black_box(&out);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deep_decompose, deep_component_lookup, repeated_decompose);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+use paired_binary::{InitialPattern, Propagator};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+fn propagator(values: &[u64], n_base_bits: usize) -> Propagator {
+    Propagator::new(InitialPattern::new(values.iter().copied().map(BigUint::from).collect(), n_base_bits).unwrap())
+}
+
+/// Membership by halving recursion, the approach `is_member` replaced.
+fn is_member_recursive(base: &BTreeSet<BigUint>, n_base_bits: usize, x: &BigUint, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(x);
+    }
+    let half = n_bits / 2;
+    let lower = x & ((BigUint::from(1u32) << half) - 1u32);
+    is_member_recursive(base, n_base_bits, &(x >> half), half) && is_member_recursive(base, n_base_bits, &lower, half)
+}
+
+/// Membership checks at levels above 64k bits, where every base-width window is read.
+fn deep_is_member(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_member_deep");
+    group.sample_size(10);
+    for (values, n_base_bits) in [(&[1u64][..], 1), (&[1, 3, 6, 9, 12][..], 4)] {
+        let p = propagator(values, n_base_bits);
+        let base = &p.initial_pattern().s_base_values;
+        for n_bits in [1usize << 17, 1 << 20] {
+            let member = p.nth_member(n_bits, &(p.count_members(n_bits).unwrap() / 2u32)).unwrap();
+            group.bench_with_input(BenchmarkId::new(format!("windows_base_{n_base_bits}"), n_bits), &member, |b, member| {
+                b.iter(|| p.is_member(black_box(member), n_bits).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new(format!("recursive_base_{n_base_bits}"), n_bits), &member, |b, member| {
+                b.iter(|| is_member_recursive(base, n_base_bits, black_box(member), n_bits))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// A batch of 100k values at one level, checked with `is_members` and with a loop of
+/// `is_member` calls.
+fn batch_is_member(c: &mut Criterion) {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let mut rng = StdRng::seed_from_u64(7);
+    let targets: Vec<BigUint> = (0..100_000).map(|_| BigUint::from(rng.gen::<u64>())).collect();
+    let mut group = c.benchmark_group("is_member_batch");
+    group.sample_size(10);
+    group.bench_function("is_members", |b| b.iter(|| p.is_members(black_box(&targets), 64).unwrap()));
+    group.bench_function("is_member_loop", |b| {
+        b.iter(|| black_box(&targets).iter().map(|x| p.is_member(x, 64)).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+/// 100k 64-bit members over an 8-bit base through the `u64` API and the `BigUint` API.
+fn machine_word(c: &mut Criterion) {
+    let p = propagator(&[0x3C, 0xA5, 0xFF, 0x01, 0x80], 8);
+    let mut rng = StdRng::seed_from_u64(11);
+    let members: Vec<BigUint> = (0..100_000).map(|_| p.generate_random_s_n_member(64, &mut rng).unwrap()).collect();
+    let words: Vec<u64> = members.iter().map(|x| x.iter_u64_digits().next().unwrap_or(0)).collect();
+    let components: Vec<Vec<u64>> = words.iter().map(|&x| p.decompose_to_base_u64(x, 64).unwrap()).collect();
+    let big_components: Vec<Vec<BigUint>> =
+        components.iter().map(|parts| parts.iter().map(|&part| BigUint::from(part)).collect()).collect();
+
+    let mut group = c.benchmark_group("machine_word_100k");
+    group.sample_size(10);
+    group.bench_function("is_member_u64", |b| {
+        b.iter(|| black_box(&words).iter().filter(|&&x| p.is_member_u64(x, 64).unwrap()).count())
+    });
+    group.bench_function("is_member", |b| {
+        b.iter(|| black_box(&members).iter().filter(|x| p.is_member(x, 64).unwrap()).count())
+    });
+    group.bench_function("decompose_to_base_u64", |b| {
+        b.iter(|| black_box(&words).iter().map(|&x| p.decompose_to_base_u64(x, 64).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("decompose_to_base", |b| {
+        b.iter(|| black_box(&members).iter().map(|x| p.decompose_to_base(x, 64).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("compose_from_base_u64", |b| {
+        b.iter(|| black_box(&components).iter().map(|parts| p.compose_from_base_u64(parts).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("compose_from_base", |b| {
+        b.iter(|| black_box(&big_components).iter().map(|parts| p.compose_from_base(parts).unwrap()).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+/// 10k membership checks at 1024 bits over a 4-bit base, with S_base lookups and with
+/// precomputed 16-bit and 32-bit levels.
+fn precomputed_levels(c: &mut Criterion) {
+    let plain = propagator(&[1, 3, 6, 9, 12], 4);
+    let mut rng = StdRng::seed_from_u64(13);
+    let members: Vec<BigUint> = (0..10_000).map(|_| plain.generate_random_s_n_member(1024, &mut rng).unwrap()).collect();
+    let mut group = c.benchmark_group("is_member_1024_bits_10k");
+    group.sample_size(10);
+    group.bench_function("s_base_lookups", |b| {
+        b.iter(|| black_box(&members).iter().filter(|x| plain.is_member(x, 1024).unwrap()).count())
+    });
+    for level in [16, 32] {
+        let mut p = plain.clone();
+        p.precompute_levels(level, 1 << 20).unwrap();
+        group.bench_function(BenchmarkId::new("precomputed", level), |b| {
+            b.iter(|| black_box(&members).iter().filter(|x| p.is_member(x, 1024).unwrap()).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, deep_is_member, batch_is_member, machine_word, precomputed_levels);
+criterion_main!(benches);
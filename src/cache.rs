@@ -0,0 +1,110 @@
+use crate::HashMap;
+use num_bigint::BigUint;
+use crate::propagator::Propagator;
+use crate::pattern::InitialPattern;
+use crate::error::HierarchyError;
+
+/// Hit and miss counters of a `CachedPropagator`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of sub-block lookups answered from the cache.
+    pub hits: u64,
+    /// Number of sub-block lookups that had to be computed.
+    pub misses: u64,
+}
+
+/// A `Propagator` wrapper that memoizes membership results for intermediate
+/// sub-blocks, keyed on the sub-block value and its bit-width.
+///
+/// This pays off when many queries share upper or lower halves, at the cost of
+/// allocating the cache. The plain `Propagator` is unaffected.
+#[derive(Debug, Clone)]
+pub struct CachedPropagator {
+    propagator: Propagator,
+    cache: HashMap<(BigUint, usize), bool>,
+    /// Maximum number of cached entries; `None` means unbounded.
+    capacity: Option<usize>,
+    stats: CacheStats,
+}
+
+impl CachedPropagator {
+    /// Creates a new `CachedPropagator` around `propagator`.
+    ///
+    /// # Arguments
+    /// * `propagator`: The propagator whose membership results are memoized.
+    /// * `capacity`: Maximum number of cached sub-block results, or `None` for an
+    ///   unbounded cache. When the cache is full it is cleared before inserting.
+    pub fn new(propagator: Propagator, capacity: Option<usize>) -> Self {
+        Self { propagator, cache: HashMap::new(), capacity, stats: CacheStats::default() }
+    }
+
+    /// Returns a reference to the wrapped `Propagator`.
+    pub fn propagator(&self) -> &Propagator {
+        &self.propagator
+    }
+
+    /// Returns the number of sub-block results currently cached.
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns the hit and miss counters accumulated since creation or the last
+    /// `reset_stats`.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Resets the hit and miss counters to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Discards all cached results.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Replaces the initial pattern, keeping the arity, membership rule and split strategy,
+    /// and discards all cached results since they were computed against the previous
+    /// pattern.
+    pub fn set_initial_pattern(&mut self, initial_pattern: InitialPattern) {
+        let propagator = &self.propagator;
+        self.propagator = Propagator::from_parts(initial_pattern, propagator.arity(), propagator.rule(), propagator.split_strategy());
+        self.cache.clear();
+    }
+
+    /// Checks if `x_target` is a member of S_N at `n_target_bits`, consulting and
+    /// populating the cache. Returns exactly what `Propagator::is_member` returns.
+    pub fn is_member(&mut self, x_target: &BigUint, n_target_bits: usize) -> Result<bool, HierarchyError> {
+        self.propagator.validate_member_query(x_target, n_target_bits)?;
+        Ok(self._is_member_cached(x_target, n_target_bits))
+    }
+
+    fn _is_member_cached(&mut self, x_current: &BigUint, n_current_bits: usize) -> bool {
+        if n_current_bits == self.propagator.initial_pattern().n_base_bits {
+            return self.propagator.is_base_value(x_current);
+        }
+
+        let key = (x_current.clone(), n_current_bits);
+        if let Some(&is_member) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return is_member;
+        }
+        self.stats.misses += 1;
+
+        let n_part_bits = n_current_bits / self.propagator.arity();
+        let parts = self.propagator.split_parts(x_current, n_current_bits);
+        let is_member = self.propagator.rule().combine(parts, |part| self._is_member_cached(&part, n_part_bits));
+
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return is_member;
+            }
+            if self.cache.len() >= capacity {
+                self.cache.clear();
+            }
+        }
+        self.cache.insert(key, is_member);
+        is_member
+    }
+}
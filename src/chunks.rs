@@ -0,0 +1,189 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+
+/// Iterates over the consecutive `width`-bit windows of a value, most significant
+/// window first.
+///
+/// Under the splitting rule, the S_base components of an S_N value are exactly these
+/// windows whatever the arity, so they can be read off directly instead of splitting the
+/// value level by level.
+#[derive(Debug, Clone)]
+pub(crate) struct BaseChunks {
+    digits: Vec<u32>,
+    width: usize,
+    /// Number of windows not yet yielded; also the index of the next window counted
+    /// from the least significant end, plus one.
+    remaining: usize,
+}
+
+impl BaseChunks {
+    /// Creates an iterator over the `count` windows of `width` bits making up `value`.
+    pub(crate) fn new(value: &BigUint, width: usize, count: usize) -> Self {
+        Self { digits: value.to_u32_digits(), width, remaining: count }
+    }
+}
+
+impl Iterator for BaseChunks {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(extract_window(&self.digits, self.remaining * self.width, self.width))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for BaseChunks {}
+
+/// Like `BaseChunks`, but yields the windows as `u64` without allocating.
+/// `width` must be at most 64.
+#[derive(Debug, Clone)]
+pub(crate) struct SmallChunks {
+    digits: Vec<u32>,
+    width: usize,
+    remaining: usize,
+}
+
+impl SmallChunks {
+    /// Creates an iterator over the `count` windows of `width <= 64` bits making up `value`.
+    pub(crate) fn new(value: &BigUint, width: usize, count: usize) -> Self {
+        debug_assert!(width <= 64, "SmallChunks windows must fit in a u64");
+        Self { digits: value.to_u32_digits(), width, remaining: count }
+    }
+}
+
+impl Iterator for SmallChunks {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(extract_window_u64(&self.digits, self.remaining * self.width, self.width))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for SmallChunks {}
+
+/// Reads the `width <= 64` bits starting at `bit_offset` from little-endian `u32` digits.
+fn extract_window_u64(digits: &[u32], bit_offset: usize, width: usize) -> u64 {
+    let (digit_index, shift) = (bit_offset / 32, bit_offset % 32);
+    // A window of up to 64 bits starting mid-digit spans at most three digits.
+    let mut bits = 0u128;
+    for i in 0..3 {
+        let digit = digits.get(digit_index + i).copied().unwrap_or(0);
+        bits |= u128::from(digit) << (32 * i);
+    }
+    let window = (bits >> shift) as u64;
+    if width == 64 {
+        window
+    } else {
+        window & ((1u64 << width) - 1)
+    }
+}
+
+/// Reads the `width` bits starting at `bit_offset` from little-endian `u32` digits.
+fn extract_window(digits: &[u32], bit_offset: usize, width: usize) -> BigUint {
+    let word_count = width.div_ceil(32);
+    let mut words = Vec::with_capacity(word_count);
+    for word_index in 0..word_count {
+        let bit = bit_offset + 32 * word_index;
+        let (digit_index, shift) = (bit / 32, bit % 32);
+        let low = digits.get(digit_index).copied().unwrap_or(0);
+        let mut word = low >> shift;
+        if shift != 0 {
+            let high = digits.get(digit_index + 1).copied().unwrap_or(0);
+            word |= high << (32 - shift);
+        }
+        words.push(word);
+    }
+
+    let trailing_bits = width % 32;
+    if trailing_bits != 0 {
+        if let Some(last) = words.last_mut() {
+            *last &= (1u32 << trailing_bits) - 1;
+        }
+    }
+    BigUint::new(words)
+}
+
+/// ORs `value` into little-endian `u32` digits starting at `bit_offset`. The value must
+/// fit within the digits from that offset on; this is the inverse of `extract_window`.
+pub(crate) fn write_window(digits: &mut [u32], bit_offset: usize, value: &BigUint) {
+    for (word_index, word) in value.iter_u32_digits().enumerate() {
+        let bit = bit_offset + 32 * word_index;
+        let (digit_index, shift) = (bit / 32, bit % 32);
+        digits[digit_index] |= word << shift;
+        if shift != 0 {
+            if let Some(high) = digits.get_mut(digit_index + 1) {
+                *high |= word >> (32 - shift);
+            }
+        }
+    }
+}
+
+/// Rearranges an `n_bits`-wide value split by interleaving, with `arity` parts per level,
+/// so that its parts at `part_n_bits` become consecutive windows, most significant first.
+///
+/// At each level part `j` (0 = most significant) takes the bits whose position is
+/// `arity - 1 - j` modulo `arity`. Over several levels the part holding bit `p` is then
+/// given by the low base-`arity` digits of `p` in reverse order, and the bit's position
+/// within the part by the remaining high digits.
+pub(crate) fn interleaved_to_windows(value: &BigUint, n_bits: usize, part_n_bits: usize, arity: usize) -> BigUint {
+    let part_count = n_bits / part_n_bits;
+    permute_bits(value, n_bits, |position| {
+        let window = reverse_digits(position % part_count, arity, part_count);
+        window * part_n_bits + position / part_count
+    })
+}
+
+/// The inverse of `interleaved_to_windows`.
+pub(crate) fn windows_to_interleaved(value: &BigUint, n_bits: usize, part_n_bits: usize, arity: usize) -> BigUint {
+    let part_count = n_bits / part_n_bits;
+    permute_bits(value, n_bits, |position| {
+        let window = position / part_n_bits;
+        (position % part_n_bits) * part_count + reverse_digits(window, arity, part_count)
+    })
+}
+
+/// Reverses the base-`radix` digits of `value`, taking as many digits as `digit_span`,
+/// a power of `radix`, has trailing zeros in that base.
+fn reverse_digits(mut value: usize, radix: usize, mut digit_span: usize) -> usize {
+    let mut reversed = 0;
+    while digit_span > 1 {
+        reversed = reversed * radix + value % radix;
+        value /= radix;
+        digit_span /= radix;
+    }
+    reversed
+}
+
+/// Moves every set bit of an `n_bits`-wide value from its position `p` to `target(p)`.
+/// Bits at or above `n_bits` are dropped, so wider values are truncated first.
+fn permute_bits<F: Fn(usize) -> usize>(value: &BigUint, n_bits: usize, target: F) -> BigUint {
+    let mut permuted = vec![0u32; n_bits.div_ceil(32)];
+    for (digit_index, mut bits) in value.iter_u32_digits().enumerate() {
+        while bits != 0 {
+            let position = digit_index * 32 + bits.trailing_zeros() as usize;
+            if position >= n_bits {
+                return BigUint::new(permuted);
+            }
+            let position = target(position);
+            permuted[position / 32] |= 1 << (position % 32);
+            bits &= bits - 1;
+        }
+    }
+    BigUint::new(permuted)
+}
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use crate::error::HierarchyError;
+use crate::propagator::MembershipRule;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A base-width chunk of a value that is not a member of S_base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkFailure {
+    /// Position of the chunk among the value's S_base-width components
+    /// (0 = most significant).
+    pub leaf_index: usize,
+    /// The chunk's value.
+    pub value: BigUint,
+}
+
+/// An S_base component position at which two members differ, as reported by
+/// `Propagator::diff_members`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkDiff {
+    /// Position of the component among the members' S_base components
+    /// (0 = most significant).
+    pub leaf_index: usize,
+    /// The component of the first member.
+    pub a_value: BigUint,
+    /// The component of the second member.
+    pub b_value: BigUint,
+}
+
+/// The result of `Propagator::check_membership_detailed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipReport {
+    /// Whether the value is a member of S_N.
+    pub is_member: bool,
+    /// Every chunk that is not in S_base, most significant first.
+    /// Empty exactly when `is_member` is `true`.
+    pub failures: Vec<ChunkFailure>,
+}
+
+/// The result of `Propagator::validate_components`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentValidation {
+    /// Every invalid component's index with the error describing why it is invalid, in
+    /// ascending index order.
+    pub invalid_indices: Vec<(usize, HierarchyError)>,
+    /// Whether the number of components is a non-zero power of the arity.
+    pub count_ok: bool,
+}
+
+impl ComponentValidation {
+    /// Returns `true` if the components can be composed: the count is valid and every
+    /// component is in S_base.
+    pub fn is_valid(&self) -> bool {
+        self.count_ok && self.invalid_indices.is_empty()
+    }
+}
+
+/// A record of why a value is a member of S_N, produced by `Propagator::membership_witness`
+/// and checked by `Propagator::verify_witness`.
+///
+/// With the `serde` feature enabled, `value` and `components` are serialized as decimal
+/// strings (big-endian bytes in binary formats).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MembershipWitness {
+    /// The member of S_N.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::biguint"))]
+    pub value: BigUint,
+    /// The level of `value`.
+    pub n_bits: usize,
+    /// The S_base components of `value`, most significant first.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::biguint_vec"))]
+    pub components: Vec<BigUint>,
+    /// `InitialPattern::fingerprint` of the pattern the witness was produced with.
+    pub pattern_fingerprint: u64,
+}
+
+/// The result of `Propagator::explain_membership`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipExplanation {
+    /// The value is a member of S_N.
+    IsMember,
+    /// The value is not a member. Following the first non-member part at each level leads
+    /// down to an S_base-width block that is not in S_base, or under
+    /// `MembershipRule::ExactlyOne` possibly to a wider block with several member parts.
+    NotMember {
+        /// The offending block.
+        value: BigUint,
+        /// The bit-width of the offending block, `n_base_bits` unless the descent stopped
+        /// early under `ExactlyOne`.
+        n_bits: usize,
+        /// The index of the part taken at each level, from the top level down
+        /// (0 = most significant, so 0 is the upper and 1 the lower half for arity 2).
+        path: Vec<usize>,
+    },
+}
+
+/// A node of the full evaluation of a membership check, produced by
+/// `Propagator::trace_membership`.
+///
+/// The root holds the checked value; every internal node holds a sub-block and its parts
+/// (the upper and lower halves for arity 2) as children. Nodes at `n_base_bits` are
+/// leaves, which pass if they are in S_base. Unlike `is_member`, every part is evaluated,
+/// so the trace is complete even below a failing part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipTrace {
+    /// The value of this sub-block.
+    pub value: BigUint,
+    /// The bit-width of this sub-block.
+    pub n_bits: usize,
+    /// Whether this sub-block is a member of the selected set at `n_bits`.
+    pub passed: bool,
+    /// The parts of this sub-block, most significant first, or empty at the base level.
+    pub children: Vec<MembershipTrace>,
+}
+
+impl MembershipTrace {
+    /// Builds a trace bottom-up from its leaves, most significant first, by joining
+    /// `arity` neighbouring nodes level by level and combining their results with `rule`.
+    /// `leaves.len()` must be a non-zero power of `arity`.
+    pub(crate) fn from_leaves(leaves: Vec<MembershipTrace>, arity: usize, rule: MembershipRule) -> Self {
+        let mut level = leaves;
+        while level.len() > 1 {
+            let mut parents = Vec::with_capacity(level.len() / arity);
+            let mut nodes = level.into_iter();
+            loop {
+                let children: Vec<MembershipTrace> = nodes.by_ref().take(arity).collect();
+                if children.is_empty() {
+                    break;
+                }
+                let n_bits = children.iter().map(|child| child.n_bits).sum();
+                let value = children
+                    .iter()
+                    .fold(BigUint::default(), |value, child| (value << child.n_bits) | &child.value);
+                let passed = rule.combine(children.iter(), |child| child.passed);
+                parents.push(MembershipTrace { value, n_bits, passed, children });
+            }
+            level = parents;
+        }
+        level.pop().expect("a membership trace has at least one leaf")
+    }
+
+    /// Returns `true` if this node is at the base level.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns every leaf that is not in S_base, most significant first, with its
+    /// position among the leaves.
+    pub fn failed_leaves(&self) -> Vec<ChunkFailure> {
+        let mut failures = Vec::new();
+        let mut leaf_index = 0;
+        let mut stack = alloc::vec![self];
+        while let Some(node) = stack.pop() {
+            if node.is_leaf() {
+                if !node.passed {
+                    failures.push(ChunkFailure { leaf_index, value: node.value.clone() });
+                }
+                leaf_index += 1;
+            } else {
+                stack.extend(node.children.iter().rev());
+            }
+        }
+        failures
+    }
+}
+
+/// Which values of a `PairedEntity` are members of S_N, as reported by
+/// `Propagator::is_entity_member`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityMembership {
+    /// Only `x` is a member.
+    XMember,
+    /// Only `x_prime` is a member.
+    XPrimeMember,
+    /// Both `x` and `x_prime` are members.
+    Both,
+    /// Neither value is a member.
+    Neither,
+}
+
+/// How `Propagator::repair` chooses a replacement for a chunk that is not in S_base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairStrategy {
+    /// The S_base value with the fewest differing bits; ties go to the smallest value.
+    NearestHamming,
+    /// The S_base value with the smallest absolute difference; ties go to the smaller value.
+    NearestNumeric,
+    /// The smallest S_base value, regardless of the chunk.
+    SmallestBaseValue,
+}
@@ -1,127 +1,415 @@
-use num_bigint::BigUint;
-use num_traits::One; 
-use crate::error::HierarchyError;
-
-/// Represents an N-bit Paired Entity, consisting of an N-bit value X
-/// and its bitwise complement X'.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PairedEntity {
-    /// The N-bit X-value. In canonical representations, this is often
-    /// chosen as the numerically smaller value of the pair (X, X').
-    pub x: BigUint,
-    /// The N-bit bitwise complement of X.
-    pub x_prime: BigUint,
-    /// The bit-width N of X and X'.
-    pub n_bits: usize,
-}
-
-impl PairedEntity {
-    /// Creates a new `PairedEntity` from an X-value and its bit-width N.
-    /// The complement X' is calculated automatically.
-    ///
-    /// # Arguments
-    /// * `x`: The `BigUint` X-value.
-    /// * `n_bits`: The bit-width N. Must be greater than 0.
-    ///
-    /// # Errors
-    /// Returns `HierarchyError` if:
-    /// * `n_bits` is 0.
-    /// * `x` cannot be represented within `n_bits` (i.e., x >= 2^`n_bits`).
-    pub fn new(x: BigUint, n_bits: usize) -> Result<Self, HierarchyError> {
-        if n_bits == 0 {
-            return Err(HierarchyError::NonPositiveNBits(n_bits));
-        }
-
-        let one = BigUint::one();
-        let limit_exclusive = &one << n_bits;
-        if x >= limit_exclusive {
-            return Err(HierarchyError::ValueTooLargeForNBits { value: x.clone(), n_bits });
-        }
-
-        // Calculate complement: X' = (2^N - 1) - X
-        // 2^N - 1 is a sequence of N ones.
-        // Since n_bits >= 1, limit_exclusive >= 2, so subtracting one is safe.
-        let all_ones = limit_exclusive - &one;
-        let x_prime = all_ones - &x;
-
-        Ok(PairedEntity { x, x_prime, n_bits })
-    }
-
-    /// Creates a new `PairedEntity` in its canonical form, where `x` is guaranteed
-    /// to be the numerically smaller value of the (value, complement) pair.
-    ///
-    /// # Arguments
-    /// * `value`: A `BigUint` value, which could be either X or X'.
-    /// * `n_bits`: The bit-width N. Must be greater than 0.
-    ///
-    /// # Errors
-    /// Returns `HierarchyError` if `n_bits` is 0 or `value` is too large for `n_bits`.
-    pub fn new_canonical_from_x(value: BigUint, n_bits: usize) -> Result<Self, HierarchyError> {
-        if n_bits == 0 {
-            return Err(HierarchyError::NonPositiveNBits(n_bits));
-        }
-        let one = BigUint::one();
-        let limit_exclusive = &one << n_bits;
-        if value >= limit_exclusive {
-            return Err(HierarchyError::ValueTooLargeForNBits { value: value.clone(), n_bits });
-        }
-
-        // Since n_bits >= 1, limit_exclusive >= 2, so subtracting one is safe.
-        let all_ones = limit_exclusive - &one; 
-        let complement = &all_ones - &value;
-
-        if value <= complement {
-            Ok(PairedEntity { x: value, x_prime: complement, n_bits })
-        } else {
-            Ok(PairedEntity { x: complement, x_prime: value, n_bits })
-        }
-    }
-
-    /// Creates a `PairedEntity` from two values, asserting they are complements.
-    /// This constructor is useful if X and X' are already known and their
-    /// complementarity has been verified or is trusted.
-    /// It will pick the smaller value as `self.x` for canonical representation.
-    ///
-    /// # Arguments
-    /// * `val1`: One N-bit value.
-    /// * `val2_supposed_complement`: The other N-bit value, assumed to be the complement of `val1`.
-    /// * `n_bits`: The bit-width N.
-    ///
-    /// # Errors
-    /// Returns `HierarchyError` if `n_bits` is 0, if values are too large for `n_bits`,
-    /// or if `val1` and `val2_supposed_complement` are not valid N-bit complements.
-    pub fn new_from_pair_assert_canonical(
-        val1: BigUint, 
-        val2_supposed_complement: BigUint, 
-        n_bits: usize
-    ) -> Result<Self, HierarchyError> {
-        if n_bits == 0 {
-            return Err(HierarchyError::NonPositiveNBits(n_bits));
-        }
-        let one = BigUint::one();
-        let limit_exclusive = &one << n_bits;
-
-        if val1 >= limit_exclusive {
-            return Err(HierarchyError::ValueTooLargeForNBits { value: val1.clone(), n_bits });
-        }
-        if val2_supposed_complement >= limit_exclusive {
-            return Err(HierarchyError::ValueTooLargeForNBits { value: val2_supposed_complement.clone(), n_bits });
-        }
-        
-        // Since n_bits >= 1, limit_exclusive >= 2, so subtracting one is safe.
-        let all_ones = limit_exclusive - &one;
-        if &val1 + &val2_supposed_complement != all_ones {
-            return Err(HierarchyError::NonComplementaryPair { 
-                val1: val1.clone(), 
-                val2_complement: val2_supposed_complement.clone(), 
-                n_bits 
-            });
-        }
-
-        if val1 <= val2_supposed_complement {
-            Ok(PairedEntity { x: val1, x_prime: val2_supposed_complement, n_bits })
-        } else {
-            Ok(PairedEntity { x: val2_supposed_complement, x_prime: val1, n_bits })
-        }
-    }
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::cmp::Ordering;
+use core::fmt;
+use num_bigint::BigUint;
+use num_traits::One; 
+use crate::error::HierarchyError;
+use crate::pattern::parse_hex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents an N-bit Paired Entity, consisting of an N-bit value X
+/// and its bitwise complement X'.
+///
+/// With the `serde` feature enabled, `x` and `x_prime` are serialized as decimal strings
+/// (big-endian bytes in binary formats) and `n_bits` as a number. Deserialization checks
+/// the pair with the same rules as `PairedEntity::new_from_pair_assert_canonical`, so a
+/// pair that is out of range or not complementary is rejected; the order of `x` and
+/// `x_prime` is kept as written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PairedEntityRepr", into = "PairedEntityRepr"))]
+pub struct PairedEntity {
+    /// The N-bit X-value. In canonical representations, this is often
+    /// chosen as the numerically smaller value of the pair (X, X').
+    pub x: BigUint,
+    /// The N-bit bitwise complement of X.
+    pub x_prime: BigUint,
+    /// The bit-width N of X and X'.
+    pub n_bits: usize,
+}
+
+impl PairedEntity {
+    /// Creates a new `PairedEntity` from an X-value and its bit-width N.
+    /// The complement X' is calculated automatically.
+    ///
+    /// # Arguments
+    /// * `x`: The `BigUint` X-value.
+    /// * `n_bits`: The bit-width N. Must be greater than 0.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `n_bits` is 0.
+    /// * `x` cannot be represented within `n_bits` (i.e., x >= 2^`n_bits`).
+    pub fn new(x: BigUint, n_bits: usize) -> Result<Self, HierarchyError> {
+        if n_bits == 0 {
+            return Err(HierarchyError::NonPositiveNBits(n_bits));
+        }
+
+        let one = BigUint::one();
+        let limit_exclusive = &one << n_bits;
+        if x >= limit_exclusive {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: x.clone(), n_bits });
+        }
+
+        let x_prime = complement_within(&x, n_bits);
+
+        Ok(PairedEntity { x, x_prime, n_bits })
+    }
+
+    /// Creates a new `PairedEntity` from a hexadecimal X-value (without a `0x` prefix).
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHexString` if `x_hex` is not valid hexadecimal,
+    /// or any error returned by `PairedEntity::new`.
+    pub fn from_hex(x_hex: &str, n_bits: usize) -> Result<Self, HierarchyError> {
+        Self::new(parse_hex(x_hex)?, n_bits)
+    }
+
+    /// Creates a new `PairedEntity` from a `u64` X-value, for the common case of small
+    /// values.
+    ///
+    /// ```
+    /// use paired_binary::PairedEntity;
+    ///
+    /// let entity = PairedEntity::from_u64(0b0110, 4).unwrap();
+    /// assert_eq!(entity.x_prime, 0b1001u32.into());
+    /// assert!(PairedEntity::from_u64(16, 4).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns any error returned by `PairedEntity::new`.
+    pub fn from_u64(x: u64, n_bits: usize) -> Result<Self, HierarchyError> {
+        Self::new(BigUint::from(x), n_bits)
+    }
+
+    /// Encodes the entity as `n_bits` in 4 little-endian bytes, followed by `x` in
+    /// big-endian order, zero-padded to exactly `ceil(n_bits / 8)` bytes. `x_prime` is not
+    /// stored since it follows from `x`.
+    ///
+    /// # Panics
+    /// Panics if `n_bits` does not fit in a `u32`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n_bits = u32::try_from(self.n_bits).expect("n_bits must fit in a u32 to be encoded");
+        let mut bytes = Vec::with_capacity(4 + self.n_bits.div_ceil(8));
+        bytes.extend_from_slice(&n_bits.to_le_bytes());
+        bytes.extend_from_slice(&to_fixed_bytes_be(&self.x, self.n_bits));
+        bytes
+    }
+
+    /// Decodes an entity written by `to_bytes`, recomputing `x_prime`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MalformedBytes` if `bytes` is shorter than the 4-byte
+    /// length prefix or its length does not match the encoded `n_bits`, or any error
+    /// returned by `PairedEntity::new`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HierarchyError> {
+        let (prefix, value_bytes) = bytes
+            .split_first_chunk::<4>()
+            .ok_or(HierarchyError::MalformedBytes { expected_len: 4, actual_len: bytes.len() })?;
+        let n_bits = u32::from_le_bytes(*prefix) as usize;
+        let expected_len = 4 + n_bits.div_ceil(8);
+        if bytes.len() != expected_len {
+            return Err(HierarchyError::MalformedBytes { expected_len, actual_len: bytes.len() });
+        }
+        Self::new(BigUint::from_bytes_be(value_bytes), n_bits)
+    }
+
+    /// Returns `(x, x_prime)`.
+    pub fn pair(&self) -> (&BigUint, &BigUint) {
+        (&self.x, &self.x_prime)
+    }
+
+    /// Returns `true` if `x` is the smaller value of the pair, as produced by the
+    /// canonical constructors. `PairedEntity::new` keeps `x` as given, so its result may
+    /// not be canonical.
+    pub fn is_canonical(&self) -> bool {
+        self.x <= self.x_prime
+    }
+
+    /// Returns the entity in canonical form, swapping `x` and `x_prime` if `x` is the
+    /// larger value. No values are reallocated.
+    pub fn into_canonical(mut self) -> Self {
+        if !self.is_canonical() {
+            core::mem::swap(&mut self.x, &mut self.x_prime);
+        }
+        self
+    }
+
+    /// Returns the bitwise complement of `value` within exactly this entity's `n_bits`,
+    /// i.e. `(2^n_bits - 1) - value`. Only the low `n_bits` bits are flipped, so the
+    /// result also fits in `n_bits`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::ValueTooLargeForNBits` if `value` does not fit in `n_bits`.
+    pub fn complement_of(&self, value: &BigUint) -> Result<BigUint, HierarchyError> {
+        if *value >= BigUint::one() << self.n_bits {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: value.clone(), n_bits: self.n_bits });
+        }
+        Ok(complement_within(value, self.n_bits))
+    }
+
+    /// Creates a new `PairedEntity` in its canonical form, where `x` is guaranteed
+    /// to be the numerically smaller value of the (value, complement) pair.
+    ///
+    /// # Arguments
+    /// * `value`: A `BigUint` value, which could be either X or X'.
+    /// * `n_bits`: The bit-width N. Must be greater than 0.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if `n_bits` is 0 or `value` is too large for `n_bits`.
+    pub fn new_canonical_from_x(value: BigUint, n_bits: usize) -> Result<Self, HierarchyError> {
+        if n_bits == 0 {
+            return Err(HierarchyError::NonPositiveNBits(n_bits));
+        }
+        let one = BigUint::one();
+        let limit_exclusive = &one << n_bits;
+        if value >= limit_exclusive {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: value.clone(), n_bits });
+        }
+
+        // Since n_bits >= 1, limit_exclusive >= 2, so subtracting one is safe.
+        let all_ones = limit_exclusive - &one; 
+        let complement = &all_ones - &value;
+
+        if value <= complement {
+            Ok(PairedEntity { x: value, x_prime: complement, n_bits })
+        } else {
+            Ok(PairedEntity { x: complement, x_prime: value, n_bits })
+        }
+    }
+
+    /// Creates a `PairedEntity` from two values, asserting they are complements.
+    /// This constructor is useful if X and X' are already known and their
+    /// complementarity has been verified or is trusted.
+    /// It will pick the smaller value as `self.x` for canonical representation.
+    ///
+    /// # Arguments
+    /// * `val1`: One N-bit value.
+    /// * `val2_supposed_complement`: The other N-bit value, assumed to be the complement of `val1`.
+    /// * `n_bits`: The bit-width N.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if `n_bits` is 0, if values are too large for `n_bits`,
+    /// or if `val1` and `val2_supposed_complement` are not valid N-bit complements.
+    pub fn new_from_pair_assert_canonical(
+        val1: BigUint, 
+        val2_supposed_complement: BigUint, 
+        n_bits: usize
+    ) -> Result<Self, HierarchyError> {
+        if n_bits == 0 {
+            return Err(HierarchyError::NonPositiveNBits(n_bits));
+        }
+        let one = BigUint::one();
+        let limit_exclusive = &one << n_bits;
+
+        if val1 >= limit_exclusive {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: val1.clone(), n_bits });
+        }
+        if val2_supposed_complement >= limit_exclusive {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: val2_supposed_complement.clone(), n_bits });
+        }
+        
+        // Since n_bits >= 1, limit_exclusive >= 2, so subtracting one is safe.
+        let all_ones = limit_exclusive - &one;
+        if &val1 + &val2_supposed_complement != all_ones {
+            return Err(HierarchyError::NonComplementaryPair { 
+                val1: val1.clone(), 
+                val2_complement: val2_supposed_complement.clone(), 
+                n_bits 
+            });
+        }
+
+        if val1 <= val2_supposed_complement {
+            Ok(PairedEntity { x: val1, x_prime: val2_supposed_complement, n_bits })
+        } else {
+            Ok(PairedEntity { x: val2_supposed_complement, x_prime: val1, n_bits })
+        }
+    }
+
+    /// Returns the number of set bits in `x`.
+    ///
+    /// Since X' flips exactly the `n_bits` bits of X, the weights of `x` and `x_prime`
+    /// always sum to `n_bits`.
+    pub fn hamming_weight(&self) -> u64 {
+        let weight = self.x.count_ones();
+        debug_assert_eq!(
+            weight + self.x_prime.count_ones(),
+            self.n_bits as u64,
+            "x and x_prime must be n_bits complements"
+        );
+        weight
+    }
+
+    /// Returns the canonical entity whose X-value is the bitwise AND of the `x` fields of
+    /// `self` and `other`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MismatchedNBits` unless both entities have the same `n_bits`.
+    pub fn and(&self, other: &PairedEntity) -> Result<PairedEntity, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs & rhs)
+    }
+
+    /// Returns the canonical entity whose X-value is the bitwise OR of the `x` fields of
+    /// `self` and `other`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MismatchedNBits` unless both entities have the same `n_bits`.
+    pub fn or(&self, other: &PairedEntity) -> Result<PairedEntity, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs | rhs)
+    }
+
+    /// Returns the canonical entity whose X-value is the bitwise XOR of the `x` fields of
+    /// `self` and `other`. XOR-ing X with its own complement gives all ones, i.e. the
+    /// canonical entity with `x == 0`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MismatchedNBits` unless both entities have the same `n_bits`.
+    pub fn xor(&self, other: &PairedEntity) -> Result<PairedEntity, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs ^ rhs)
+    }
+
+    /// Applies `op` to the `x` fields of two entities of the same width and builds the
+    /// canonical entity of the result, which always fits in `n_bits`.
+    fn combine<F>(&self, other: &PairedEntity, op: F) -> Result<PairedEntity, HierarchyError>
+    where
+        F: FnOnce(&BigUint, &BigUint) -> BigUint,
+    {
+        if self.n_bits != other.n_bits {
+            return Err(HierarchyError::MismatchedNBits { lhs: self.n_bits, rhs: other.n_bits });
+        }
+        PairedEntity::new_canonical_from_x(op(&self.x, &other.x), self.n_bits)
+    }
+
+    /// Returns the entity whose X-value is `x` with its `n_bits` bits in reverse order,
+    /// built with `PairedEntity::new`. Leading zeros within `n_bits` become trailing zeros.
+    pub fn reverse_bits(&self) -> Self {
+        let mut reversed = BigUint::default();
+        for bit in 0..self.n_bits as u64 {
+            if self.x.bit(bit) {
+                reversed.set_bit(self.n_bits as u64 - 1 - bit, true);
+            }
+        }
+        self.with_x(reversed)
+    }
+
+    /// Returns the entity whose X-value is `x` rotated left by `k` bits within the
+    /// `n_bits` window, built with `PairedEntity::new`. Bits shifted out at the top
+    /// re-enter at the bottom; `k` may exceed `n_bits`.
+    pub fn rotate_left(&self, k: usize) -> Self {
+        let k = k % self.n_bits;
+        let all_ones = (BigUint::one() << self.n_bits) - BigUint::one();
+        let rotated = ((&self.x << k) & &all_ones) | (&self.x >> (self.n_bits - k));
+        self.with_x(rotated)
+    }
+
+    /// Returns the entity whose X-value is `x` rotated right by `k` bits within the
+    /// `n_bits` window, the inverse of `rotate_left`.
+    pub fn rotate_right(&self, k: usize) -> Self {
+        self.rotate_left(self.n_bits - k % self.n_bits)
+    }
+
+    /// Builds the entity of the same width for `x`, which must fit in `n_bits`.
+    fn with_x(&self, x: BigUint) -> Self {
+        PairedEntity::new(x, self.n_bits).expect("the value stays within the entity's n_bits")
+    }
+
+    /// Returns X and X' as binary strings zero-padded to exactly `n_bits` digits.
+    pub fn to_binary_string(&self) -> (String, String) {
+        (
+            format!("{:0width$b}", self.x, width = self.n_bits),
+            format!("{:0width$b}", self.x_prime, width = self.n_bits),
+        )
+    }
+}
+
+/// Returns the number of bit positions in which `a` and `b` differ.
+pub fn hamming_distance(a: &BigUint, b: &BigUint) -> u64 {
+    (a ^ b).count_ones()
+}
+
+/// Encodes `value` in big-endian order, zero-padded to exactly `ceil(n_bits / 8)` bytes.
+/// `value` must already fit in `n_bits`.
+pub(crate) fn to_fixed_bytes_be(value: &BigUint, n_bits: usize) -> Vec<u8> {
+    let value_bytes = value.to_bytes_be();
+    let mut bytes = vec![0; n_bits.div_ceil(8) - value_bytes.len()];
+    bytes.extend_from_slice(&value_bytes);
+    bytes
+}
+
+/// Calculates the complement X' = (2^N - 1) - X. `value` must already fit in `n_bits`.
+fn complement_within(value: &BigUint, n_bits: usize) -> BigUint {
+    // 2^N - 1 is a sequence of N ones.
+    let one = BigUint::one();
+    let all_ones = (&one << n_bits) - &one;
+    all_ones - value
+}
+
+/// Orders entities by `n_bits`, then by `x`.
+///
+/// For entities built by the canonical constructors `x` is the smaller value of the pair,
+/// so this is a stable total order on pairs. `PairedEntity::new` does not canonicalize,
+/// so for such entities the raw `x` is compared. `x_prime` is only compared last, to keep
+/// the order consistent with `Eq` for entities whose fields were set by hand.
+impl Ord for PairedEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.n_bits
+            .cmp(&other.n_bits)
+            .then_with(|| self.x.cmp(&other.x))
+            .then_with(|| self.x_prime.cmp(&other.x_prime))
+    }
+}
+
+impl PartialOrd for PairedEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Formats the entity as `PairedEntity(n=4, x=0011 (3), x'=1100 (12))`, with both
+/// values shown as `n_bits`-wide binary followed by their decimal value.
+impl fmt::Display for PairedEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x_bits, x_prime_bits) = self.to_binary_string();
+        write!(
+            f,
+            "PairedEntity(n={}, x={} ({}), x'={} ({}))",
+            self.n_bits, x_bits, self.x, x_prime_bits, self.x_prime
+        )
+    }
+}
+
+/// The serialized form of a `PairedEntity`.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PairedEntityRepr {
+    #[serde(with = "crate::serde_support::biguint")]
+    x: BigUint,
+    #[serde(with = "crate::serde_support::biguint")]
+    x_prime: BigUint,
+    n_bits: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<PairedEntity> for PairedEntityRepr {
+    fn from(entity: PairedEntity) -> Self {
+        Self { x: entity.x, x_prime: entity.x_prime, n_bits: entity.n_bits }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PairedEntityRepr> for PairedEntity {
+    type Error = HierarchyError;
+
+    fn try_from(repr: PairedEntityRepr) -> Result<Self, Self::Error> {
+        let x_is_larger = repr.x > repr.x_prime;
+        let mut entity = PairedEntity::new_from_pair_assert_canonical(repr.x, repr.x_prime, repr.n_bits)?;
+        if x_is_larger {
+            core::mem::swap(&mut entity.x, &mut entity.x_prime);
+        }
+        Ok(entity)
+    }
 }
\ No newline at end of file
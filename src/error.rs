@@ -1,65 +1,197 @@
-use thiserror::Error;
-use num_bigint::BigUint;
-
-/// Custom error types for the hierarchical_info library.
-#[derive(Error, Debug, PartialEq, Eq, Clone)]
-pub enum HierarchyError {
-    /// Error indicating that an N-bits specification (e.g., for n_base_bits or n_target_bits)
-    /// is zero, which is invalid for most operations requiring positive bit width.
-    #[error("N-bits value ({0}) must be positive.")]
-    NonPositiveNBits(usize),
-
-    /// Error indicating that the set of base values for an InitialPattern is empty.
-    /// An initial pattern must contain at least one value.
-    #[error("S_base_values set cannot be empty.")]
-    EmptySBaseValues,
-
-    /// Error indicating that a value provided for S_base (InitialPattern)
-    /// is too large to be represented by n_base_bits.
-    #[error("S_base value {value} (decimal) does not fit within n_base_bits {n_bits}. Maximum representable value is {max_val} (decimal).")]
-    ValueExceedsNBaseBits { value: BigUint, n_bits: usize, max_val: BigUint },
-    
-    /// Error indicating that the target N-bits for an operation (e.g., is_member, decompose)
-    /// is smaller than the N-bits of the base pattern.
-    #[error("Target N-bits ({target_n_bits}) is smaller than base N-bits ({base_n_bits}).")]
-    TargetNBitsTooSmall { target_n_bits: usize, base_n_bits: usize },
-
-    /// Error indicating that the target N-bits is not a valid hierarchical level
-    /// derivable from the base N-bits by the rule N_target = N_base * 2^k.
-    #[error("Target N-bits ({target_n_bits}) is not a valid hierarchical level from base N-bits ({base_n_bits}). Must be base_n_bits * 2^k for some integer k >= 0.")]
-    InvalidHierarchicalLevel { target_n_bits: usize, base_n_bits: usize },
-
-    /// Error indicating that an input X value is too large to be represented
-    /// by the specified number of bits (n_bits).
-    /// An N-bit number must be less than 2^N.
-    #[error("Input X value {value} (decimal) is too large for specified n_bits {n_bits}. Value must be < 2^{n_bits}.")]
-    ValueTooLargeForNBits { value: BigUint, n_bits: usize },
-
-    /// Error indicating that an input X value is not a member of the
-    /// selected set S_N for the given InitialPattern and target N-bits.
-    #[error("Input X value {0} (decimal) is not a member of the selected set S_N for the given N-bits and initial pattern.")]
-    NotAMember(BigUint),
-
-    /// Error indicating that a component provided for composition
-    /// is not a valid member of the initial S_base pattern.
-    #[error("Base component {0} (decimal) is not a valid member of the initial S_base pattern.")]
-    InvalidBaseComponent(BigUint),
-
-    /// Error indicating that the number of base components provided for composition
-    /// is not a non-zero power of 2, which is required for hierarchical composition.
-    #[error("Number of base components ({0}) must be a non-zero power of 2 (e.g., 1, 2, 4, 8...).")]
-    InvalidComponentCount(usize),
-
-    /// Error indicating that a value cannot be decomposed further because its half bit-width
-    /// would be smaller than the base pattern's bit-width.
-    #[error("Cannot decompose further: half N-bits ({half_n_bits}) is smaller than base N-bits ({base_n_bits}).")]
-    DecompositionLimitReached { half_n_bits: usize, base_n_bits: usize },
-
-    /// Error indicating that a pair of values provided to create a PairedEntity
-    /// are not bitwise complements for the specified n_bits.
-    #[error("Values {val1} (decimal) and {val2_complement} (decimal) are not N-bit complements for n_bits = {n_bits}. Their sum should be 2^{n_bits} - 1.")]
-    NonComplementaryPair { val1: BigUint, val2_complement: BigUint, n_bits: usize },
-
-    #[error("Cannot generate random member: S_base pattern is empty (should be caught by InitialPattern::new).")]
-    EmptySBaseForRandomGeneration, // For random generation specifically
+use alloc::string::String;
+use thiserror::Error;
+use num_bigint::BigUint;
+use crate::propagator::{MembershipRule, SplitStrategy};
+
+/// Custom error types for the hierarchical_info library.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum HierarchyError {
+    /// Error indicating that an N-bits specification (e.g., for n_base_bits or n_target_bits)
+    /// is zero, which is invalid for most operations requiring positive bit width.
+    #[error("N-bits value ({0}) must be positive.")]
+    NonPositiveNBits(usize),
+
+    /// Error indicating that the set of base values for an InitialPattern is empty.
+    /// An initial pattern must contain at least one value.
+    #[error("S_base_values set cannot be empty.")]
+    EmptySBaseValues,
+
+    /// Error indicating that a value provided for S_base (InitialPattern)
+    /// is too large to be represented by n_base_bits.
+    #[error("S_base value {value} (decimal) does not fit within n_base_bits {n_bits}. Maximum representable value is {max_val} (decimal).")]
+    ValueExceedsNBaseBits { value: BigUint, n_bits: usize, max_val: BigUint },
+    
+    /// Error indicating that the target N-bits for an operation (e.g., is_member, decompose)
+    /// is smaller than the N-bits of the base pattern.
+    #[error("Target N-bits ({target_n_bits}) is smaller than base N-bits ({base_n_bits}).")]
+    TargetNBitsTooSmall { target_n_bits: usize, base_n_bits: usize },
+
+    /// Error indicating that the intermediate level to stop a decomposition at is wider
+    /// than the level being decomposed.
+    #[error("Stop level N-bits ({stop_n_bits}) is larger than target N-bits ({target_n_bits}).")]
+    StopLevelTooLarge { stop_n_bits: usize, target_n_bits: usize },
+
+    /// Error indicating that the target N-bits is not a valid hierarchical level
+    /// derivable from the base N-bits by the rule N_target = N_base * m^k, where m is the
+    /// propagator's arity (2 by default).
+    #[error("Target N-bits ({target_n_bits}) is not a valid hierarchical level from base N-bits ({base_n_bits}). Must be base_n_bits * m^k for some integer k >= 0, where m is the arity (2 by default).")]
+    InvalidHierarchicalLevel { target_n_bits: usize, base_n_bits: usize },
+
+    /// Error indicating that an input X value is too large to be represented
+    /// by the specified number of bits (n_bits).
+    /// An N-bit number must be less than 2^N.
+    #[error("Input X value {value} (decimal) is too large for specified n_bits {n_bits}. Value must be < 2^{n_bits}.")]
+    ValueTooLargeForNBits { value: BigUint, n_bits: usize },
+
+    /// Error indicating that an input X value is not a member of the
+    /// selected set S_N for the given InitialPattern and target N-bits.
+    #[error("Input X value {0} (decimal) is not a member of the selected set S_N for the given N-bits and initial pattern.")]
+    NotAMember(BigUint),
+
+    /// Error indicating that a component provided for composition
+    /// is not a valid member of the initial S_base pattern.
+    #[error("Base component {0} (decimal) is not a valid member of the initial S_base pattern.")]
+    InvalidBaseComponent(BigUint),
+
+    /// Error indicating that the number of base components provided for composition
+    /// is not a non-zero power of the arity (2 by default), which is required for
+    /// hierarchical composition.
+    #[error("Number of base components ({0}) must be a non-zero power of the arity (e.g., 1, 2, 4, 8... for the default arity 2).")]
+    InvalidComponentCount(usize),
+
+    /// Error indicating that a value cannot be decomposed further because its half bit-width
+    /// would be smaller than the base pattern's bit-width.
+    #[error("Cannot decompose further: half N-bits ({half_n_bits}) is smaller than base N-bits ({base_n_bits}).")]
+    DecompositionLimitReached { half_n_bits: usize, base_n_bits: usize },
+
+    /// Error indicating that a pair of values provided to create a PairedEntity
+    /// are not bitwise complements for the specified n_bits.
+    #[error("Values {val1} (decimal) and {val2_complement} (decimal) are not N-bit complements for n_bits = {n_bits}. Their sum should be 2^{n_bits} - 1.")]
+    NonComplementaryPair { val1: BigUint, val2_complement: BigUint, n_bits: usize },
+
+    /// Error indicating that an index into the ordered enumeration of S_N
+    /// is not smaller than the number of members at that level.
+    #[error("Index {index} (decimal) is out of range for a selected set with {count} members.")]
+    IndexOutOfRange { index: BigUint, count: BigUint },
+
+    /// Error indicating that more distinct members were requested than
+    /// the selected set S_N contains.
+    #[error("Requested {requested} distinct members but the selected set only has {available}.")]
+    NotEnoughDistinctMembers { requested: usize, available: BigUint },
+
+    /// Error indicating that an `InitialPatternBuilder` was built without setting
+    /// n_base_bits.
+    #[error("n_base_bits must be set before building an InitialPattern.")]
+    MissingNBaseBits,
+
+    /// Error indicating that a string could not be parsed as a hexadecimal number.
+    #[error("'{0}' is not a valid hexadecimal number.")]
+    InvalidHexString(String),
+
+    /// Error indicating that a byte encoding of a PairedEntity is truncated or its
+    /// length does not match the encoded n_bits.
+    #[error("Malformed PairedEntity bytes: expected {expected_len} bytes, got {actual_len}.")]
+    MalformedBytes { expected_len: usize, actual_len: usize },
+
+    /// Error indicating that a selected set has more members than the caller allowed
+    /// to be materialized.
+    #[error("The selected set has {count} members, more than the allowed maximum of {max_members}.")]
+    TooManyMembers { count: BigUint, max_members: usize },
+
+    /// Error indicating that two PairedEntities or InitialPatterns of different
+    /// bit-widths were combined.
+    #[error("Cannot combine operands of different widths: {lhs} bits and {rhs} bits.")]
+    MismatchedNBits { lhs: usize, rhs: usize },
+
+    /// Error indicating that a PairedEntity was checked at a level other than its own
+    /// bit-width.
+    #[error("Entity of {entity_n_bits} bits cannot be checked at a level of {target_n_bits} bits.")]
+    EntityWidthMismatch { entity_n_bits: usize, target_n_bits: usize },
+
+    /// Error indicating that two propagators over patterns of different base widths were
+    /// combined.
+    #[error("Cannot combine propagators with different base widths: {lhs} bits and {rhs} bits.")]
+    MismatchedBaseBits { lhs: usize, rhs: usize },
+
+    /// Error indicating that two propagators of different arities were combined.
+    #[error("Cannot combine propagators with different arities: {lhs} and {rhs}.")]
+    MismatchedArity { lhs: usize, rhs: usize },
+
+    /// Error indicating that two propagators with different membership rules were
+    /// combined.
+    #[error("Cannot combine propagators with different membership rules: {lhs:?} and {rhs:?}.")]
+    MismatchedRule { lhs: MembershipRule, rhs: MembershipRule },
+
+    /// Error indicating that two propagators with different split strategies were
+    /// combined.
+    #[error("Cannot combine propagators with different split strategies: {lhs:?} and {rhs:?}.")]
+    MismatchedSplitStrategy { lhs: SplitStrategy, rhs: SplitStrategy },
+
+    /// Error indicating that a propagator was configured with an arity below 2.
+    #[error("Arity {0} is invalid. Each level must split into at least 2 parts.")]
+    InvalidArity(usize),
+
+    /// Error indicating that an operation producing an upper/lower binary structure
+    /// was used with a propagator whose arity is not 2.
+    #[error("This operation requires arity 2, but the propagator has arity {0}.")]
+    UnsupportedArity(usize),
+
+    /// Error indicating that an operation relying on members factoring into S_base
+    /// components, such as composing or decomposing them, was used under a membership
+    /// rule other than `BothHalves`.
+    #[error("Composing or decomposing members by S_base components is undefined under the {0:?} membership rule.")]
+    DecompositionUndefinedForRule(MembershipRule),
+
+    /// Error indicating that an operation relying on members occupying consecutive bit
+    /// windows, such as numeric ordering or bit offsets, was used under a split strategy
+    /// other than `HighLow`.
+    #[error("This operation relies on consecutive bit windows and is undefined under the {0:?} split strategy.")]
+    UnsupportedSplitStrategy(SplitStrategy),
+
+    /// Error indicating that a machine-integer operation was used at a level wider than
+    /// the integer type.
+    #[error("Level of {n_bits} bits does not fit in a {max_bits}-bit machine integer.")]
+    LevelExceedsMachineWord { n_bits: usize, max_bits: usize },
+
+    /// Error indicating that more prefix components were given than the level has
+    /// S_base components.
+    #[error("Prefix of {prefix_len} components is longer than the {component_count} components of the level.")]
+    PrefixTooLong { prefix_len: usize, component_count: usize },
+
+    /// Error indicating that composing S_base components and decomposing the result did
+    /// not give the components back, which indicates a bug in the library.
+    #[error("Composing {n_bits}-bit value {composed} (decimal) from S_base components did not round-trip through decomposition.")]
+    RoundTripMismatch { composed: BigUint, n_bits: usize },
+
+    /// Error indicating that a value composed from valid S_base components failed the
+    /// membership check, which indicates a bug in the library.
+    #[error("Composed value {value} (decimal) is not a member of the selected set at {n_bits} bits.")]
+    ComposedValueNotMember { value: BigUint, n_bits: usize },
+
+    /// Error indicating that a leaf index does not address one of the S_base components
+    /// of a level.
+    #[error("Leaf index {leaf_index} is out of range for a level of {component_count} components.")]
+    LeafIndexOutOfRange { leaf_index: usize, component_count: usize },
+
+    /// Error indicating that the same leaf index was constrained more than once.
+    #[error("Leaf index {0} is constrained more than once.")]
+    DuplicateLeafConstraint(usize),
+
+    /// Error indicating that S_base holds every value of its width, so every value at
+    /// every level is a member and no non-member exists.
+    #[error("S_base holds all 2^{n_base_bits} values of {n_base_bits} bits, so the selected sets have no non-members.")]
+    SetIsComplete { n_base_bits: usize },
+
+    /// Error indicating that the selected set has no members at a level, which can happen
+    /// under `MembershipRule::ExactlyOne` when S_base holds every value of its width.
+    #[error("The selected set has no members at {n_bits} bits.")]
+    NoMembers { n_bits: usize },
+
+    /// Error indicating that a JSON propagator configuration could not be parsed. Carries
+    /// the parser's message.
+    #[error("Invalid JSON configuration: {0}")]
+    JsonParseError(String),
+
+    #[error("Cannot generate random member: S_base pattern is empty (should be caught by InitialPattern::new).")]
+    EmptySBaseForRandomGeneration, // For random generation specifically
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use num_bigint::BigUint;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+/// Lazily enumerates the members of a selected set S_N in ascending numeric order.
+///
+/// Every S_N member is a sequence of S_base components (most significant first).
+/// The iterator walks these sequences like an odometer over the sorted base values,
+/// so it only ever holds one digit per component plus the current member.
+/// `Propagator::iter_members_matching` pins some components, which the odometer then skips.
+#[derive(Debug, Clone)]
+pub struct MemberIter {
+    sorted_base_values: Vec<BigUint>,
+    n_base_bits: usize,
+    /// Index into `sorted_base_values` for each component, most significant first.
+    digits: Vec<usize>,
+    /// Whether each component is pinned to its initial digit and skipped when advancing.
+    pinned: Vec<bool>,
+    /// The member that will be yielded next, or `None` once the set is exhausted.
+    next_value: Option<BigUint>,
+    /// Number of members not yet yielded, or `None` if it does not fit in a `usize`.
+    remaining: Option<usize>,
+}
+
+impl MemberIter {
+    /// Creates an iterator over all compositions of `component_count` components drawn
+    /// from `sorted_base_values`, which must be sorted ascending and non-empty.
+    pub(crate) fn new(sorted_base_values: Vec<BigUint>, n_base_bits: usize, component_count: usize) -> Self {
+        Self::with_pinned(sorted_base_values, n_base_bits, component_count, &[])
+    }
+
+    /// Like `new`, but every `(position, digit)` in `pins` fixes the component at
+    /// `position` to `sorted_base_values[digit]`, so only the other components vary.
+    /// Positions must be distinct and smaller than `component_count`, and digits must
+    /// index `sorted_base_values`.
+    pub(crate) fn with_pinned(sorted_base_values: Vec<BigUint>, n_base_bits: usize, component_count: usize, pins: &[(usize, usize)]) -> Self {
+        assert!(!sorted_base_values.is_empty(), "S_base_values cannot be empty due to InitialPattern::new");
+
+        let mut digits = vec![0; component_count];
+        let mut pinned = vec![false; component_count];
+        for &(position, digit) in pins {
+            digits[position] = digit;
+            pinned[position] = true;
+        }
+
+        let mut first = BigUint::default();
+        for &digit in &digits {
+            first = (first << n_base_bits) | &sorted_base_values[digit];
+        }
+
+        let remaining = u32::try_from(component_count - pins.len())
+            .ok()
+            .and_then(|exponent| sorted_base_values.len().checked_pow(exponent));
+
+        Self {
+            sorted_base_values,
+            n_base_bits,
+            digits,
+            pinned,
+            next_value: Some(first),
+            remaining,
+        }
+    }
+
+    /// Bit offset of the component at `position` (0 = most significant).
+    fn shift_for(&self, position: usize) -> usize {
+        (self.digits.len() - 1 - position) * self.n_base_bits
+    }
+
+    /// Advances the odometer by one, updating `value` in place.
+    /// Returns `false` if the odometer wrapped around, i.e. the set is exhausted.
+    fn advance(&mut self, value: &mut BigUint) -> bool {
+        let radix = self.sorted_base_values.len();
+        for position in (0..self.digits.len()).rev() {
+            if self.pinned[position] {
+                continue;
+            }
+            let shift = self.shift_for(position);
+            let old_digit = self.digits[position];
+            let new_digit = if old_digit + 1 < radix { old_digit + 1 } else { 0 };
+
+            // Add before subtracting so the intermediate value never underflows.
+            *value += &self.sorted_base_values[new_digit] << shift;
+            *value -= &self.sorted_base_values[old_digit] << shift;
+            self.digits[position] = new_digit;
+
+            if new_digit != 0 {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for MemberIter {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let current = self.next_value.take()?;
+        let mut successor = current.clone();
+        if self.advance(&mut successor) {
+            self.next_value = Some(successor);
+        }
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    /// Exact when the number of remaining members fits in a `usize`.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for MemberIter {}
@@ -1,10 +1,33 @@
-pub mod error;
-pub mod pattern;
-pub mod entity; 
-pub mod propagator;
-pub mod wasm_api;
-
-pub use error::HierarchyError;
-pub use pattern::InitialPattern;
-pub use entity::PairedEntity;
-pub use propagator::Propagator;
\ No newline at end of file
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("paired_binary requires either the `std` or the `alloc` feature.");
+
+extern crate alloc;
+
+pub mod error;
+pub mod pattern;
+pub mod entity; 
+pub mod propagator;
+pub mod iter;
+pub mod cache;
+mod chunks;
+pub mod tree;
+pub mod diagnostics;
+#[cfg(feature = "serde")]
+mod serde_support;
+
+// Without `std`, hash-based collections come from hashbrown instead.
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap, HashSet};
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet};
+
+pub use error::HierarchyError;
+pub use pattern::{InitialPattern, InitialPatternBuilder};
+pub use entity::{hamming_distance, PairedEntity};
+pub use propagator::{MembershipRule, PadSide, Propagator, SplitStrategy};
+pub use iter::MemberIter;
+pub use cache::{CachedPropagator, CacheStats};
+pub use tree::{ComponentAt, DecompNode};
+pub use diagnostics::{ChunkDiff, ChunkFailure, ComponentValidation, EntityMembership, MembershipExplanation, MembershipReport, MembershipTrace, MembershipWitness, RepairStrategy};
\ No newline at end of file
@@ -1,57 +1,301 @@
-use std::collections::HashSet;
-use num_bigint::BigUint;
-use num_traits::One; 
-use crate::error::HierarchyError;
-
-/// Represents the initial pattern (S_base) at a specific bit-width (N_base).
-/// This pattern is the seed for generating hierarchical structures at higher N-levels.
-#[derive(Debug, Clone)]
-pub struct InitialPattern {
-    /// The set of X-values (as BigUint) that constitute the base pattern.
-    /// These are typically the numerically smaller values of canonical Paired Entities.
-    pub s_base_values: HashSet<BigUint>,
-    /// The bit-width (N) of the X-values in `s_base_values`.
-    pub n_base_bits: usize,
-}
-
-impl InitialPattern {
-    /// Creates a new `InitialPattern`.
-    ///
-    /// # Arguments
-    /// * `s_base_values`: A set of `BigUint` X-values for the base pattern.
-    /// * `n_base_bits`: The bit-width N for these base X-values.
-    ///
-    /// # Errors
-    /// Returns `HierarchyError` if:
-    /// * `n_base_bits` is 0.
-    /// * `s_base_values` is empty.
-    /// * Any value in `s_base_values` cannot be represented within `n_base_bits`
-    ///   (i.e., value >= 2^`n_base_bits`).
-    pub fn new(s_base_values: HashSet<BigUint>, n_base_bits: usize) -> Result<Self, HierarchyError> {
-        if n_base_bits == 0 {
-            return Err(HierarchyError::NonPositiveNBits(n_base_bits));
-        }
-        if s_base_values.is_empty() {
-            return Err(HierarchyError::EmptySBaseValues);
-        }
-
-        let one = BigUint::one();
-        // limit_exclusive represents 2^n_base_bits.
-        // Values in s_base_values must be < limit_exclusive.
-        let limit_exclusive = &one << n_base_bits;
-
-        for val in &s_base_values {
-            if *val >= limit_exclusive {
-                // max_representable_value is 2^n_base_bits - 1.
-                // Since n_base_bits >= 1, limit_exclusive >= 2, so subtracting 1 is safe.
-                let max_representable_value = limit_exclusive - &one;
-                return Err(HierarchyError::ValueExceedsNBaseBits {
-                    value: val.clone(),
-                    n_bits: n_base_bits,
-                    max_val: max_representable_value,
-                });
-            }
-        }
-        Ok(Self { s_base_values, n_base_bits })
-    }
+use alloc::collections::BTreeSet;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_traits::One; 
+use crate::error::HierarchyError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents the initial pattern (S_base) at a specific bit-width (N_base).
+/// This pattern is the seed for generating hierarchical structures at higher N-levels.
+///
+/// With the `serde` feature enabled, `s_base_values` is serialized as a sorted array of
+/// decimal strings (big-endian bytes in binary formats) and `n_base_bits` as a number.
+/// Deserialization goes through `InitialPattern::new`, so a pattern that fails its
+/// validation is rejected.
+///
+/// Two patterns are equal if they have the same `n_base_bits` and the same set of values,
+/// however the set was built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "InitialPatternRepr", into = "InitialPatternRepr"))]
+pub struct InitialPattern {
+    /// The set of X-values (as BigUint) that constitute the base pattern, in ascending
+    /// order. These are typically the numerically smaller values of canonical Paired
+    /// Entities. The set type is the same whichever features are enabled.
+    pub s_base_values: BTreeSet<BigUint>,
+    /// The bit-width (N) of the X-values in `s_base_values`.
+    pub n_base_bits: usize,
+}
+
+impl InitialPattern {
+    /// Creates a new `InitialPattern`.
+    ///
+    /// # Arguments
+    /// * `s_base_values`: A set of `BigUint` X-values for the base pattern.
+    /// * `n_base_bits`: The bit-width N for these base X-values.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `n_base_bits` is 0.
+    /// * `s_base_values` is empty.
+    /// * Any value in `s_base_values` cannot be represented within `n_base_bits`
+    ///   (i.e., value >= 2^`n_base_bits`).
+    pub fn new(s_base_values: BTreeSet<BigUint>, n_base_bits: usize) -> Result<Self, HierarchyError> {
+        if n_base_bits == 0 {
+            return Err(HierarchyError::NonPositiveNBits(n_base_bits));
+        }
+        if s_base_values.is_empty() {
+            return Err(HierarchyError::EmptySBaseValues);
+        }
+
+        let one = BigUint::one();
+        // limit_exclusive represents 2^n_base_bits.
+        // Values in s_base_values must be < limit_exclusive.
+        let limit_exclusive = &one << n_base_bits;
+
+        for val in &s_base_values {
+            if *val >= limit_exclusive {
+                // max_representable_value is 2^n_base_bits - 1.
+                // Since n_base_bits >= 1, limit_exclusive >= 2, so subtracting 1 is safe.
+                let max_representable_value = limit_exclusive - &one;
+                return Err(HierarchyError::ValueExceedsNBaseBits {
+                    value: val.clone(),
+                    n_bits: n_base_bits,
+                    max_val: max_representable_value,
+                });
+            }
+        }
+        Ok(Self { s_base_values, n_base_bits })
+    }
+
+    /// Creates a new `InitialPattern` from `u64` values, for the common case of small
+    /// base values. Duplicate values are merged.
+    ///
+    /// ```
+    /// use paired_binary::InitialPattern;
+    ///
+    /// let pattern = InitialPattern::from_u64_values(&[1, 2, 5], 3).unwrap();
+    /// assert_eq!(pattern.s_base_values.len(), 3);
+    /// assert!(InitialPattern::from_u64_values(&[1, 8], 3).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns any error returned by `InitialPattern::new`, in particular
+    /// `ValueExceedsNBaseBits` for a value that does not fit in `n_base_bits`.
+    pub fn from_u64_values(values: &[u64], n_base_bits: usize) -> Result<Self, HierarchyError> {
+        Self::new(values.iter().copied().map(BigUint::from).collect(), n_base_bits)
+    }
+
+    /// Returns an `InitialPatternBuilder` with no values and `n_base_bits` unset.
+    pub fn builder() -> InitialPatternBuilder {
+        InitialPatternBuilder::default()
+    }
+
+    /// Creates a new `InitialPattern` containing `s_base_values` and the `n_base_bits`
+    /// complement `(2^n_base_bits - 1) - v` of every value `v`, so only one representative
+    /// per pair needs to be given. The result is always complement-closed.
+    ///
+    /// # Errors
+    /// Returns the same errors as `InitialPattern::new` for `s_base_values`. Complements
+    /// of values that fit in `n_base_bits` always fit as well.
+    pub fn new_complement_closed(s_base_values: BTreeSet<BigUint>, n_base_bits: usize) -> Result<Self, HierarchyError> {
+        let mut pattern = Self::new(s_base_values, n_base_bits)?;
+        let all_ones = pattern.all_ones();
+        let complements: Vec<BigUint> = pattern.s_base_values.iter().map(|val| &all_ones - val).collect();
+        pattern.s_base_values.extend(complements);
+        Ok(pattern)
+    }
+
+    /// Returns the pattern holding the values of both `self` and `other`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MismatchedNBits` unless both patterns have the same
+    /// `n_base_bits`.
+    pub fn union(&self, other: &InitialPattern) -> Result<Self, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs | rhs)
+    }
+
+    /// Returns the pattern holding the values present in both `self` and `other`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The patterns have different `n_base_bits` (`MismatchedNBits`).
+    /// * The patterns share no values (`EmptySBaseValues`).
+    pub fn intersection(&self, other: &InitialPattern) -> Result<Self, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs & rhs)
+    }
+
+    /// Returns the pattern holding the values of `self` that are not in `other`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The patterns have different `n_base_bits` (`MismatchedNBits`).
+    /// * Every value of `self` is also in `other` (`EmptySBaseValues`).
+    pub fn difference(&self, other: &InitialPattern) -> Result<Self, HierarchyError> {
+        self.combine(other, |lhs, rhs| lhs - rhs)
+    }
+
+    /// Applies a set operation to the values of two patterns of the same width and
+    /// validates the result with `InitialPattern::new`.
+    fn combine<F>(&self, other: &InitialPattern, op: F) -> Result<Self, HierarchyError>
+    where
+        F: FnOnce(&BTreeSet<BigUint>, &BTreeSet<BigUint>) -> BTreeSet<BigUint>,
+    {
+        if self.n_base_bits != other.n_base_bits {
+            return Err(HierarchyError::MismatchedNBits { lhs: self.n_base_bits, rhs: other.n_base_bits });
+        }
+        Self::new(op(&self.s_base_values, &other.s_base_values), self.n_base_bits)
+    }
+
+    /// Returns `true` if the pattern is closed under `n_base_bits` complement, i.e. for
+    /// every `v` in `s_base_values`, `(2^n_base_bits - 1) - v` is also present.
+    pub fn is_complement_closed(&self) -> bool {
+        let all_ones = self.all_ones();
+        self.s_base_values.iter().all(|val| self.s_base_values.contains(&(&all_ones - val)))
+    }
+
+    /// Returns, in ascending order, the values in `s_base_values` whose `n_base_bits`
+    /// complement is not in `s_base_values`. Empty exactly when the pattern is
+    /// complement-closed.
+    pub fn missing_complements(&self) -> Vec<BigUint> {
+        let all_ones = self.all_ones();
+        self.s_base_values
+            .iter()
+            .filter(|val| !self.s_base_values.contains(&(&all_ones - *val)))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a 64-bit fingerprint of the pattern, computed with FNV-1a over
+    /// `n_base_bits` and the sorted base values.
+    ///
+    /// The fingerprint only depends on the pattern's contents, so it is stable across
+    /// processes and platforms and can be stored alongside derived data to detect a
+    /// changed pattern. It is not a cryptographic hash.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        feed(&(self.n_base_bits as u64).to_le_bytes());
+        for value in &self.s_base_values {
+            // Length-prefix each value so different value sets cannot feed the same bytes.
+            let bytes = value.to_bytes_be();
+            feed(&(bytes.len() as u64).to_le_bytes());
+            feed(&bytes);
+        }
+        hash
+    }
+
+    /// Returns `2^n_base_bits - 1`, the value whose complement relation defines pairs.
+    fn all_ones(&self) -> BigUint {
+        let one = BigUint::one();
+        (&one << self.n_base_bits) - &one
+    }
+
+    /// Creates a new `InitialPattern` from hexadecimal strings (without a `0x` prefix).
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHexString` if a string is not valid hexadecimal,
+    /// or any error returned by `InitialPattern::new`.
+    pub fn from_hex(values: &[&str], n_base_bits: usize) -> Result<Self, HierarchyError> {
+        let s_base_values = values
+            .iter()
+            .map(|hex| parse_hex(hex))
+            .collect::<Result<BTreeSet<BigUint>, HierarchyError>>()?;
+        Self::new(s_base_values, n_base_bits)
+    }
+}
+
+/// Builds an `InitialPattern` fluently, accepting plain integers for the values.
+///
+/// ```
+/// use paired_binary::InitialPattern;
+///
+/// let pattern = InitialPattern::builder()
+///     .with_n_base_bits(3)
+///     .add_value(0u32)
+///     .add_value(1u32)
+///     .add_value(2u32)
+///     .build()
+///     .unwrap();
+/// assert_eq!(pattern.s_base_values.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InitialPatternBuilder {
+    s_base_values: BTreeSet<BigUint>,
+    n_base_bits: Option<usize>,
+}
+
+impl InitialPatternBuilder {
+    /// Creates a builder with no values and `n_base_bits` unset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the bit-width N of the base values.
+    pub fn with_n_base_bits(mut self, n_base_bits: usize) -> Self {
+        self.n_base_bits = Some(n_base_bits);
+        self
+    }
+
+    /// Adds one value to the pattern.
+    pub fn add_value(mut self, value: impl Into<BigUint>) -> Self {
+        self.s_base_values.insert(value.into());
+        self
+    }
+
+    /// Adds every value yielded by `values` to the pattern.
+    pub fn add_values(mut self, values: impl IntoIterator<Item = BigUint>) -> Self {
+        self.s_base_values.extend(values);
+        self
+    }
+
+    /// Builds the pattern with `InitialPattern::new`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::MissingNBaseBits` if `with_n_base_bits` was never called,
+    /// or any error returned by `InitialPattern::new`.
+    pub fn build(self) -> Result<InitialPattern, HierarchyError> {
+        let n_base_bits = self.n_base_bits.ok_or(HierarchyError::MissingNBaseBits)?;
+        InitialPattern::new(self.s_base_values, n_base_bits)
+    }
+}
+
+/// Parses a hexadecimal string (without a `0x` prefix) into a `BigUint`.
+pub(crate) fn parse_hex(hex: &str) -> Result<BigUint, HierarchyError> {
+    BigUint::parse_bytes(hex.as_bytes(), 16).ok_or_else(|| HierarchyError::InvalidHexString(hex.to_string()))
+}
+
+/// The serialized form of an `InitialPattern`.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct InitialPatternRepr {
+    #[serde(with = "crate::serde_support::biguint_vec")]
+    s_base_values: Vec<BigUint>,
+    n_base_bits: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<InitialPattern> for InitialPatternRepr {
+    fn from(pattern: InitialPattern) -> Self {
+        Self { s_base_values: pattern.s_base_values.into_iter().collect(), n_base_bits: pattern.n_base_bits }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<InitialPatternRepr> for InitialPattern {
+    type Error = HierarchyError;
+
+    fn try_from(repr: InitialPatternRepr) -> Result<Self, Self::Error> {
+        InitialPattern::new(repr.s_base_values.into_iter().collect(), repr.n_base_bits)
+    }
 }
\ No newline at end of file
@@ -1,196 +1,2613 @@
-use num_bigint::BigUint;
-use num_traits::One; // Zero is not used in this file
-use rand::seq::SliceRandom;
-use rand::Rng;
-use crate::pattern::InitialPattern;
-use crate::error::HierarchyError;
-
-/// `Propagator` is responsible for applying the hierarchical propagation rules
-/// based on a given `InitialPattern` (S_base).
-/// It determines membership in higher-level selected sets (S_N),
-/// decomposes S_N members into their S_base components, and composes
-/// S_N members from S_base components.
-#[derive(Debug, Clone)]
-pub struct Propagator {
-    initial_pattern: InitialPattern,
-}
-
-impl Propagator {
-    /// Creates a new `Propagator` with a specific `InitialPattern`.
-    pub fn new(initial_pattern: InitialPattern) -> Self {
-        Self { initial_pattern }
-    }
-
-    /// Returns a reference to the `InitialPattern` used by this propagator.
-    pub fn initial_pattern(&self) -> &InitialPattern {
-        &self.initial_pattern
-    }
-
-    /// Checks if `target_n_bits` is a valid hierarchical level that can be derived
-    /// from `self.initial_pattern.n_base_bits` by successive doublings.
-    /// A valid level means `target_n_bits = n_base_bits * 2^k` for some integer `k >= 0`.
-    fn is_valid_hierarchical_level(&self, target_n_bits: usize) -> bool {
-        let base_n_bits = self.initial_pattern.n_base_bits; 
-        if target_n_bits < base_n_bits {
-            return false;
-        }
-        if target_n_bits == base_n_bits {
-            return true;
-        }
-        
-        if base_n_bits == 0 { return false; } 
-        if target_n_bits % base_n_bits != 0 {
-            return false;
-        }
-        let factor = target_n_bits / base_n_bits;
-        factor.is_power_of_two()
-    }
-
-    /// Checks if a given X-value (`x_target`) is a member of the selected set S_N
-    /// at `n_target_bits`, according to the propagation rules and the `InitialPattern`.
-    pub fn is_member(&self, x_target: &BigUint, n_target_bits: usize) -> Result<bool, HierarchyError> {
-        if n_target_bits == 0 {
-             return Err(HierarchyError::InvalidHierarchicalLevel { 
-                target_n_bits: n_target_bits, // Corrected: field_name: variable_value
-                base_n_bits: self.initial_pattern.n_base_bits 
-            });
-        }
-        
-        let limit_exclusive = BigUint::one() << n_target_bits;
-        if *x_target >= limit_exclusive {
-            return Err(HierarchyError::ValueTooLargeForNBits {
-                value: x_target.clone(),
-                n_bits: n_target_bits,
-            });
-        }
-
-        if !self.is_valid_hierarchical_level(n_target_bits) { // This was error line 54/63 previously
-            return Err(HierarchyError::InvalidHierarchicalLevel { 
-                target_n_bits: n_target_bits, // Corrected: field_name: variable_value
-                base_n_bits: self.initial_pattern.n_base_bits 
-            });
-        }
-        // Note: The compiler reported error on line 69 as well for this.
-        // The above is the only place it's constructed in is_member.
-        // If line 69 is still an error, it must be in a different function or a test.
-        // However, the function seems complete as is. Let's assume this fixes both.
-        
-        Ok(self._is_member_recursive(x_target, n_target_bits))
-    }
-    
-    fn _is_member_recursive(&self, x_current: &BigUint, n_current_bits: usize) -> bool {
-        if n_current_bits == self.initial_pattern.n_base_bits {
-            return self.initial_pattern.s_base_values.contains(x_current);
-        }
-
-        let n_half_bits = n_current_bits / 2;
-
-        let one = BigUint::one();
-        let mask = (&one << n_half_bits) - &one;
-        
-        let h_upper = x_current >> n_half_bits;
-        let h_lower = x_current & &mask;
-
-        self._is_member_recursive(&h_upper, n_half_bits) && 
-        self._is_member_recursive(&h_lower, n_half_bits)
-    }
-
-    /// Decomposes a given X-value (`x_target`), known to be a member of S_N,
-    /// into its constituent S_base components.
-    pub fn decompose_to_base(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Vec<BigUint>, HierarchyError> {
-        if !self.is_member(x_target, n_target_bits)? {
-            return Err(HierarchyError::NotAMember(x_target.clone()));
-        }
-
-        let mut components = Vec::new();
-        self._decompose_recursive_collect(x_target, n_target_bits, &mut components);
-        Ok(components)
-    }
-
-    fn _decompose_recursive_collect(&self, current_x: &BigUint, current_n_bits: usize, components: &mut Vec<BigUint>) {
-        if current_n_bits == self.initial_pattern.n_base_bits {
-            components.push(current_x.clone());
-            return;
-        }
-
-        let n_half_bits = current_n_bits / 2;
-        
-        let one = BigUint::one();
-        let mask = (&one << n_half_bits) - &one;
-        let h_upper = current_x >> n_half_bits;
-        let h_lower = current_x & &mask;
-
-        self._decompose_recursive_collect(&h_upper, n_half_bits, components);
-        self._decompose_recursive_collect(&h_lower, n_half_bits, components);
-    }
-
-    /// Composes an S_N member from a sequence of its S_base components.
-    pub fn compose_from_base(&self, s_base_components: &[BigUint]) -> Result<(BigUint, usize), HierarchyError> {
-        let num_components = s_base_components.len();
-        if num_components == 0 || !num_components.is_power_of_two() {
-            return Err(HierarchyError::InvalidComponentCount(s_base_components.len()));
-        }
-
-        let one = BigUint::one();
-        let limit_exclusive_base = &one << self.initial_pattern.n_base_bits;
-
-        for comp in s_base_components {
-            if !self.initial_pattern.s_base_values.contains(comp) {
-                return Err(HierarchyError::InvalidBaseComponent(comp.clone()));
-            }
-            if *comp >= limit_exclusive_base {
-                 let max_val = limit_exclusive_base - &one;
-                return Err(HierarchyError::ValueExceedsNBaseBits {
-                    value: comp.clone(),
-                    n_bits: self.initial_pattern.n_base_bits,
-                    max_val,
-                });
-            }
-        }
-        
-        Ok(self._compose_recursive(s_base_components))
-    }
-
-    fn _compose_recursive(&self, components_slice: &[BigUint]) -> (BigUint, usize) {
-        if components_slice.len() == 1 {
-            return (components_slice[0].clone(), self.initial_pattern.n_base_bits);
-        }
-
-        let mid = components_slice.len() / 2;
-        let (upper_half_val, upper_n_bits) = self._compose_recursive(&components_slice[0..mid]);
-        let (lower_half_val, _lower_n_bits) = self._compose_recursive(&components_slice[mid..]);
-        
-        let composed_n_bits = upper_n_bits * 2; 
-        let composed_val = (upper_half_val << upper_n_bits) | lower_half_val;
-        
-        (composed_val, composed_n_bits)
-    }
-
-    /// Generates a random member of the selected set S_N at `target_n_bits`.
-    pub fn generate_random_s_n_member<R: Rng + ?Sized>(&self, target_n_bits: usize, rng: &mut R) -> Result<BigUint, HierarchyError> {
-        if !self.is_valid_hierarchical_level(target_n_bits) {
-            return Err(HierarchyError::InvalidHierarchicalLevel {
-                target_n_bits: target_n_bits, // Corrected: field_name: variable_value
-                base_n_bits: self.initial_pattern.n_base_bits,
-            });
-        }
-        if self.initial_pattern.s_base_values.is_empty() {
-            return Err(HierarchyError::EmptySBaseForRandomGeneration);
-        }
-
-        Ok(self._generate_random_recursive(target_n_bits, rng))
-    }
-
-    fn _generate_random_recursive<R: Rng + ?Sized>(&self, current_n_bits: usize, rng: &mut R) -> BigUint {
-        if current_n_bits == self.initial_pattern.n_base_bits {
-            let s_base_vec: Vec<&BigUint> = self.initial_pattern.s_base_values.iter().collect();
-            return (*s_base_vec.choose(rng).expect("S_base_values cannot be empty due to earlier check")).clone();
-        }
-
-        let n_half_bits = current_n_bits / 2;
-        let h_upper = self._generate_random_recursive(n_half_bits, rng);
-        let h_lower = self._generate_random_recursive(n_half_bits, rng);
-
-        (h_upper << n_half_bits) | h_lower
-    }
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+#[cfg(feature = "serde")]
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{Float, One, Pow, ToPrimitive}; // Zero is not used in this file
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::HashSet;
+use crate::pattern::InitialPattern;
+use crate::error::HierarchyError;
+use crate::iter::MemberIter;
+use crate::cache::CachedPropagator;
+use crate::chunks::{interleaved_to_windows, windows_to_interleaved, write_window, BaseChunks, SmallChunks};
+use crate::tree::{ComponentAt, DecompNode};
+use crate::entity::{hamming_distance, to_fixed_bytes_be, PairedEntity};
+use crate::diagnostics::{ChunkDiff, ChunkFailure, ComponentValidation, EntityMembership, MembershipExplanation, MembershipReport, MembershipTrace, MembershipWitness, RepairStrategy};
+
+/// The widest level, S_base included, for which `Propagator` keeps the level's members
+/// as a bit-set, which then takes at most 8 KiB.
+const LEVEL_BITSET_MAX_BITS: usize = 16;
+
+/// Builds a bit-set of `2^n_bits` bits with the bits of `values` set.
+fn bitset_of(values: &[u64], n_bits: usize) -> Vec<u64> {
+    let mut bitset = vec![0u64; (1usize << n_bits).div_ceil(64)];
+    for &value in values {
+        bitset[(value / 64) as usize] |= 1u64 << (value % 64);
+    }
+    bitset
+}
+
+/// Returns `true` if bit `value` is set in `bitset`.
+#[inline]
+fn bitset_contains(bitset: &[u64], value: u64) -> bool {
+    bitset.get((value / 64) as usize).is_some_and(|word| word & (1u64 << (value % 64)) != 0)
+}
+
+/// The members of a level as `u64`s: S_base when it is at most 64 bits wide, or a level
+/// materialized by `Propagator::precompute_levels`.
+#[derive(Debug, Clone)]
+enum LevelTable {
+    /// A bit-set of `2^n_bits` bits, for levels of at most `LEVEL_BITSET_MAX_BITS` bits.
+    Bitset(Vec<u64>),
+    /// The members in ascending order, searched by binary search.
+    Sorted(Vec<u64>),
+}
+
+impl LevelTable {
+    /// Stores sorted `members` of a level of `n_bits` bits, as a bit-set if the level is
+    /// narrow enough.
+    fn new(members: Vec<u64>, n_bits: usize) -> Self {
+        if n_bits <= LEVEL_BITSET_MAX_BITS {
+            LevelTable::Bitset(bitset_of(&members, n_bits))
+        } else {
+            LevelTable::Sorted(members)
+        }
+    }
+
+    #[inline]
+    fn contains(&self, value: u64) -> bool {
+        match self {
+            LevelTable::Bitset(bitset) => bitset_contains(bitset, value),
+            LevelTable::Sorted(members) => members.binary_search(&value).is_ok(),
+        }
+    }
+}
+
+/// The serialized form of a `Propagator`.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PropagatorRepr {
+    initial_pattern: InitialPattern,
+    arity: usize,
+    #[serde(default)]
+    rule: MembershipRule,
+    #[serde(default)]
+    split_strategy: SplitStrategy,
+}
+
+#[cfg(feature = "serde")]
+impl From<Propagator> for PropagatorRepr {
+    fn from(propagator: Propagator) -> Self {
+        Self {
+            initial_pattern: propagator.initial_pattern,
+            arity: propagator.arity,
+            rule: propagator.rule,
+            split_strategy: propagator.split_strategy,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PropagatorRepr> for Propagator {
+    type Error = HierarchyError;
+
+    fn try_from(repr: PropagatorRepr) -> Result<Self, Self::Error> {
+        let mut propagator = Propagator::with_arity(repr.initial_pattern, repr.arity)?;
+        propagator.rule = repr.rule;
+        propagator.split_strategy = repr.split_strategy;
+        Ok(propagator)
+    }
+}
+
+/// The JSON configuration read by `Propagator::from_json` and written by
+/// `Propagator::to_json`.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PropagatorConfig {
+    n_base_bits: usize,
+    #[serde(with = "crate::serde_support::biguint_vec")]
+    s_base_values: Vec<BigUint>,
+    #[serde(default = "PropagatorConfig::default_arity")]
+    arity: usize,
+    #[serde(default)]
+    rule: MembershipRule,
+    #[serde(default)]
+    split_strategy: SplitStrategy,
+}
+
+#[cfg(feature = "serde")]
+impl PropagatorConfig {
+    fn default_arity() -> usize {
+        2
+    }
+}
+
+/// Where `Propagator::compose_from_base_padded` inserts fill components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadSide {
+    /// Fill components are placed before the given ones, at the most significant end.
+    Leading,
+    /// Fill components are placed after the given ones, at the least significant end.
+    Trailing,
+}
+
+/// How the membership of a value's parts combines into the membership of the value.
+///
+/// The names refer to the default arity of 2; with a larger arity they apply to all
+/// parts of a level in the same way.
+///
+/// Only under `BothHalves` do members factor into S_base components, so operations that
+/// compose, decompose, enumerate or repair members return
+/// `HierarchyError::DecompositionUndefinedForRule` under the other rules. Membership
+/// checks, `count_members`, `density` and random generation work under every rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MembershipRule {
+    /// A value is a member if every part is a member. Members are then exactly the values
+    /// made of S_base components.
+    #[default]
+    BothHalves,
+    /// A value is a member if at least one part is a member, i.e. if at least one of its
+    /// S_base-width chunks is in S_base.
+    EitherHalf,
+    /// A value is a member if exactly one part is a member. Unlike the other rules this
+    /// does not flatten to a condition on the S_base-width chunks, so each level is
+    /// evaluated in turn.
+    ExactlyOne,
+}
+
+impl MembershipRule {
+    /// Combines the membership of `parts` under this rule, stopping as soon as the result
+    /// is decided.
+    pub(crate) fn combine<T, I, F>(self, mut parts: I, is_member: F) -> bool
+    where
+        I: Iterator<Item = T>,
+        F: FnMut(T) -> bool,
+    {
+        match self {
+            MembershipRule::BothHalves => parts.all(is_member),
+            MembershipRule::EitherHalf => parts.any(is_member),
+            MembershipRule::ExactlyOne => parts.map(is_member).filter(|&is_member| is_member).take(2).count() == 1,
+        }
+    }
+
+    /// Combines the membership of a value's S_base-width chunks, most significant first,
+    /// level by level with `arity` parts per level. Nesting `all` or `any` does not change
+    /// the result, so under `BothHalves` and `EitherHalf` the chunks are combined directly
+    /// and the scan stops as soon as the result is decided.
+    pub(crate) fn combine_leaves<T, I, F>(self, leaves: I, arity: usize, is_member: F) -> bool
+    where
+        I: Iterator<Item = T>,
+        F: FnMut(T) -> bool,
+    {
+        if self != MembershipRule::ExactlyOne {
+            return self.combine(leaves, is_member);
+        }
+        let mut level: Vec<bool> = leaves.map(is_member).collect();
+        while level.len() > 1 {
+            level = level.chunks(arity).map(|parts| self.combine(parts.iter(), |&is_member| is_member)).collect();
+        }
+        level[0]
+    }
+
+    /// Returns `true` if a value with `member_parts` of its `arity` parts being members is
+    /// itself a member under this rule.
+    fn accepts(self, member_parts: usize, arity: usize) -> bool {
+        match self {
+            MembershipRule::BothHalves => member_parts == arity,
+            MembershipRule::EitherHalf => member_parts >= 1,
+            MembershipRule::ExactlyOne => member_parts == 1,
+        }
+    }
+}
+
+/// How a value is split into its parts at each level.
+///
+/// The names refer to the default arity of 2; with a larger arity `m`, `Interleaved`
+/// deals the bits out to the `m` parts in turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SplitStrategy {
+    /// The parts are consecutive bit windows: the upper and the lower half. The S_base
+    /// components of a value are then its consecutive `n_base_bits`-wide windows.
+    #[default]
+    HighLow,
+    /// The parts are the bits at every `m`-th position: the odd-indexed bits (counting
+    /// from the least significant bit 0) form the first part, playing the role of the
+    /// upper half, and the even-indexed bits the second. Composition interleaves the parts
+    /// the same way.
+    ///
+    /// Members no longer occupy consecutive bit windows, so operations that rely on
+    /// numeric order or bit offsets return `HierarchyError::UnsupportedSplitStrategy`.
+    Interleaved,
+}
+
+/// `Propagator` is responsible for applying the hierarchical propagation rules
+/// based on a given `InitialPattern` (S_base).
+/// It determines membership in higher-level selected sets (S_N),
+/// decomposes S_N members into their S_base components, and composes
+/// S_N members from S_base components.
+///
+/// By default each level splits into an upper and a lower half, so the levels are
+/// `n_base_bits * 2^k`. `Propagator::with_arity` generalizes this to `m` equal parts
+/// per level, giving levels `n_base_bits * m^k`.
+///
+/// `Propagator::with_split_strategy` splits each level by interleaving bits rather than
+/// into upper and lower halves.
+///
+/// With the `serde` feature enabled, a propagator is serialized as its `InitialPattern`,
+/// arity, membership rule and split strategy (which default to `BothHalves` and
+/// `HighLow` when absent).
+/// Deserialization re-validates the pattern and arity, through `InitialPattern::new` and
+/// `Propagator::with_arity`.
+///
+/// Two propagators are equal if they have equal patterns, arity, membership rule and
+/// split strategy.
+/// Levels materialized by `precompute_levels` only speed up lookups and are ignored.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PropagatorRepr", into = "PropagatorRepr"))]
+pub struct Propagator {
+    initial_pattern: InitialPattern,
+    /// The S_base values in ascending order, used for ordered enumeration and for
+    /// membership lookups by binary search.
+    sorted_base_values: Vec<BigUint>,
+    /// The S_base values as `u64`, present when `n_base_bits <= 64`. Lookups then avoid
+    /// allocating a `BigUint` per chunk, and up to 16 bits are a single bit test.
+    small_base_values: Option<LevelTable>,
+    /// The members of one intermediate level, with that level's width, set by
+    /// `precompute_levels`. Membership checks at or above that level look up chunks of
+    /// this width instead of S_base-width chunks.
+    precomputed_level: Option<(usize, LevelTable)>,
+    /// The number of equal parts each level splits into.
+    arity: usize,
+    /// How the membership of the parts combines at each level.
+    rule: MembershipRule,
+    /// How each level splits into its parts.
+    split_strategy: SplitStrategy,
+}
+
+impl PartialEq for Propagator {
+    fn eq(&self, other: &Self) -> bool {
+        self.initial_pattern == other.initial_pattern
+            && self.arity == other.arity
+            && self.rule == other.rule
+            && self.split_strategy == other.split_strategy
+    }
+}
+
+impl Eq for Propagator {}
+
+impl Propagator {
+    /// Creates a new `Propagator` with a specific `InitialPattern`, splitting each level
+    /// into two halves.
+    pub fn new(initial_pattern: InitialPattern) -> Self {
+        Self::from_parts(initial_pattern, 2, MembershipRule::BothHalves, SplitStrategy::HighLow)
+    }
+
+    /// Creates a new `Propagator` that splits each level into two halves and combines
+    /// their membership with `rule`.
+    pub fn with_rule(initial_pattern: InitialPattern, rule: MembershipRule) -> Self {
+        Self::from_parts(initial_pattern, 2, rule, SplitStrategy::HighLow)
+    }
+
+    /// Creates a new `Propagator` that splits each level into two parts with `strategy`,
+    /// so a value is a member of S_N if and only if both parts are members of S_{N/2}.
+    pub fn with_split_strategy(initial_pattern: InitialPattern, strategy: SplitStrategy) -> Self {
+        Self::from_parts(initial_pattern, 2, MembershipRule::BothHalves, strategy)
+    }
+
+    /// Creates a new `Propagator` whose levels each split into `arity` equal parts, so a
+    /// value is a member of S_N if and only if all `arity` parts are members of S_{N/arity}.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidArity` if `arity` is smaller than 2.
+    pub fn with_arity(initial_pattern: InitialPattern, arity: usize) -> Result<Self, HierarchyError> {
+        if arity < 2 {
+            return Err(HierarchyError::InvalidArity(arity));
+        }
+        Ok(Self::from_parts(initial_pattern, arity, MembershipRule::BothHalves, SplitStrategy::HighLow))
+    }
+
+    /// Creates a new `Propagator` with an arity already known to be at least 2.
+    pub(crate) fn from_parts(initial_pattern: InitialPattern, arity: usize, rule: MembershipRule, split_strategy: SplitStrategy) -> Self {
+        let sorted_base_values: Vec<BigUint> = initial_pattern.s_base_values.iter().cloned().collect();
+        let small_base_values = (initial_pattern.n_base_bits <= 64).then(|| {
+            let values = sorted_base_values
+                .iter()
+                .map(|value| value.to_u64().expect("a value of at most 64 bits fits in u64"))
+                .collect();
+            LevelTable::new(values, initial_pattern.n_base_bits)
+        });
+        Self { initial_pattern, sorted_base_values, small_base_values, precomputed_level: None, arity, rule, split_strategy }
+    }
+
+    /// Infers the smallest S_base that makes every sample a member of S_N at
+    /// `sample_n_bits`: the distinct `n_base_bits`-wide chunks of all samples.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `n_base_bits` is zero (`NonPositiveNBits`).
+    /// * `sample_n_bits` is smaller than `n_base_bits` (`TargetNBitsTooSmall`) or is not
+    ///   `n_base_bits * 2^k` (`InvalidHierarchicalLevel`).
+    /// * A sample does not fit within `sample_n_bits` (`ValueTooLargeForNBits`).
+    /// * `samples` is empty (`EmptySBaseValues`).
+    pub fn infer_base_pattern(samples: &[BigUint], sample_n_bits: usize, n_base_bits: usize) -> Result<InitialPattern, HierarchyError> {
+        if n_base_bits == 0 {
+            return Err(HierarchyError::NonPositiveNBits(n_base_bits));
+        }
+        if sample_n_bits < n_base_bits {
+            return Err(HierarchyError::TargetNBitsTooSmall { target_n_bits: sample_n_bits, base_n_bits: n_base_bits });
+        }
+        if !sample_n_bits.is_multiple_of(n_base_bits) || !(sample_n_bits / n_base_bits).is_power_of_two() {
+            return Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: sample_n_bits, base_n_bits: n_base_bits });
+        }
+
+        let chunk_count = sample_n_bits / n_base_bits;
+        let mut s_base_values = BTreeSet::new();
+        for sample in samples {
+            if sample.bits() > sample_n_bits as u64 {
+                return Err(HierarchyError::ValueTooLargeForNBits { value: sample.clone(), n_bits: sample_n_bits });
+            }
+            s_base_values.extend(BaseChunks::new(sample, n_base_bits, chunk_count));
+        }
+        InitialPattern::new(s_base_values, n_base_bits)
+    }
+
+    /// Returns a propagator over the S_base values shared by `self` and `other`, with
+    /// their common arity, rule and split strategy. For two `BothHalves` propagators, a
+    /// value is a member of the result exactly when it is a member under both.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The patterns have different `n_base_bits` (`MismatchedBaseBits`).
+    /// * The propagators have different arities (`MismatchedArity`), membership rules
+    ///   (`MismatchedRule`) or split strategies (`MismatchedSplitStrategy`).
+    /// * The patterns share no values (`EmptySBaseValues`).
+    pub fn intersect(&self, other: &Propagator) -> Result<Propagator, HierarchyError> {
+        self.require_same_config(other)?;
+        let pattern = self.initial_pattern.intersection(&other.initial_pattern)?;
+        Ok(Self::from_parts(pattern, self.arity, self.rule, self.split_strategy))
+    }
+
+    /// Returns a propagator over the S_base values of both `self` and `other`, with
+    /// their common arity, rule and split strategy. Every member under either propagator
+    /// is a member of the result, but so are values mixing components of both patterns.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The patterns have different `n_base_bits` (`MismatchedBaseBits`).
+    /// * The propagators have different arities (`MismatchedArity`), membership rules
+    ///   (`MismatchedRule`) or split strategies (`MismatchedSplitStrategy`).
+    pub fn union(&self, other: &Propagator) -> Result<Propagator, HierarchyError> {
+        self.require_same_config(other)?;
+        let pattern = self.initial_pattern.union(&other.initial_pattern)?;
+        Ok(Self::from_parts(pattern, self.arity, self.rule, self.split_strategy))
+    }
+
+    /// Checks that `other` has the same base width, arity, rule and split strategy, so
+    /// membership of a propagator combining both patterns means the same under both.
+    fn require_same_config(&self, other: &Propagator) -> Result<(), HierarchyError> {
+        let (lhs, rhs) = (self.initial_pattern.n_base_bits, other.initial_pattern.n_base_bits);
+        if lhs != rhs {
+            return Err(HierarchyError::MismatchedBaseBits { lhs, rhs });
+        }
+        if self.arity != other.arity {
+            return Err(HierarchyError::MismatchedArity { lhs: self.arity, rhs: other.arity });
+        }
+        if self.rule != other.rule {
+            return Err(HierarchyError::MismatchedRule { lhs: self.rule, rhs: other.rule });
+        }
+        if self.split_strategy != other.split_strategy {
+            return Err(HierarchyError::MismatchedSplitStrategy { lhs: self.split_strategy, rhs: other.split_strategy });
+        }
+        Ok(())
+    }
+
+    /// Loads a propagator from a JSON configuration such as
+    /// `{ "n_base_bits": 3, "s_base_values": ["1", "2", "5"] }`, with the values as decimal
+    /// strings. Optional `arity`, `rule` and `split_strategy` fields default to 2,
+    /// `BothHalves` and `HighLow`.
+    ///
+    /// The pattern and arity are validated through `InitialPattern::new` and
+    /// `Propagator::with_arity`, whose errors are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::JsonParseError` with the parser's message if `json` is not
+    /// a well-formed configuration, or any error from validating the pattern or arity.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Propagator, HierarchyError> {
+        let config: PropagatorConfig =
+            serde_json::from_str(json).map_err(|err| HierarchyError::JsonParseError(err.to_string()))?;
+        let pattern = InitialPattern::new(config.s_base_values.into_iter().collect(), config.n_base_bits)?;
+        let mut propagator = Self::with_arity(pattern, config.arity)?;
+        propagator.rule = config.rule;
+        propagator.split_strategy = config.split_strategy;
+        Ok(propagator)
+    }
+
+    /// Writes the configuration read by `from_json`, with the values sorted and the arity,
+    /// rule and split strategy always included.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let config = PropagatorConfig {
+            n_base_bits: self.initial_pattern.n_base_bits,
+            s_base_values: self.sorted_base_values.clone(),
+            arity: self.arity,
+            rule: self.rule,
+            split_strategy: self.split_strategy,
+        };
+        serde_json::to_string(&config).expect("a propagator configuration always serializes")
+    }
+
+    /// Returns a reference to the `InitialPattern` used by this propagator.
+    pub fn initial_pattern(&self) -> &InitialPattern {
+        &self.initial_pattern
+    }
+
+    /// Returns the number of equal parts each level splits into (2 unless the propagator
+    /// was created with `with_arity`).
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the rule combining the membership of a value's parts (`BothHalves` unless
+    /// the propagator was created with `with_rule`).
+    pub fn rule(&self) -> MembershipRule {
+        self.rule
+    }
+
+    /// Returns how each level splits into its parts (`HighLow` unless the propagator was
+    /// created with `with_split_strategy`).
+    pub fn split_strategy(&self) -> SplitStrategy {
+        self.split_strategy
+    }
+
+    /// Checks that members are exactly the values made of S_base components, which every
+    /// operation that decomposes, counts or enumerates members relies on.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::DecompositionUndefinedForRule` unless the rule is
+    /// `BothHalves`.
+    fn require_decomposable_rule(&self) -> Result<(), HierarchyError> {
+        match self.rule {
+            MembershipRule::BothHalves => Ok(()),
+            rule => Err(HierarchyError::DecompositionUndefinedForRule(rule)),
+        }
+    }
+
+    /// Checks that members occupy consecutive bit windows, which operations relying on
+    /// numeric order or bit offsets need.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::UnsupportedSplitStrategy` unless the split strategy is
+    /// `HighLow`.
+    fn require_high_low_split(&self) -> Result<(), HierarchyError> {
+        match self.split_strategy {
+            SplitStrategy::HighLow => Ok(()),
+            strategy => Err(HierarchyError::UnsupportedSplitStrategy(strategy)),
+        }
+    }
+
+    /// Returns `x_target`, an `n_bits`-wide value, with its bits rearranged so that its
+    /// parts at the level `part_n_bits` are consecutive windows, most significant first,
+    /// as they are under `HighLow`. Under `HighLow` this is `x_target` itself.
+    fn high_low_layout<'a>(&self, x_target: &'a BigUint, n_bits: usize, part_n_bits: usize) -> Cow<'a, BigUint> {
+        match self.split_strategy {
+            SplitStrategy::HighLow => Cow::Borrowed(x_target),
+            SplitStrategy::Interleaved => Cow::Owned(interleaved_to_windows(x_target, n_bits, part_n_bits, self.arity)),
+        }
+    }
+
+    /// The inverse of `high_low_layout`: places consecutive `part_n_bits`-wide windows of
+    /// an `n_bits`-wide value where the split strategy puts the parts of that level.
+    fn split_layout(&self, value: BigUint, n_bits: usize, part_n_bits: usize) -> BigUint {
+        match self.split_strategy {
+            SplitStrategy::HighLow => value,
+            SplitStrategy::Interleaved => windows_to_interleaved(&value, n_bits, part_n_bits, self.arity),
+        }
+    }
+
+    /// Materializes the members of the highest valid level not above `up_to_level`, so
+    /// membership checks at that level and above look up whole chunks of its width rather
+    /// than each S_base-width chunk, cutting the number of lookups by the ratio of the
+    /// widths. Returns the precomputed level, replacing any earlier one.
+    ///
+    /// Levels of at most 16 bits are stored as a bit-set of up to 8 KiB, so each lookup is
+    /// a single bit test. Wider levels are stored as sorted values and searched by binary
+    /// search, which for large tables can be slower than the S_base lookups it replaces.
+    ///
+    /// With `up_to_level == n_base_bits` nothing is materialized and checks go back to
+    /// S_base lookups. The table is not serialized, so a deserialized propagator needs to
+    /// precompute again. Results are unchanged either way.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `up_to_level` is smaller than `n_base_bits` (`TargetNBitsTooSmall`).
+    /// * The level is wider than 64 bits (`LevelExceedsMachineWord`).
+    /// * The level has more than `max_table_size` members (`TooManyMembers`).
+    pub fn precompute_levels(&mut self, up_to_level: usize, max_table_size: usize) -> Result<usize, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let base_n_bits = self.initial_pattern.n_base_bits;
+        let level_n_bits = self
+            .highest_valid_level_leq(up_to_level)
+            .ok_or(HierarchyError::TargetNBitsTooSmall { target_n_bits: up_to_level, base_n_bits })?;
+        if level_n_bits > u64::BITS as usize {
+            return Err(HierarchyError::LevelExceedsMachineWord { n_bits: level_n_bits, max_bits: u64::BITS as usize });
+        }
+        if level_n_bits == base_n_bits {
+            self.precomputed_level = None;
+            return Ok(level_n_bits);
+        }
+
+        let count = self.count_members(level_n_bits)?;
+        if count > BigUint::from(max_table_size) {
+            return Err(HierarchyError::TooManyMembers { count, max_members: max_table_size });
+        }
+        // Members are enumerated in ascending order, so the table is already sorted. They
+        // are kept as consecutive windows, the layout membership checks look them up in.
+        let members: Vec<u64> = self
+            .high_low_members(level_n_bits)
+            .map(|member| member.to_u64().expect("a member of at most 64 bits fits in u64"))
+            .collect();
+        self.precomputed_level = Some((level_n_bits, LevelTable::new(members, level_n_bits)));
+        Ok(level_n_bits)
+    }
+
+    /// Checks the preconditions for every member of S_N at `n_target_bits` to decompose:
+    /// the level is valid, S_base is non-empty and the membership rule is `BothHalves`.
+    /// Under that rule members are exactly the values made of S_base components, so no
+    /// further property of S_base is needed.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * S_base is empty (`EmptySBaseValues`).
+    pub fn verify_decomposability(&self, n_target_bits: usize) -> Result<(), HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.level_depth(n_target_bits)?;
+        if self.sorted_base_values.is_empty() {
+            return Err(HierarchyError::EmptySBaseValues);
+        }
+        Ok(())
+    }
+
+    /// Composes a small member of the first level above S_base, alternating the smallest
+    /// and largest S_base values, and checks that it is a member and that it decomposes
+    /// back into the same components. Its size does not grow with S_base.
+    ///
+    /// This catches logic regressions rather than problems with the pattern: for a valid
+    /// `BothHalves` propagator it always succeeds.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::RoundTripMismatch` if the check fails,
+    /// `DecompositionUndefinedForRule` unless the rule is `BothHalves`, or any error from
+    /// composing or decomposing the member.
+    pub fn self_test(&self) -> Result<(), HierarchyError> {
+        self.require_decomposable_rule()?;
+        let smallest = self.sorted_base_values.first().ok_or(HierarchyError::EmptySBaseValues)?;
+        let largest = self.sorted_base_values.last().ok_or(HierarchyError::EmptySBaseValues)?;
+        let components: Vec<BigUint> = [smallest, largest].into_iter().cycle().take(self.arity).cloned().collect();
+
+        let (composed, n_bits) = self.compose_from_base(&components)?;
+        let round_trips = self.is_member(&composed, n_bits)? && self.decompose_to_base(&composed, n_bits)? == components;
+        if !round_trips {
+            return Err(HierarchyError::RoundTripMismatch { composed, n_bits });
+        }
+        Ok(())
+    }
+
+    /// Wraps this propagator in a `CachedPropagator` that memoizes membership of
+    /// intermediate sub-blocks across queries.
+    ///
+    /// # Arguments
+    /// * `capacity`: Maximum number of cached sub-block results, or `None` for an
+    ///   unbounded cache. When the cache is full it is cleared before inserting.
+    pub fn with_cache(self, capacity: Option<usize>) -> CachedPropagator {
+        CachedPropagator::new(self, capacity)
+    }
+
+    /// Returns `true` if `n_bits` is a valid hierarchical level, i.e.
+    /// `n_bits = n_base_bits * m^k` for some integer `k >= 0`, where `m` is the arity.
+    pub fn is_valid_level(&self, n_bits: usize) -> bool {
+        self.is_valid_hierarchical_level(n_bits)
+    }
+
+    /// Returns `k` such that `n_bits = n_base_bits * m^k`, where `m` is the arity, or
+    /// `None` if `n_bits` is not a valid hierarchical level.
+    pub fn level_index(&self, n_bits: usize) -> Option<u32> {
+        self.level_depth(n_bits).ok()
+    }
+
+    /// Returns the number of S_base components a value at `n_target_bits` decomposes into,
+    /// `m^k` for `n_target_bits = n_base_bits * m^k` with the arity `m`. This is 1 at
+    /// `n_base_bits` and the length of every `decompose_to_base` result at that level.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    pub fn component_count(&self, n_target_bits: usize) -> Result<usize, HierarchyError> {
+        self.level_depth(n_target_bits)?;
+        Ok(n_target_bits / self.initial_pattern.n_base_bits)
+    }
+
+    /// Returns the valid hierarchical levels `n_base_bits`, `n_base_bits * m`,
+    /// `n_base_bits * m^2`, ... in ascending order, up to and including `max_bits`.
+    ///
+    /// The iterator ends early rather than overflowing if the next level would not fit
+    /// in a `usize`, and is empty if `max_bits < n_base_bits`.
+    pub fn levels_up_to(&self, max_bits: usize) -> impl Iterator<Item = usize> {
+        let arity = self.arity;
+        core::iter::successors(Some(self.initial_pattern.n_base_bits), move |&n_bits| n_bits.checked_mul(arity))
+            .take_while(move |&n_bits| n_bits <= max_bits)
+    }
+
+    /// Returns the valid hierarchical levels up to and including `max_n_bits`, in
+    /// ascending order, as collected from `levels_up_to`.
+    pub fn valid_levels_up_to(&self, max_n_bits: usize) -> Vec<usize> {
+        self.levels_up_to(max_n_bits).collect()
+    }
+
+    /// Returns the largest valid hierarchical level not exceeding `max_n_bits`, or `None`
+    /// if `max_n_bits` is smaller than `n_base_bits`. Useful to snap an arbitrary
+    /// requested width down to a selectable level.
+    pub fn highest_valid_level_leq(&self, max_n_bits: usize) -> Option<usize> {
+        self.levels_up_to(max_n_bits).last()
+    }
+
+    /// Checks if `target_n_bits` is a valid hierarchical level that can be derived
+    /// from `self.initial_pattern.n_base_bits` by successive splits.
+    /// A valid level means `target_n_bits = n_base_bits * m^k` for some integer `k >= 0`,
+    /// where `m` is the arity.
+    fn is_valid_hierarchical_level(&self, target_n_bits: usize) -> bool {
+        let base_n_bits = self.initial_pattern.n_base_bits; 
+        if target_n_bits < base_n_bits {
+            return false;
+        }
+        if target_n_bits == base_n_bits {
+            return true;
+        }
+        
+        if base_n_bits == 0 { return false; } 
+        if !target_n_bits.is_multiple_of(base_n_bits) {
+            return false;
+        }
+        let factor = target_n_bits / base_n_bits;
+        self.arity_exponent(factor).is_some()
+    }
+
+    /// Returns `k` such that `value = m^k` for the arity `m`, or `None` if `value` is not
+    /// a power of the arity.
+    fn arity_exponent(&self, mut value: usize) -> Option<u32> {
+        if value == 0 {
+            return None;
+        }
+        let mut exponent = 0;
+        while value.is_multiple_of(self.arity) {
+            value /= self.arity;
+            exponent += 1;
+        }
+        (value == 1).then_some(exponent)
+    }
+
+    /// Returns the number of splits `k` such that `target_n_bits = n_base_bits * m^k`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `target_n_bits` is not
+    /// a valid hierarchical level.
+    fn level_depth(&self, target_n_bits: usize) -> Result<u32, HierarchyError> {
+        if !self.is_valid_hierarchical_level(target_n_bits) {
+            return Err(HierarchyError::InvalidHierarchicalLevel {
+                target_n_bits,
+                base_n_bits: self.initial_pattern.n_base_bits,
+            });
+        }
+        Ok(self
+            .arity_exponent(target_n_bits / self.initial_pattern.n_base_bits)
+            .expect("a valid level is n_base_bits times a power of the arity"))
+    }
+
+    /// Returns `true` if `num_components` is a non-zero power of the arity.
+    fn is_valid_component_count(&self, num_components: usize) -> bool {
+        self.arity_exponent(num_components).is_some()
+    }
+
+    /// Returns the exact number of members of the selected set S_N at `n_target_bits`.
+    ///
+    /// Under `BothHalves` every member is made of `m^k` independently chosen S_base
+    /// components, where `m` is the arity and `k` the number of splits from `n_base_bits`,
+    /// so the count is `|S_base|^(m^k)`. It is computed by raising to the `m`-th power `k`
+    /// times, so arbitrarily deep levels never overflow. At `n_target_bits == n_base_bits`
+    /// this is just `|S_base|`.
+    ///
+    /// For example, an S_base of 3 values at 2 bits has `3^4 = 81` members at 8 bits.
+    ///
+    /// The other rules are counted level by level from the `c` members and `u` non-members
+    /// of the level below: `EitherHalf` by inclusion-exclusion, as all `(c + u)^m` values
+    /// less the `u^m` with no member part, and `ExactlyOne` as `m * c * u^(m-1)`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    pub fn count_members(&self, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        let depth = self.level_depth(n_target_bits)?;
+        Ok(self.member_counts(depth).pop().expect("there is a count for the base level"))
+    }
+
+    /// Returns the number of members at each of the levels `n_base_bits * m^j` for
+    /// `j = 0..=depth`, where `m` is the arity.
+    fn member_counts(&self, depth: u32) -> Vec<BigUint> {
+        let mut counts = Vec::with_capacity(depth as usize + 1);
+        let mut count = BigUint::from(self.initial_pattern.s_base_values.len());
+        let mut n_part_bits = self.initial_pattern.n_base_bits;
+        for _ in 0..depth {
+            let next = match self.rule {
+                MembershipRule::BothHalves => Pow::pow(&count, self.arity),
+                MembershipRule::EitherHalf => {
+                    let non_members = (BigUint::one() << n_part_bits) - &count;
+                    (BigUint::one() << (n_part_bits * self.arity)) - Pow::pow(&non_members, self.arity)
+                }
+                MembershipRule::ExactlyOne => {
+                    let non_members = (BigUint::one() << n_part_bits) - &count;
+                    &count * self.arity * Pow::pow(&non_members, self.arity - 1)
+                }
+            };
+            counts.push(core::mem::replace(&mut count, next));
+            n_part_bits *= self.arity;
+        }
+        counts.push(count);
+        counts
+    }
+
+    /// Returns the fraction of all `n_target_bits`-bit values that are members of S_N,
+    /// i.e. `count_members(n_target_bits) / 2^n_target_bits`.
+    ///
+    /// Both terms can be far too large for `f64`, so under `BothHalves` the ratio is
+    /// computed in log space from the member count's closed form, as
+    /// `exp(c * (ln|S_base| - n_base_bits * ln 2))` for `c = n_target_bits / n_base_bits`
+    /// components, and clamped to `[0, 1]`. The result is an approximation for large
+    /// levels and saturates to `0.0` once the true density is below the smallest positive
+    /// `f64`, losing precision in the subnormal range before that. Under the other rules
+    /// the density `d` of the level below is carried up level by level, as
+    /// `1 - (1 - d)^m` for `EitherHalf` and `m * d * (1 - d)^(m-1)` for `ExactlyOne`.
+    /// `density_exact` gives the exact ratio.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    pub fn density(&self, n_target_bits: usize) -> Result<f64, HierarchyError> {
+        let depth = self.level_depth(n_target_bits)?;
+
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        // `Float` provides `ln`, `exp` and `powi` without `std`, through libm.
+        let base_ln_density = Float::ln(self.initial_pattern.s_base_values.len() as f64)
+            - n_base_bits as f64 * core::f64::consts::LN_2;
+        if self.rule != MembershipRule::BothHalves {
+            let arity = self.arity as i32;
+            let mut density = Float::exp(base_ln_density);
+            for _ in 0..depth {
+                density = match self.rule {
+                    MembershipRule::ExactlyOne => arity as f64 * density * Float::powi(1.0 - density, arity - 1),
+                    _ => 1.0 - Float::powi(1.0 - density, arity),
+                };
+            }
+            return Ok(density.clamp(0.0, 1.0));
+        }
+
+        let component_count = (n_target_bits / n_base_bits) as f64;
+        Ok(Float::exp(component_count * base_ln_density).clamp(0.0, 1.0))
+    }
+
+    /// Returns the density of S_N at `n_target_bits` exactly, as the unreduced fraction
+    /// `(count_members(n_target_bits), 2^n_target_bits)`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    pub fn density_exact(&self, n_target_bits: usize) -> Result<(BigUint, BigUint), HierarchyError> {
+        let count = self.count_members(n_target_bits)?;
+        Ok((count, BigUint::one() << n_target_bits))
+    }
+
+    /// Returns the number of members `m` of S_N at `n_target_bits` with `low <= m <= high`,
+    /// or zero if `low > high`.
+    ///
+    /// Counts are computed digit by digit over the S_base-width chunks of the bounds, in the
+    /// radix convention documented on `nth_member`, so no members are enumerated.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * The split strategy is not `HighLow` (`UnsupportedSplitStrategy`).
+    /// * `n_target_bits` is not a valid hierarchical level, or either bound does not fit
+    ///   in it.
+    pub fn count_members_in_range(&self, low: &BigUint, high: &BigUint, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.validate_member_query(low, n_target_bits)?;
+        self.validate_member_query(high, n_target_bits)?;
+        if low > high {
+            return Ok(BigUint::default());
+        }
+
+        let below_low = self.count_members_at_most(low, n_target_bits)
+            - u32::from(self._is_member_iterative(low, n_target_bits));
+        Ok(self.count_members_at_most(high, n_target_bits) - below_low)
+    }
+
+    /// Counts the members of S_N that are `<= bound`. `bound` must already be validated.
+    ///
+    /// A member is below `bound` if it agrees with it on some prefix of chunks and is
+    /// smaller in the next one; the remaining chunks are then free. Such a prefix can only
+    /// run through chunks of `bound` that are in S_base.
+    fn count_members_at_most(&self, bound: &BigUint, n_target_bits: usize) -> BigUint {
+        let chunks: Vec<BigUint> = self.base_chunks(bound, n_target_bits).collect();
+        let valid_prefix = chunks
+            .iter()
+            .position(|chunk| !self.is_base_value(chunk))
+            .unwrap_or(chunks.len());
+
+        let radix = BigUint::from(self.sorted_base_values.len());
+        let mut count = BigUint::from(u32::from(valid_prefix == chunks.len()));
+        let mut free_choices = BigUint::one();
+        for (position, chunk) in chunks.iter().enumerate().rev() {
+            if position <= valid_prefix {
+                let smaller = self.sorted_base_values.partition_point(|value| value < chunk);
+                count += &free_choices * smaller;
+            }
+            free_choices *= &radix;
+        }
+        count
+    }
+
+    /// Returns a lazy iterator over every member of S_N at `n_target_bits`,
+    /// in ascending numeric order.
+    ///
+    /// Members are produced by walking the Cartesian product of sorted S_base values
+    /// across the `n_target_bits / n_base_bits` component positions, so memory stays
+    /// proportional to the size of a single member rather than to the (combinatorially
+    /// large) size of S_N.
+    /// At `n_target_bits == n_base_bits` this simply yields the sorted S_base values.
+    /// The iterator's `size_hint` is exact whenever the member count fits in a `usize`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level, or `UnsupportedSplitStrategy` unless the split strategy
+    /// is `HighLow`.
+    pub fn iter_members(&self, n_target_bits: usize) -> Result<MemberIter, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.level_depth(n_target_bits)?;
+        Ok(self.high_low_members(n_target_bits))
+    }
+
+    /// Enumerates the members at the valid level `n_target_bits` like `iter_members`, but
+    /// with their S_base components as consecutive windows whatever the split strategy.
+    fn high_low_members(&self, n_target_bits: usize) -> MemberIter {
+        MemberIter::new(
+            self.sorted_base_values.clone(),
+            self.initial_pattern.n_base_bits,
+            n_target_bits / self.initial_pattern.n_base_bits,
+        )
+    }
+
+    /// Returns a lazy iterator over the members of S_N at `n_target_bits` whose S_base
+    /// components at the given leaf indices (0 = most significant) equal the given values,
+    /// in ascending numeric order. Only the unconstrained components vary.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The split strategy is not `HighLow` (`UnsupportedSplitStrategy`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * A leaf index is not below the level's component count (`LeafIndexOutOfRange`).
+    /// * A leaf index appears more than once (`DuplicateLeafConstraint`).
+    /// * A constrained value is not in S_base (`InvalidBaseComponent`).
+    pub fn iter_members_matching(&self, n_target_bits: usize, constraints: &[(usize, BigUint)]) -> Result<MemberIter, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.level_depth(n_target_bits)?;
+        let component_count = n_target_bits / self.initial_pattern.n_base_bits;
+
+        let mut constrained = HashSet::with_capacity(constraints.len());
+        let mut pins = Vec::with_capacity(constraints.len());
+        for (leaf_index, value) in constraints {
+            if *leaf_index >= component_count {
+                return Err(HierarchyError::LeafIndexOutOfRange { leaf_index: *leaf_index, component_count });
+            }
+            if !constrained.insert(*leaf_index) {
+                return Err(HierarchyError::DuplicateLeafConstraint(*leaf_index));
+            }
+            let digit = self
+                .sorted_base_values
+                .binary_search(value)
+                .map_err(|_| HierarchyError::InvalidBaseComponent(value.clone()))?;
+            pins.push((*leaf_index, digit));
+        }
+
+        Ok(MemberIter::with_pinned(
+            self.sorted_base_values.clone(),
+            self.initial_pattern.n_base_bits,
+            component_count,
+            &pins,
+        ))
+    }
+
+    /// Returns the smallest member of S_N at `n_target_bits`: the smallest S_base value in
+    /// every component. This is the first member yielded by `iter_members`, and remains
+    /// the smallest member under `Interleaved`, where `iter_members` is unavailable.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    pub fn min_member(&self, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        let smallest = &self.sorted_base_values[0];
+        self.repeated_component_member(smallest, n_target_bits)
+    }
+
+    /// Returns the largest member of S_N at `n_target_bits`: the largest S_base value in
+    /// every component. This is the last member yielded by `iter_members`, and remains the
+    /// largest member under `Interleaved`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    pub fn max_member(&self, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        let largest = self.sorted_base_values.last().expect("S_base_values cannot be empty due to InitialPattern::new");
+        self.repeated_component_member(largest, n_target_bits)
+    }
+
+    /// Returns the smallest member of S_N at `n_target_bits`, exactly as `min_member` does.
+    ///
+    /// # Errors
+    /// Returns the same errors as `min_member`.
+    #[inline]
+    pub fn smallest_member(&self, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        self.min_member(n_target_bits)
+    }
+
+    /// Returns the largest member of S_N at `n_target_bits`, exactly as `max_member` does.
+    ///
+    /// # Errors
+    /// Returns the same errors as `max_member`.
+    #[inline]
+    pub fn largest_member(&self, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        self.max_member(n_target_bits)
+    }
+
+    /// Returns the member of S_N at `n_target_bits` with `component` in every position.
+    fn repeated_component_member(&self, component: &BigUint, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let component_count = self.component_count(n_target_bits)?;
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let mut member = BigUint::default();
+        for _ in 0..component_count {
+            member = (member << n_base_bits) | component;
+        }
+        Ok(self.split_layout(member, n_target_bits, n_base_bits))
+    }
+
+    /// Returns the smallest member of S_N at `n_target_bits` that is strictly greater than
+    /// `x_target`, or `None` if there is none. `x_target` need not be a member.
+    ///
+    /// Works chunk-wise like incrementing a mixed-radix number over the sorted S_base
+    /// values, so it never enumerates members. Starting from `nth_member(n_target_bits, &0)`,
+    /// repeated calls visit every member in the order of `iter_members`; starting from a
+    /// non-member bound instead skips ahead to the first member above it.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if `n_target_bits` is not a valid hierarchical level or
+    /// `x_target` does not fit in it, or `UnsupportedSplitStrategy` unless the split
+    /// strategy is `HighLow`.
+    pub fn next_member(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Option<BigUint>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+        let smallest = &self.sorted_base_values[0];
+        Ok(self.step_to_member(x_target, n_target_bits, smallest, |chunk| {
+            let above = self.sorted_base_values.partition_point(|value| value <= chunk);
+            self.sorted_base_values.get(above)
+        }))
+    }
+
+    /// Returns the largest member of S_N at `n_target_bits` that is strictly smaller than
+    /// `x_target`, or `None` if there is none. `x_target` need not be a member.
+    ///
+    /// This mirrors `next_member`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if `n_target_bits` is not a valid hierarchical level or
+    /// `x_target` does not fit in it, or `UnsupportedSplitStrategy` unless the split
+    /// strategy is `HighLow`.
+    pub fn prev_member(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Option<BigUint>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+        let largest = self.sorted_base_values.last().expect("S_base_values cannot be empty due to InitialPattern::new");
+        Ok(self.step_to_member(x_target, n_target_bits, largest, |chunk| {
+            let below = self.sorted_base_values.partition_point(|value| value < chunk);
+            below.checked_sub(1).map(|index| &self.sorted_base_values[index])
+        }))
+    }
+
+    /// Finds the member closest to `x_target` in one direction. The result keeps the
+    /// longest possible prefix of `x_target`'s chunks that are in S_base, replaces the
+    /// next chunk with `step(chunk)` (the nearest S_base value strictly beyond it, if any),
+    /// and fills every remaining chunk with `fill`.
+    fn step_to_member<'a, F>(&'a self, x_target: &BigUint, n_target_bits: usize, fill: &'a BigUint, step: F) -> Option<BigUint>
+    where
+        F: Fn(&BigUint) -> Option<&'a BigUint>,
+    {
+        let chunks: Vec<BigUint> = self.base_chunks(x_target, n_target_bits).collect();
+        let valid_prefix = chunks
+            .iter()
+            .position(|chunk| !self.is_base_value(chunk))
+            .unwrap_or(chunks.len());
+
+        // The later the changed position, the closer the result is to `x_target`.
+        let last_position = valid_prefix.min(chunks.len() - 1);
+        let (position, replacement) = (0..=last_position)
+            .rev()
+            .find_map(|position| step(&chunks[position]).map(|replacement| (position, replacement)))?;
+
+        let components: Vec<BigUint> = chunks[..position]
+            .iter()
+            .chain(core::iter::once(replacement))
+            .chain(core::iter::repeat_n(fill, chunks.len() - position - 1))
+            .cloned()
+            .collect();
+        let (member, _) = self
+            .compose_from_iter(components.into_iter())
+            .expect("every component is in S_base and the count matches n_target_bits");
+        Some(member)
+    }
+
+    /// Materializes S_N at `level_n_bits` as a new `InitialPattern`, so it can seed a
+    /// second `Propagator` in a two-stage pipeline.
+    ///
+    /// A propagator with the same arity and split strategy built on the lifted pattern
+    /// agrees with this one on membership at every level from `level_n_bits` upwards.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `level_n_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * S_N has more than `max_members` members (`TooManyMembers`).
+    pub fn lift_to_level(&self, level_n_bits: usize, max_members: usize) -> Result<InitialPattern, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let count = self.count_members(level_n_bits)?;
+        if count > BigUint::from(max_members) {
+            return Err(HierarchyError::TooManyMembers { count, max_members });
+        }
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let members = self
+            .high_low_members(level_n_bits)
+            .map(|member| self.split_layout(member, level_n_bits, n_base_bits))
+            .collect();
+        InitialPattern::new(members, level_n_bits)
+    }
+
+    /// Returns the member of S_N at position `index` in the ascending enumeration
+    /// produced by `iter_members`.
+    ///
+    /// The index is decoded as a mixed-radix number with one digit per S_base component,
+    /// each digit selecting a value from the sorted S_base values.
+    ///
+    /// # Radix convention
+    /// With `r = |S_base|` and `c = n_target_bits / n_base_bits` components, the index is
+    /// read in base `r` with the **most significant digit mapping to the most significant
+    /// (leftmost) component**: `index = d_0 * r^(c-1) + d_1 * r^(c-2) + ... + d_(c-1)`,
+    /// and component `i` of the member is `sorted_s_base[d_i]`. Since each component
+    /// occupies a fixed bit window, this order coincides with numeric order, so index 0 is
+    /// the smallest member and `count_members(n_target_bits) - 1` is the largest.
+    /// Contiguous index ranges can therefore be handed to independent workers. Under
+    /// `Interleaved` the components follow the same convention but their bits are spread
+    /// out, so the order is no longer numeric.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `index` is not smaller than `count_members(n_target_bits)` (`IndexOutOfRange`).
+    pub fn nth_member(&self, n_target_bits: usize, index: &BigUint) -> Result<BigUint, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let count = self.count_members(n_target_bits)?;
+        if *index >= count {
+            return Err(HierarchyError::IndexOutOfRange { index: index.clone(), count });
+        }
+
+        let component_count = n_target_bits / self.initial_pattern.n_base_bits;
+        let radix = self.sorted_base_values.len();
+        let mut remaining_index = index.clone();
+        let mut member = BigUint::default();
+        // Digits are peeled off least significant first, which is the last component.
+        for position in 0..component_count {
+            let digit = (&remaining_index % radix)
+                .to_usize()
+                .expect("a remainder modulo |S_base| always fits in usize");
+            remaining_index /= radix;
+            member |= &self.sorted_base_values[digit] << (position * self.initial_pattern.n_base_bits);
+        }
+        Ok(self.split_layout(member, n_target_bits, self.initial_pattern.n_base_bits))
+    }
+
+    /// Returns the zero-based position of `x_target` in the ascending enumeration of S_N,
+    /// i.e. the inverse of `nth_member`.
+    ///
+    /// The value is decomposed into its S_base components, and each component's position
+    /// among the sorted S_base values becomes one digit of the index, following the radix
+    /// convention documented on `nth_member`. Consequently
+    /// `index_of_member(&nth_member(n, &i)?, n)? == i` for every `i < count_members(n)`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if `n_target_bits` is invalid, `x_target` does not fit in
+    /// `n_target_bits`, or `x_target` is not a member of S_N (`NotAMember`).
+    pub fn index_of_member(&self, x_target: &BigUint, n_target_bits: usize) -> Result<BigUint, HierarchyError> {
+        let components = self.decompose_to_base(x_target, n_target_bits)?;
+
+        let radix = self.sorted_base_values.len();
+        let mut index = BigUint::default();
+        for component in &components {
+            let digit = self
+                .sorted_base_values
+                .binary_search(component)
+                .expect("decomposed components are members of S_base");
+            index = index * radix + digit;
+        }
+        Ok(index)
+    }
+
+    /// Checks if a given X-value (`x_target`) is a member of the selected set S_N
+    /// at `n_target_bits`, according to the propagation rules and the `InitialPattern`.
+    pub fn is_member(&self, x_target: &BigUint, n_target_bits: usize) -> Result<bool, HierarchyError> {
+        self.validate_member_query(x_target, n_target_bits)?;
+        Ok(self._is_member_iterative(x_target, n_target_bits))
+    }
+
+    /// Checks membership of the value whose parts at the intermediate level
+    /// `component_n_bits` are `components`, most significant first, without composing it.
+    ///
+    /// Returns whether the value is a member, combining the parts under the membership
+    /// rule, and the level `component_n_bits * components.len()` it was checked at.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The number of components is not a non-zero power of the arity (`InvalidComponentCount`).
+    /// * `component_n_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * A component does not fit in `component_n_bits` (`ValueTooLargeForNBits`).
+    pub fn is_member_from_components(&self, components: &[BigUint], component_n_bits: usize) -> Result<(bool, usize), HierarchyError> {
+        let num_components = components.len();
+        if !self.is_valid_component_count(num_components) {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+        let n_target_bits = component_n_bits
+            .checked_mul(num_components)
+            .ok_or(HierarchyError::InvalidComponentCount(num_components))?;
+        for comp in components {
+            self.validate_member_query(comp, component_n_bits)?;
+        }
+
+        let is_member = self
+            .rule
+            .combine_leaves(components.iter(), self.arity, |comp| self._is_member_iterative(comp, component_n_bits));
+        Ok((is_member, n_target_bits))
+    }
+
+    /// Checks if `prefix`, the top `prefix_n_bits` of a value at `target_n_bits`, is a
+    /// member of S_{prefix_n_bits}.
+    ///
+    /// Every member of S_N starts with a member of each lower level, so a `false` result
+    /// means no completion of `prefix` can be a member and a streamed candidate can be
+    /// discarded before its remaining bits arrive.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The split strategy is not `HighLow` (`UnsupportedSplitStrategy`).
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`), since
+    ///   a prefix then no longer constrains the rest of the value.
+    /// * Either width is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `target_n_bits` is smaller than `prefix_n_bits` (`PrefixTooLong`, counting both
+    ///   widths in S_base components).
+    /// * `prefix` does not fit in `prefix_n_bits` (`ValueTooLargeForNBits`).
+    pub fn is_member_prefix(&self, prefix: &BigUint, prefix_n_bits: usize, target_n_bits: usize) -> Result<bool, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.require_high_low_split()?;
+        self.level_depth(target_n_bits)?;
+        self.validate_member_query(prefix, prefix_n_bits)?;
+        if target_n_bits < prefix_n_bits {
+            let n_base_bits = self.initial_pattern.n_base_bits;
+            return Err(HierarchyError::PrefixTooLong {
+                prefix_len: prefix_n_bits / n_base_bits,
+                component_count: target_n_bits / n_base_bits,
+            });
+        }
+        Ok(self._is_member_iterative(prefix, prefix_n_bits))
+    }
+
+    /// Checks if the big-endian value in `bytes_be` is a member of S_N at `n_target_bits`.
+    ///
+    /// `bytes_be` may have any length: leading zero bytes are ignored, so inputs padded
+    /// beyond `ceil(n_target_bits / 8)` bytes are accepted, while any set bit above
+    /// `n_target_bits` is rejected, including within the top byte when `n_target_bits` is
+    /// not a multiple of 8.
+    ///
+    /// # Errors
+    /// Returns the same errors as `is_member`.
+    pub fn is_member_bytes(&self, bytes_be: &[u8], n_target_bits: usize) -> Result<bool, HierarchyError> {
+        self.is_member(&BigUint::from_bytes_be(bytes_be), n_target_bits)
+    }
+
+    /// Returns, in ascending order, the valid hierarchical levels up to `max_n_bits` at
+    /// which `x_target` is a member of S_N. Levels too narrow to hold `x_target` are
+    /// skipped rather than reported as errors, so this classifies an arbitrary value
+    /// against the whole hierarchy.
+    ///
+    /// # Errors
+    /// Returns the same errors as `is_member`, which cannot occur for the levels checked.
+    pub fn membership_levels(&self, x_target: &BigUint, max_n_bits: usize) -> Result<Vec<usize>, HierarchyError> {
+        let mut levels = Vec::new();
+        for n_bits in self.levels_up_to(max_n_bits) {
+            if x_target.bits() <= n_bits as u64 && self.is_member(x_target, n_bits)? {
+                levels.push(n_bits);
+            }
+        }
+        Ok(levels)
+    }
+
+    /// Checks membership of every value in `targets` at the same `n_target_bits`.
+    ///
+    /// The level is validated once up front; each value is then checked on its own.
+    /// Values that do not fit in `n_target_bits` produce a per-item
+    /// `ValueTooLargeForNBits` error instead of failing the whole batch.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    pub fn is_members(&self, targets: &[BigUint], n_target_bits: usize) -> Result<Vec<Result<bool, HierarchyError>>, HierarchyError> {
+        self.level_depth(n_target_bits)?;
+
+        Ok(targets
+            .iter()
+            .map(|x_target| self._is_member_at_valid_level(x_target, n_target_bits))
+            .collect())
+    }
+
+    /// Parallel version of `is_members`, checking the values on the rayon thread pool.
+    /// Returns exactly what `is_members` returns, in the same order.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not
+    /// a valid hierarchical level.
+    #[cfg(feature = "rayon")]
+    pub fn par_is_members(&self, targets: &[BigUint], n_target_bits: usize) -> Result<Vec<Result<bool, HierarchyError>>, HierarchyError> {
+        self.level_depth(n_target_bits)?;
+
+        Ok(targets
+            .par_iter()
+            .map(|x_target| self._is_member_at_valid_level(x_target, n_target_bits))
+            .collect())
+    }
+
+    /// Checks one value of a batch whose level has already been validated.
+    fn _is_member_at_valid_level(&self, x_target: &BigUint, n_target_bits: usize) -> Result<bool, HierarchyError> {
+        if x_target.bits() > n_target_bits as u64 {
+            return Err(HierarchyError::ValueTooLargeForNBits {
+                value: x_target.clone(),
+                n_bits: n_target_bits,
+            });
+        }
+        Ok(self._is_member_iterative(x_target, n_target_bits))
+    }
+
+    /// Checks which values of `entity` are members of S_N at `n_target_bits`, so a
+    /// pattern defined on raw X-values can be queried with canonical entities.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `entity.n_bits` differs from `n_target_bits` (`EntityWidthMismatch`).
+    /// * The usual `is_member` validation fails.
+    pub fn is_entity_member(&self, entity: &PairedEntity, n_target_bits: usize) -> Result<EntityMembership, HierarchyError> {
+        if entity.n_bits != n_target_bits {
+            return Err(HierarchyError::EntityWidthMismatch { entity_n_bits: entity.n_bits, target_n_bits: n_target_bits });
+        }
+        let x_is_member = self.is_member(&entity.x, n_target_bits)?;
+        let x_prime_is_member = self.is_member(&entity.x_prime, n_target_bits)?;
+        Ok(match (x_is_member, x_prime_is_member) {
+            (true, true) => EntityMembership::Both,
+            (true, false) => EntityMembership::XMember,
+            (false, true) => EntityMembership::XPrimeMember,
+            (false, false) => EntityMembership::Neither,
+        })
+    }
+
+    /// Checks membership of `x_target` and reports every S_base-width chunk that is not
+    /// in S_base, rather than stopping at the first one.
+    ///
+    /// # Errors
+    /// Returns the same validation errors as `is_member`.
+    pub fn check_membership_detailed(&self, x_target: &BigUint, n_target_bits: usize) -> Result<MembershipReport, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+
+        let failures: Vec<ChunkFailure> = self
+            .base_chunks(x_target, n_target_bits)
+            .enumerate()
+            .filter(|(_, chunk)| !self.is_base_value(chunk))
+            .map(|(leaf_index, value)| ChunkFailure { leaf_index, value })
+            .collect();
+
+        Ok(MembershipReport { is_member: failures.is_empty(), failures })
+    }
+
+    /// Returns the number of S_base-width chunks of `x_target` that are not in S_base,
+    /// the length of `check_membership_detailed`'s failures without collecting them. The
+    /// count is 0 exactly when `x_target` is a member.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::DecompositionUndefinedForRule` unless the rule is
+    /// `BothHalves`, or the same validation errors as `is_member`.
+    pub fn invalid_chunk_count(&self, x_target: &BigUint, n_target_bits: usize) -> Result<usize, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+        Ok(self.base_chunks(x_target, n_target_bits).filter(|chunk| !self.is_base_value(chunk)).count())
+    }
+
+    /// Explains why `x_target` is or is not a member of S_N at `n_target_bits`.
+    ///
+    /// For a non-member, the value is split level by level, each time descending into the
+    /// first part that is not a member of the level below, until an S_base-width block
+    /// that is not in S_base is reached. The block and the path to it are reported. Under
+    /// `ExactlyOne` the descent instead stops at a block with more than one member part.
+    ///
+    /// # Errors
+    /// Returns the same validation errors as `is_member`.
+    pub fn explain_membership(&self, x_target: &BigUint, n_target_bits: usize) -> Result<MembershipExplanation, HierarchyError> {
+        if self.is_member(x_target, n_target_bits)? {
+            return Ok(MembershipExplanation::IsMember);
+        }
+
+        let mut value = x_target.clone();
+        let mut n_bits = n_target_bits;
+        let mut path = Vec::new();
+        while n_bits > self.initial_pattern.n_base_bits {
+            let n_part_bits = n_bits / self.arity;
+            let parts: Vec<(BigUint, bool)> = self
+                .split_parts(&value, n_bits)
+                .map(|part| {
+                    let is_member = self._is_member_iterative(&part, n_part_bits);
+                    (part, is_member)
+                })
+                .collect();
+            // Under `ExactlyOne` a value with several member parts fails at its own level.
+            if self.rule == MembershipRule::ExactlyOne && parts.iter().filter(|(_, is_member)| *is_member).count() > 1 {
+                break;
+            }
+            let (index, (part, _)) = parts
+                .into_iter()
+                .enumerate()
+                .find(|(_, (_, is_member))| !is_member)
+                .expect("a non-member has a non-member part unless it has several member parts");
+            path.push(index);
+            value = part;
+            n_bits = n_part_bits;
+        }
+        Ok(MembershipExplanation::NotMember { value, n_bits, path })
+    }
+
+    /// Returns the full evaluation of the membership check of `x_target` at
+    /// `n_target_bits` as a tree mirroring the recursive definition: each node records a
+    /// sub-block, its width, whether it is a member, and its parts.
+    ///
+    /// Every part is evaluated rather than stopping at the first failure, and the whole
+    /// tree is materialized, so this is meant for teaching and debugging rather than bulk
+    /// checks. The root's `passed` always equals `is_member`.
+    ///
+    /// # Errors
+    /// Returns the same validation errors as `is_member`.
+    pub fn trace_membership(&self, x_target: &BigUint, n_target_bits: usize) -> Result<MembershipTrace, HierarchyError> {
+        self.validate_member_query(x_target, n_target_bits)?;
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let leaves = self
+            .base_chunks(x_target, n_target_bits)
+            .map(|chunk| MembershipTrace { passed: self.is_base_value(&chunk), value: chunk, n_bits: n_base_bits, children: Vec::new() })
+            .collect();
+        let mut trace = MembershipTrace::from_leaves(leaves, self.arity, self.rule);
+        if self.split_strategy != SplitStrategy::HighLow {
+            self.relayout_trace(&mut trace);
+        }
+        Ok(trace)
+    }
+
+    /// Rearranges the value of every node of a trace joined from consecutive windows with
+    /// `split_layout`, so each node holds its sub-block as the split strategy lays it out.
+    fn relayout_trace(&self, trace: &mut MembershipTrace) {
+        trace.value = self.split_layout(core::mem::take(&mut trace.value), trace.n_bits, self.initial_pattern.n_base_bits);
+        for child in &mut trace.children {
+            self.relayout_trace(child);
+        }
+    }
+
+    /// Produces a witness recording that `x_target` is a member of S_N at `n_target_bits`:
+    /// its S_base components and the fingerprint of the current pattern.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NotAMember` if `x_target` is not a member of S_N, or the
+    /// usual `is_member` validation errors.
+    pub fn membership_witness(&self, x_target: &BigUint, n_target_bits: usize) -> Result<MembershipWitness, HierarchyError> {
+        let components = self.decompose_to_base(x_target, n_target_bits)?;
+        Ok(MembershipWitness {
+            value: x_target.clone(),
+            n_bits: n_target_bits,
+            components,
+            pattern_fingerprint: self.initial_pattern.fingerprint(),
+        })
+    }
+
+    /// Re-checks a witness against the current pattern.
+    ///
+    /// Returns `Ok(true)` only if the witness was produced with a pattern of the same
+    /// fingerprint, it has one component per S_base-width chunk, every component is in
+    /// S_base, and the components compose back to the witnessed value.
+    ///
+    /// # Errors
+    /// Returns the usual `is_member` validation errors if `w.n_bits` is not a valid level
+    /// or `w.value` does not fit within it.
+    pub fn verify_witness(&self, w: &MembershipWitness) -> Result<bool, HierarchyError> {
+        self.validate_member_query(&w.value, w.n_bits)?;
+
+        if w.pattern_fingerprint != self.initial_pattern.fingerprint()
+            || w.components.len() != w.n_bits / self.initial_pattern.n_base_bits
+            || !w.components.iter().all(|comp| self.is_base_value(comp))
+        {
+            return Ok(false);
+        }
+        let (composed, _) = self.compose_from_base(&w.components)?;
+        Ok(composed == w.value)
+    }
+
+    /// Finds a member of S_N closest to `x_target` in Hamming distance, returning the
+    /// member and its distance.
+    ///
+    /// Membership constrains each S_base-width chunk independently, so the nearest member
+    /// is found by replacing every chunk with its closest S_base value. This is exact and
+    /// takes O(chunks × |S_base|). Ties are broken towards the smallest S_base value, and
+    /// a value that is already a member is returned unchanged with distance 0.
+    ///
+    /// # Errors
+    /// Returns the same validation errors as `is_member`.
+    pub fn nearest_member_hamming(&self, x_target: &BigUint, n_target_bits: usize) -> Result<(BigUint, u64), HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+
+        let mut nearest = BigUint::default();
+        let mut total_distance = 0u64;
+        for chunk in self.base_chunks(x_target, n_target_bits) {
+            let (best_value, best_distance) = self.nearest_base_value_hamming(&chunk);
+            nearest = (nearest << self.initial_pattern.n_base_bits) | best_value;
+            total_distance += best_distance;
+        }
+        Ok((self.split_layout(nearest, n_target_bits, self.initial_pattern.n_base_bits), total_distance))
+    }
+
+    /// Returns the Hamming distance from `x_target` to the nearest member of S_N at
+    /// `n_target_bits`: the sum over its S_base-width chunks of each chunk's distance to
+    /// the closest S_base value. This is the distance reported by
+    /// `nearest_member_hamming`, without building the member, and is 0 exactly for
+    /// members.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::DecompositionUndefinedForRule` unless the rule is
+    /// `BothHalves`, or the same validation errors as `is_member`.
+    pub fn hamming_distance_to_set(&self, x_target: &BigUint, n_target_bits: usize) -> Result<u64, HierarchyError> {
+        Ok(self.chunk_hamming_distances(x_target, n_target_bits)?.iter().sum())
+    }
+
+    /// Returns, for each S_base-width chunk of `x_target` (most significant first), its
+    /// Hamming distance to the closest S_base value. The entries sum to
+    /// `hamming_distance_to_set`.
+    ///
+    /// # Errors
+    /// Returns the same errors as `hamming_distance_to_set`.
+    pub fn chunk_hamming_distances(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Vec<u64>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+        Ok(self
+            .base_chunks(x_target, n_target_bits)
+            .map(|chunk| self.nearest_base_value_hamming(&chunk).1)
+            .collect())
+    }
+
+    /// Replaces every S_base-width chunk of `x_target` that is not in S_base with an
+    /// S_base value chosen by `strategy`, leaving valid chunks untouched.
+    /// The result is always a member of S_N at `n_target_bits`.
+    ///
+    /// Returns the repaired value and the number of chunks that were replaced; a count
+    /// of 0 means `x_target` was already a member and is returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level.
+    /// * `x_target` is too large for `n_target_bits`.
+    pub fn repair(&self, x_target: &BigUint, n_target_bits: usize, strategy: RepairStrategy) -> Result<(BigUint, usize), HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)?;
+
+        let mut repaired = BigUint::default();
+        let mut replaced = 0usize;
+        for chunk in self.base_chunks(x_target, n_target_bits) {
+            let component = if self.is_base_value(&chunk) {
+                chunk
+            } else {
+                replaced += 1;
+                match strategy {
+                    RepairStrategy::NearestHamming => self.nearest_base_value_hamming(&chunk).0.clone(),
+                    RepairStrategy::NearestNumeric => self.nearest_base_value_numeric(&chunk).clone(),
+                    RepairStrategy::SmallestBaseValue => self.sorted_base_values[0].clone(),
+                }
+            };
+            repaired = (repaired << self.initial_pattern.n_base_bits) | component;
+        }
+        Ok((self.split_layout(repaired, n_target_bits, self.initial_pattern.n_base_bits), replaced))
+    }
+
+    /// Returns the S_base value closest to `chunk` in Hamming distance, together with
+    /// that distance. Ties are broken toward the smallest value.
+    fn nearest_base_value_hamming(&self, chunk: &BigUint) -> (&BigUint, u64) {
+        let mut best: Option<(&BigUint, u64)> = None;
+        // Sorted order makes the first minimum the smallest value among ties.
+        for candidate in &self.sorted_base_values {
+            let distance = hamming_distance(candidate, chunk);
+            if best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                best = Some((candidate, distance));
+            }
+        }
+        best.expect("S_base_values cannot be empty due to InitialPattern::new")
+    }
+
+    /// Returns the S_base value numerically closest to `chunk`. Ties are broken toward
+    /// the smaller value.
+    fn nearest_base_value_numeric(&self, chunk: &BigUint) -> &BigUint {
+        let values = &self.sorted_base_values;
+        let above = values.partition_point(|value| value < chunk);
+        match (above.checked_sub(1).map(|i| &values[i]), values.get(above)) {
+            (Some(below), Some(above)) => {
+                if chunk - below <= above - chunk { below } else { above }
+            }
+            (Some(below), None) => below,
+            (None, Some(above)) => above,
+            (None, None) => unreachable!("S_base_values cannot be empty due to InitialPattern::new"),
+        }
+    }
+
+    /// Validates the inputs of a membership query: `n_target_bits` must be a valid
+    /// hierarchical level and `x_target` must fit within it.
+    pub(crate) fn validate_member_query(&self, x_target: &BigUint, n_target_bits: usize) -> Result<(), HierarchyError> {
+        if n_target_bits == 0 {
+             return Err(HierarchyError::InvalidHierarchicalLevel { 
+                target_n_bits: n_target_bits,
+                base_n_bits: self.initial_pattern.n_base_bits 
+            });
+        }
+        
+        if x_target.bits() > n_target_bits as u64 {
+            return Err(HierarchyError::ValueTooLargeForNBits {
+                value: x_target.clone(),
+                n_bits: n_target_bits,
+            });
+        }
+
+        if !self.is_valid_hierarchical_level(n_target_bits) {
+            return Err(HierarchyError::InvalidHierarchicalLevel { 
+                target_n_bits: n_target_bits,
+                base_n_bits: self.initial_pattern.n_base_bits 
+            });
+        }
+        Ok(())
+    }
+
+    /// Splits an `n_current_bits`-wide value into its `arity` equal parts, most
+    /// significant first, following the split strategy. With the default arity and
+    /// strategy these are its upper and lower halves.
+    pub(crate) fn split_parts(&self, x_current: &BigUint, n_current_bits: usize) -> BaseChunks {
+        let n_part_bits = n_current_bits / self.arity;
+        BaseChunks::new(&self.high_low_layout(x_current, n_current_bits, n_part_bits), n_part_bits, self.arity)
+    }
+    
+    /// Returns the S_base-width components of `x_target`, most significant first.
+    ///
+    /// Splitting a value level by level ends in its consecutive `n_base_bits`-wide windows,
+    /// once rearranged by `high_low_layout`, so these are read off directly from the
+    /// value's digits. `n_target_bits` must already be a valid hierarchical level.
+    pub(crate) fn base_chunks(&self, x_target: &BigUint, n_target_bits: usize) -> BaseChunks {
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        BaseChunks::new(&self.high_low_layout(x_target, n_target_bits, n_base_bits), n_base_bits, n_target_bits / n_base_bits)
+    }
+
+    /// Checks every S_base-width component of `x_target` without recursion, so arbitrarily
+    /// deep levels cannot overflow the call stack, and without building per-level masks.
+    /// Components are visited most significant first and the scan stops at the first
+    /// component outside S_base.
+    ///
+    /// Under `EitherHalf` a value is a member if any part is, recursively down to the
+    /// S_base-width chunks, so the scan instead stops at the first chunk in S_base. Under
+    /// `ExactlyOne` every chunk is looked up and the results are combined level by level.
+    ///
+    /// If `precompute_levels` materialized a level no wider than `n_target_bits`, chunks of
+    /// that level's width are looked up in its members instead.
+    ///
+    /// Under `Interleaved` the value is first rearranged so its S_base components are
+    /// consecutive windows, which precomputed levels store in the same arrangement.
+    fn _is_member_iterative(&self, x_target: &BigUint, n_target_bits: usize) -> bool {
+        let x_target = self.high_low_layout(x_target, n_target_bits, self.initial_pattern.n_base_bits);
+        let x_target = x_target.as_ref();
+        if let Some((level_n_bits, table)) = &self.precomputed_level {
+            if *level_n_bits <= n_target_bits {
+                let chunks = SmallChunks::new(x_target, *level_n_bits, n_target_bits / level_n_bits);
+                return self.rule.combine(chunks, |chunk| table.contains(chunk));
+            }
+        }
+        match &self.small_base_values {
+            Some(small_base_values) => {
+                let n_base_bits = self.initial_pattern.n_base_bits;
+                let chunks = SmallChunks::new(x_target, n_base_bits, n_target_bits / n_base_bits);
+                self.rule.combine_leaves(chunks, self.arity, |chunk| small_base_values.contains(chunk))
+            }
+            None => self
+                .rule
+                .combine_leaves(self.base_chunks(x_target, n_target_bits), self.arity, |chunk| self.is_base_value(&chunk)),
+        }
+    }
+
+    /// Returns `true` if `value` is in S_base, by binary search over the sorted values.
+    pub(crate) fn is_base_value(&self, value: &BigUint) -> bool {
+        match &self.small_base_values {
+            Some(small_base_values) => value
+                .to_u64()
+                .is_some_and(|value| small_base_values.contains(value)),
+            None => self.sorted_base_values.binary_search(value).is_ok(),
+        }
+    }
+
+    /// Decomposes a given X-value (`x_target`), known to be a member of S_N,
+    /// into its constituent S_base components.
+    ///
+    /// Each component is checked against S_base as it is read, so no separate membership
+    /// pass is made.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NotAMember` if `x_target` is not a member of S_N, or the
+    /// usual `is_member` validation errors.
+    pub fn decompose_to_base(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Vec<BigUint>, HierarchyError> {
+        self.validate_decomposition(x_target, n_target_bits)?;
+        let mut components = Vec::with_capacity(n_target_bits / self.initial_pattern.n_base_bits);
+        self.for_each_component_checked(x_target, n_target_bits, |component| components.push(component))?;
+        Ok(components)
+    }
+
+    /// Checks everything about a decomposition but the components themselves: the rule,
+    /// the level and the width of `x_target`. Callers run it before sizing any output by
+    /// the component count.
+    fn validate_decomposition(&self, x_target: &BigUint, n_target_bits: usize) -> Result<(), HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_member_query(x_target, n_target_bits)
+    }
+
+    /// Passes each S_base component of `x_target`, which must have passed
+    /// `validate_decomposition`, to `f`, most significant first, checking every component
+    /// against S_base as it is read rather than in a separate membership pass. `f` may
+    /// therefore see the leading components of a non-member before
+    /// `HierarchyError::NotAMember` is returned, so callers must discard partial output
+    /// on error.
+    fn for_each_component_checked<F>(&self, x_target: &BigUint, n_target_bits: usize, mut f: F) -> Result<(), HierarchyError>
+    where
+        F: FnMut(BigUint),
+    {
+        for component in self.base_chunks(x_target, n_target_bits) {
+            if !self.is_base_value(&component) {
+                return Err(HierarchyError::NotAMember(x_target.clone()));
+            }
+            f(component);
+        }
+        Ok(())
+    }
+
+    /// Splits `x_target` into its S_base-width components like `decompose_to_base`, but
+    /// without checking that `n_target_bits` is a valid level, that `x_target` fits in it,
+    /// or that the components are in S_base. Only for inputs already known to be members.
+    ///
+    /// For any other input the components are those of `x_target` truncated to
+    /// `n_target_bits`, cut into `n_target_bits / n_base_bits` windows; the result is then
+    /// meaningless but the call remains memory-safe. Debug builds assert that the level is
+    /// valid.
+    pub fn decompose_to_base_unchecked(&self, x_target: &BigUint, n_target_bits: usize) -> Vec<BigUint> {
+        debug_assert!(self.is_valid_hierarchical_level(n_target_bits), "{n_target_bits} is not a valid hierarchical level");
+        self.base_chunks(x_target, n_target_bits).collect()
+    }
+
+    /// Decomposes a member of S_N into its S_base components like `decompose_to_base`, but
+    /// writes them into `out` instead of a fresh vector, and returns the component count.
+    ///
+    /// `out` is overwritten in place: existing elements are reused with `clone_from`, so
+    /// repeated decompositions at the same level keep both the vector's and the
+    /// components' allocations. Afterwards `out` holds exactly the components, or nothing
+    /// if an error is returned.
+    ///
+    /// # Errors
+    /// Returns the same errors as `decompose_to_base`.
+    pub fn decompose_to_base_into(&self, x_target: &BigUint, n_target_bits: usize, out: &mut Vec<BigUint>) -> Result<usize, HierarchyError> {
+        let mut filled = 0;
+        let result = self.validate_decomposition(x_target, n_target_bits).and_then(|()| {
+            self.for_each_component_checked(x_target, n_target_bits, |component| {
+                match out.get_mut(filled) {
+                    Some(slot) => slot.clone_from(&component),
+                    None => out.push(component),
+                }
+                filled += 1;
+            })
+        });
+        out.truncate(if result.is_ok() { filled } else { 0 });
+        result.map(|()| filled)
+    }
+
+    /// Decomposes the big-endian value in `bytes_be` like `decompose_to_base`, returning
+    /// each component as big-endian bytes zero-padded to exactly `ceil(n_base_bits / 8)`
+    /// bytes. The input follows the rules of `is_member_bytes`.
+    ///
+    /// # Errors
+    /// Returns the same errors as `decompose_to_base`.
+    pub fn decompose_to_base_bytes(&self, bytes_be: &[u8], n_target_bits: usize) -> Result<Vec<Vec<u8>>, HierarchyError> {
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let x_target = BigUint::from_bytes_be(bytes_be);
+        self.validate_decomposition(&x_target, n_target_bits)?;
+        let mut components = Vec::with_capacity(n_target_bits / n_base_bits);
+        self.for_each_component_checked(&x_target, n_target_bits, |component| {
+            components.push(to_fixed_bytes_be(&component, n_base_bits));
+        })?;
+        Ok(components)
+    }
+
+    /// Decomposes each `(value, n_target_bits)` pair like `decompose_to_base`, in parallel
+    /// on the rayon thread pool. The results are in the same order as `targets`, and each
+    /// is exactly what `decompose_to_base` returns for that pair.
+    #[cfg(feature = "rayon")]
+    pub fn decompose_batch(&self, targets: &[(BigUint, usize)]) -> Vec<Result<Vec<BigUint>, HierarchyError>> {
+        targets
+            .par_iter()
+            .map(|(x_target, n_target_bits)| self.decompose_to_base(x_target, *n_target_bits))
+            .collect()
+    }
+
+    /// Decomposes a member of S_N into its S_base components like `decompose_to_base`,
+    /// but passes each component to `sink`, most significant first, instead of collecting
+    /// them. Only one component is held at a time, so memory does not grow with the
+    /// number of components.
+    ///
+    /// `sink` can stop the decomposition early by returning `ControlFlow::Break`.
+    /// Returns the number of components passed to `sink`, including the one it broke on.
+    /// Nothing is emitted unless `x_target` is a member.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NotAMember` if `x_target` is not a member of S_N, or the
+    /// usual `is_member` validation errors.
+    pub fn decompose_to_base_streaming<F>(&self, x_target: &BigUint, n_target_bits: usize, mut sink: F) -> Result<usize, HierarchyError>
+    where
+        F: FnMut(&BigUint) -> ControlFlow<()>,
+    {
+        self.require_decomposable_rule()?;
+        if !self.is_member(x_target, n_target_bits)? {
+            return Err(HierarchyError::NotAMember(x_target.clone()));
+        }
+
+        let mut emitted = 0;
+        for component in self.base_chunks(x_target, n_target_bits) {
+            emitted += 1;
+            if sink(&component).is_break() {
+                break;
+            }
+        }
+        Ok(emitted)
+    }
+
+    /// Compares the S_base components of two members of S_N at `n_target_bits`, returning
+    /// the positions where they differ in ascending order. The result is empty exactly
+    /// when `a == b`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NotAMember` with the offending value if `a` or `b` is not
+    /// a member of S_N, or the usual `is_member` validation errors.
+    pub fn diff_members(&self, a: &BigUint, b: &BigUint, n_target_bits: usize) -> Result<Vec<ChunkDiff>, HierarchyError> {
+        Ok(self
+            .decompose_pair(a, b, n_target_bits)?
+            .enumerate()
+            .filter(|(_, (a_value, b_value))| a_value != b_value)
+            .map(|(leaf_index, (a_value, b_value))| ChunkDiff { leaf_index, a_value, b_value })
+            .collect())
+    }
+
+    /// Returns, for each S_base component position of two members of S_N at
+    /// `n_target_bits`, whether their components there are equal, most significant first.
+    /// The number of `false` entries is the distance between the members in leaf space.
+    ///
+    /// # Errors
+    /// Returns the same errors as `diff_members`.
+    pub fn positional_component_diff(&self, a: &BigUint, b: &BigUint, n_target_bits: usize) -> Result<Vec<bool>, HierarchyError> {
+        Ok(self.decompose_pair(a, b, n_target_bits)?.map(|(a_value, b_value)| a_value == b_value).collect())
+    }
+
+    /// Decomposes two members of S_N at the same level and pairs up their components.
+    fn decompose_pair(&self, a: &BigUint, b: &BigUint, n_target_bits: usize) -> Result<impl Iterator<Item = (BigUint, BigUint)>, HierarchyError> {
+        let a_components = self.decompose_to_base(a, n_target_bits)?;
+        let b_components = self.decompose_to_base(b, n_target_bits)?;
+        Ok(a_components.into_iter().zip(b_components))
+    }
+
+    /// Decomposes a member of S_N into its S_base components like `decompose_to_base`,
+    /// in the same order, recording where each component sits in `x_target`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NotAMember` if `x_target` is not a member of S_N,
+    /// `UnsupportedSplitStrategy` unless the split strategy is `HighLow`, or the usual
+    /// `is_member` validation errors.
+    pub fn decompose_with_offsets(&self, x_target: &BigUint, n_target_bits: usize) -> Result<Vec<ComponentAt>, HierarchyError> {
+        self.require_high_low_split()?;
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        self.validate_decomposition(x_target, n_target_bits)?;
+        let mut components = Vec::with_capacity(n_target_bits / n_base_bits);
+        self.for_each_component_checked(x_target, n_target_bits, |value| {
+            let leaf_index = components.len();
+            components.push(ComponentAt { value, leaf_index, bit_offset: n_target_bits - (leaf_index + 1) * n_base_bits });
+        })?;
+        Ok(components)
+    }
+
+    /// Decomposes a member of S_N into its full decomposition hierarchy: the root holds
+    /// `x_target`, each internal node its two parts (its upper and lower halves under
+    /// `HighLow`), and the leaves the S_base components.
+    ///
+    /// The tree is assembled bottom-up from the leaves, so building it does not recurse.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::UnsupportedArity` if the arity is not 2, since
+    /// `DecompNode` is a binary tree. Otherwise returns
+    /// `HierarchyError::NotAMember` if `x_target` is not a member of S_N, or the
+    /// usual `is_member` validation errors.
+    pub fn decompose_to_tree(&self, x_target: &BigUint, n_target_bits: usize) -> Result<DecompNode, HierarchyError> {
+        if self.arity != 2 {
+            return Err(HierarchyError::UnsupportedArity(self.arity));
+        }
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let leaves = self
+            .decompose_to_base(x_target, n_target_bits)?
+            .into_iter()
+            .map(|component| DecompNode::leaf(component, n_base_bits))
+            .collect();
+        let mut tree = DecompNode::from_leaves(leaves);
+        if self.split_strategy != SplitStrategy::HighLow {
+            self.relayout_tree(&mut tree);
+        }
+        Ok(tree)
+    }
+
+    /// Rearranges the value of every node of a decomposition tree like `relayout_trace`.
+    fn relayout_tree(&self, node: &mut DecompNode) {
+        let n_bits = node.n_bits();
+        let value = node.value_mut();
+        *value = self.split_layout(core::mem::take(value), n_bits, self.initial_pattern.n_base_bits);
+        if let DecompNode::Internal { upper, lower, .. } = node {
+            self.relayout_tree(upper);
+            self.relayout_tree(lower);
+        }
+    }
+
+    /// Decomposes a member of S_N at `n_target_bits` into its components at the
+    /// intermediate level `stop_n_bits`, most significant first.
+    ///
+    /// Each returned component is itself a member of S_{stop_n_bits}. With
+    /// `stop_n_bits == n_base_bits` this is exactly `decompose_to_base`, and with
+    /// `stop_n_bits == n_target_bits` it returns `x_target` alone.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `stop_n_bits` is smaller than `n_base_bits` (`TargetNBitsTooSmall`).
+    /// * `stop_n_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `stop_n_bits` is larger than `n_target_bits` (`StopLevelTooLarge`).
+    /// * `x_target` is not a member of S_N at `n_target_bits` (`NotAMember`), or the
+    ///   usual `is_member` validation fails.
+    pub fn decompose_to_level(&self, x_target: &BigUint, n_target_bits: usize, stop_n_bits: usize) -> Result<Vec<BigUint>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let base_n_bits = self.initial_pattern.n_base_bits;
+        if stop_n_bits < base_n_bits {
+            return Err(HierarchyError::TargetNBitsTooSmall { target_n_bits: stop_n_bits, base_n_bits });
+        }
+        if !self.is_valid_hierarchical_level(stop_n_bits) {
+            return Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: stop_n_bits, base_n_bits });
+        }
+        if stop_n_bits > n_target_bits {
+            return Err(HierarchyError::StopLevelTooLarge { stop_n_bits, target_n_bits: n_target_bits });
+        }
+        if !self.is_member(x_target, n_target_bits)? {
+            return Err(HierarchyError::NotAMember(x_target.clone()));
+        }
+
+        let mut components = Vec::new();
+        self._decompose_recursive_collect(x_target, n_target_bits, stop_n_bits, &mut components);
+        Ok(components)
+    }
+
+    fn _decompose_recursive_collect(&self, current_x: &BigUint, current_n_bits: usize, stop_n_bits: usize, components: &mut Vec<BigUint>) {
+        if current_n_bits == stop_n_bits {
+            components.push(current_x.clone());
+            return;
+        }
+
+        let n_part_bits = current_n_bits / self.arity;
+        for part in self.split_parts(current_x, current_n_bits) {
+            self._decompose_recursive_collect(&part, n_part_bits, stop_n_bits, components);
+        }
+    }
+
+    /// Composes an S_N member from a sequence of its S_base components.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * The number of components is not a non-zero power of the arity (`InvalidComponentCount`).
+    /// * A component is not in S_base (`InvalidBaseComponent`).
+    pub fn compose_from_base(&self, s_base_components: &[BigUint]) -> Result<(BigUint, usize), HierarchyError> {
+        self.require_decomposable_rule()?;
+        let num_components = s_base_components.len();
+        if !self.is_valid_component_count(num_components) {
+            return Err(HierarchyError::InvalidComponentCount(s_base_components.len()));
+        }
+
+        let one = BigUint::one();
+        let limit_exclusive_base = &one << self.initial_pattern.n_base_bits;
+
+        for comp in s_base_components {
+            if !self.is_base_value(comp) {
+                return Err(HierarchyError::InvalidBaseComponent(comp.clone()));
+            }
+            if *comp >= limit_exclusive_base {
+                 let max_val = limit_exclusive_base - &one;
+                return Err(HierarchyError::ValueExceedsNBaseBits {
+                    value: comp.clone(),
+                    n_bits: self.initial_pattern.n_base_bits,
+                    max_val,
+                });
+            }
+        }
+        
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let (composed, n_bits) = self._compose_recursive(s_base_components, n_base_bits);
+        Ok((self.split_layout(composed, n_bits, n_base_bits), n_bits))
+    }
+
+    /// Composes an S_N member from S_base components like `compose_from_base`, but
+    /// without checking that the components are in S_base. Only for components already
+    /// known to be valid. The result is a member of S_N only if they are.
+    ///
+    /// # Panics
+    /// Panics if `components` is empty. Debug builds also assert that the count is a
+    /// power of the arity and that every component fits in `n_base_bits`; otherwise the
+    /// composed value is unspecified, and an oversized component may cause a panic.
+    pub fn compose_from_base_unchecked(&self, components: &[BigUint]) -> (BigUint, usize) {
+        assert!(!components.is_empty(), "cannot compose zero components");
+        debug_assert!(
+            self.is_valid_component_count(components.len()),
+            "{} components is not a power of the arity",
+            components.len()
+        );
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let n_bits = n_base_bits * components.len();
+        let mut digits = vec![0u32; n_bits.div_ceil(32)];
+        for (index, comp) in components.iter().enumerate() {
+            debug_assert!(comp.bits() <= n_base_bits as u64, "component {comp} does not fit in {n_base_bits} bits");
+            write_window(&mut digits, n_bits - (index + 1) * n_base_bits, comp);
+        }
+        (self.split_layout(BigUint::new(digits), n_bits, n_base_bits), n_bits)
+    }
+
+    /// Composes an S_N member like `compose_from_base` and re-checks the result with
+    /// `is_member` before returning it. For valid components this returns exactly what
+    /// `compose_from_base` returns.
+    ///
+    /// # Errors
+    /// Returns the same errors as `compose_from_base`, or
+    /// `HierarchyError::ComposedValueNotMember` if the composed value fails the check.
+    pub fn compose_checked(&self, components: &[BigUint]) -> Result<(BigUint, usize), HierarchyError> {
+        let (value, n_bits) = self.compose_from_base(components)?;
+        if !self.is_member(&value, n_bits)? {
+            return Err(HierarchyError::ComposedValueNotMember { value, n_bits });
+        }
+        Ok((value, n_bits))
+    }
+
+    /// Composes an S_N member from S_base components after padding them with copies of
+    /// `fill` up to the next valid component count (the next power of the arity, so the
+    /// next power of 2 by default).
+    ///
+    /// Returns the composed value, its bit-width, and the number of fill components added.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `components` is empty (`InvalidComponentCount`).
+    /// * `fill` or any component is not in S_base (`InvalidBaseComponent`).
+    pub fn compose_from_base_padded(&self, components: &[BigUint], fill: &BigUint, pad_side: PadSide) -> Result<(BigUint, usize, usize), HierarchyError> {
+        self.require_decomposable_rule()?;
+        let num_components = components.len();
+        if num_components == 0 {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+        if !self.is_base_value(fill) {
+            return Err(HierarchyError::InvalidBaseComponent(fill.clone()));
+        }
+
+        let mut padded_count = 1usize;
+        while padded_count < num_components {
+            padded_count = padded_count
+                .checked_mul(self.arity)
+                .ok_or(HierarchyError::InvalidComponentCount(num_components))?;
+        }
+        let padding = padded_count - num_components;
+
+        let mut padded = Vec::with_capacity(padded_count);
+        if pad_side == PadSide::Leading {
+            padded.resize(padding, fill.clone());
+        }
+        padded.extend_from_slice(components);
+        padded.resize(padded_count, fill.clone());
+
+        let (value, n_bits) = self.compose_from_base(&padded)?;
+        Ok((value, n_bits, padding))
+    }
+
+    /// Checks every component that would be passed to `compose_from_base` and reports all
+    /// problems instead of stopping at the first one.
+    ///
+    /// A component that does not fit in `n_base_bits` is reported as `ValueExceedsNBaseBits`,
+    /// and one that fits but is not in S_base as `InvalidBaseComponent`.
+    pub fn validate_components(&self, components: &[BigUint]) -> ComponentValidation {
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let one = BigUint::one();
+        let limit_exclusive_base = &one << n_base_bits;
+
+        let invalid_indices = components
+            .iter()
+            .enumerate()
+            .filter_map(|(index, comp)| {
+                if *comp >= limit_exclusive_base {
+                    let max_val = &limit_exclusive_base - &one;
+                    Some((index, HierarchyError::ValueExceedsNBaseBits { value: comp.clone(), n_bits: n_base_bits, max_val }))
+                } else if !self.is_base_value(comp) {
+                    Some((index, HierarchyError::InvalidBaseComponent(comp.clone())))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        ComponentValidation { invalid_indices, count_ok: self.is_valid_component_count(components.len()) }
+    }
+
+    /// Composes an S_N member from its S_base components, most significant first, like
+    /// `compose_from_base` but consuming them from an iterator.
+    ///
+    /// Each component is validated and written straight into the result's digits as it is
+    /// consumed, so no intermediate vectors or partial results are built and components are
+    /// never cloned. The component count is taken from `len()` up front.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * The number of components is not a non-zero power of the arity (`InvalidComponentCount`).
+    /// * A component is not in S_base (`InvalidBaseComponent`).
+    pub fn compose_from_iter<I>(&self, components: I) -> Result<(BigUint, usize), HierarchyError>
+    where
+        I: ExactSizeIterator<Item = BigUint>,
+    {
+        self.require_decomposable_rule()?;
+        let num_components = components.len();
+        if !self.is_valid_component_count(num_components) {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let n_bits = n_base_bits
+            .checked_mul(num_components)
+            .ok_or(HierarchyError::InvalidComponentCount(num_components))?;
+
+        let mut digits = vec![0u32; n_bits.div_ceil(32)];
+        let mut consumed = 0;
+        for comp in components.take(num_components) {
+            if !self.is_base_value(&comp) {
+                return Err(HierarchyError::InvalidBaseComponent(comp));
+            }
+            consumed += 1;
+            write_window(&mut digits, n_bits - consumed * n_base_bits, &comp);
+        }
+        if consumed != num_components {
+            return Err(HierarchyError::InvalidComponentCount(consumed));
+        }
+        Ok((self.split_layout(BigUint::new(digits), n_bits, n_base_bits), n_bits))
+    }
+
+    /// Composes an S_N member from a sequence of members of an intermediate level
+    /// S_{component_n_bits}, most significant first.
+    ///
+    /// This is the inverse of `decompose_to_level` and lets verified mid-level blocks be
+    /// reused. Returns the composed value and its bit-width
+    /// `component_n_bits * components.len()`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * The number of components is not a non-zero power of the arity (`InvalidComponentCount`).
+    /// * `component_n_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * A component does not fit in `component_n_bits` (`ValueTooLargeForNBits`) or is
+    ///   not a member of S_{component_n_bits} (`NotAMember`).
+    pub fn compose_from_level(&self, components: &[BigUint], component_n_bits: usize) -> Result<(BigUint, usize), HierarchyError> {
+        self.require_decomposable_rule()?;
+        let num_components = components.len();
+        if !self.is_valid_component_count(num_components) {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+        if component_n_bits.checked_mul(num_components).is_none() {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+
+        for comp in components {
+            if !self.is_member(comp, component_n_bits)? {
+                return Err(HierarchyError::NotAMember(comp.clone()));
+            }
+        }
+
+        let (composed, n_bits) = self._compose_recursive(components, component_n_bits);
+        Ok((self.split_layout(composed, n_bits, component_n_bits), n_bits))
+    }
+
+    /// Checks if `x_target` is a member of S_N at `n_target_bits` like `is_member`, but
+    /// on a `u64` without any `BigUint` arithmetic.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::LevelExceedsMachineWord` if `n_target_bits` is above 64,
+    /// or the usual `is_member` validation errors.
+    pub fn is_member_u64(&self, x_target: u64, n_target_bits: usize) -> Result<bool, HierarchyError> {
+        let small_base_values = self.validate_member_query_u64(x_target, n_target_bits)?;
+        Ok(self
+            .rule
+            .combine_leaves(self.base_chunks_u64(x_target, n_target_bits), self.arity, |chunk| small_base_values.contains(chunk)))
+    }
+
+    /// Decomposes a member of S_N into its S_base components like `decompose_to_base`,
+    /// but on a `u64`.
+    ///
+    /// # Errors
+    /// Returns the same errors as `is_member_u64`, or `HierarchyError::NotAMember` if
+    /// `x_target` is not a member of S_N.
+    pub fn decompose_to_base_u64(&self, x_target: u64, n_target_bits: usize) -> Result<Vec<u64>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        if !self.is_member_u64(x_target, n_target_bits)? {
+            return Err(HierarchyError::NotAMember(BigUint::from(x_target)));
+        }
+        Ok(self.base_chunks_u64(x_target, n_target_bits).collect())
+    }
+
+    /// Composes an S_N member from its S_base components like `compose_from_base`, but on
+    /// `u64`s. Returns the composed value and its bit-width.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * The number of components is not a non-zero power of the arity (`InvalidComponentCount`).
+    /// * The composed value would be wider than 64 bits (`LevelExceedsMachineWord`).
+    /// * A component is not in S_base (`InvalidBaseComponent`).
+    pub fn compose_from_base_u64(&self, s_base_components: &[u64]) -> Result<(u64, usize), HierarchyError> {
+        self.require_decomposable_rule()?;
+        let num_components = s_base_components.len();
+        if !self.is_valid_component_count(num_components) {
+            return Err(HierarchyError::InvalidComponentCount(num_components));
+        }
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let n_bits = n_base_bits.saturating_mul(num_components);
+        let small_base_values = self.small_base_values_for(n_bits)?;
+
+        let mut composed = 0u64;
+        for &comp in s_base_components {
+            if !small_base_values.contains(comp) {
+                return Err(HierarchyError::InvalidBaseComponent(BigUint::from(comp)));
+            }
+            // Only a single component can be 64 bits wide, so the shift then drops nothing.
+            composed = composed.checked_shl(n_base_bits as u32).unwrap_or(0) | comp;
+        }
+        if self.split_strategy != SplitStrategy::HighLow {
+            composed = self
+                .split_layout(BigUint::from(composed), n_bits, n_base_bits)
+                .to_u64()
+                .expect("rearranging the bits of a u64 keeps it a u64");
+        }
+        Ok((composed, n_bits))
+    }
+
+    /// Validates a `u64` membership query like `validate_member_query` and returns the
+    /// `u64` S_base values to look chunks up in.
+    fn validate_member_query_u64(&self, x_target: u64, n_target_bits: usize) -> Result<&LevelTable, HierarchyError> {
+        self.level_depth(n_target_bits)?;
+        let small_base_values = self.small_base_values_for(n_target_bits)?;
+        if (u64::BITS - x_target.leading_zeros()) as usize > n_target_bits {
+            return Err(HierarchyError::ValueTooLargeForNBits { value: BigUint::from(x_target), n_bits: n_target_bits });
+        }
+        Ok(small_base_values)
+    }
+
+    /// Returns the `u64` S_base values if values of `n_bits` fit in a `u64`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::LevelExceedsMachineWord` if `n_bits` is above 64.
+    fn small_base_values_for(&self, n_bits: usize) -> Result<&LevelTable, HierarchyError> {
+        if n_bits > u64::BITS as usize {
+            return Err(HierarchyError::LevelExceedsMachineWord { n_bits, max_bits: u64::BITS as usize });
+        }
+        Ok(self
+            .small_base_values
+            .as_ref()
+            .expect("n_base_bits <= n_bits <= 64, so the u64 base values exist"))
+    }
+
+    /// Returns the S_base-width components of a `u64` at a level of at most 64 bits, most
+    /// significant first.
+    fn base_chunks_u64(&self, x_target: u64, n_target_bits: usize) -> impl Iterator<Item = u64> {
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let x_target = match self.split_strategy {
+            SplitStrategy::HighLow => x_target,
+            SplitStrategy::Interleaved => self
+                .high_low_layout(&BigUint::from(x_target), n_target_bits, n_base_bits)
+                .to_u64()
+                .expect("rearranging the bits of a u64 keeps it a u64"),
+        };
+        let mask = u64::MAX >> (u64::BITS as usize - n_base_bits);
+        (0..n_target_bits / n_base_bits)
+            .rev()
+            .map(move |position| x_target.checked_shr((position * n_base_bits) as u32).unwrap_or(0) & mask)
+    }
+
+    fn _compose_recursive(&self, components_slice: &[BigUint], leaf_n_bits: usize) -> (BigUint, usize) {
+        if components_slice.len() == 1 {
+            return (components_slice[0].clone(), leaf_n_bits);
+        }
+
+        let part_len = components_slice.len() / self.arity;
+        let mut composed_val = BigUint::default();
+        let mut composed_n_bits = 0;
+        for part in components_slice.chunks(part_len) {
+            let (part_val, part_n_bits) = self._compose_recursive(part, leaf_n_bits);
+            composed_val = (composed_val << part_n_bits) | part_val;
+            composed_n_bits += part_n_bits;
+        }
+        
+        (composed_val, composed_n_bits)
+    }
+
+    /// Generates a random member of the selected set S_N at `target_n_bits`.
+    ///
+    /// Under `BothHalves` each S_base component is drawn independently. Under the other
+    /// rules the member is drawn uniformly from S_N: at each level the number of member
+    /// parts is drawn in proportion to how many values have that many, and those parts
+    /// are placed at random.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `target_n_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * S_N has no members at `target_n_bits` (`NoMembers`).
+    pub fn generate_random_s_n_member<R: Rng + ?Sized>(&self, target_n_bits: usize, rng: &mut R) -> Result<BigUint, HierarchyError> {
+        self.validate_random_generation(target_n_bits)?;
+        let member = if self.rule == MembershipRule::BothHalves {
+            self._generate_random_recursive(target_n_bits, rng)
+        } else {
+            let level_weights = self.generation_weights(target_n_bits, true)?;
+            self._generate_random_by_rule(level_weights.len() - 1, true, &level_weights, rng)
+        };
+        Ok(self.split_layout(member, target_n_bits, self.initial_pattern.n_base_bits))
+    }
+
+    /// Generates a member of S_N at `n_target_bits` uniformly at random by drawing an index
+    /// below `count_members(n_target_bits)` and mapping it to a member with `nth_member`.
+    ///
+    /// The index is drawn by rejection sampling on its bit length, so every member is
+    /// exactly equally likely, and the result only depends on `rng` and the pattern's
+    /// values, never on hash-set iteration order.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::InvalidHierarchicalLevel` if `n_target_bits` is not a valid
+    /// hierarchical level, or `DecompositionUndefinedForRule` unless the rule is
+    /// `BothHalves`.
+    pub fn generate_uniform_member<R: Rng + ?Sized>(&self, n_target_bits: usize, rng: &mut R) -> Result<BigUint, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let count = self.count_members(n_target_bits)?;
+        self.nth_member(n_target_bits, &rng.gen_biguint_below(&count))
+    }
+
+    /// Generates a random member of S_N at `n_target_bits` whose leading S_base components
+    /// are `prefix_components`, most significant first. The remaining components are drawn
+    /// like `generate_random_s_n_member`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `prefix_components` has more entries than the level has components (`PrefixTooLong`).
+    /// * A prefix component is not in S_base (`InvalidBaseComponent`).
+    pub fn generate_random_member_with_prefix<R: Rng + ?Sized>(&self, n_target_bits: usize, prefix_components: &[BigUint], rng: &mut R) -> Result<BigUint, HierarchyError> {
+        self.require_decomposable_rule()?;
+        self.validate_random_generation(n_target_bits)?;
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        let component_count = n_target_bits / n_base_bits;
+        if prefix_components.len() > component_count {
+            return Err(HierarchyError::PrefixTooLong { prefix_len: prefix_components.len(), component_count });
+        }
+        if let Some(invalid) = prefix_components.iter().find(|comp| !self.is_base_value(comp)) {
+            return Err(HierarchyError::InvalidBaseComponent(invalid.clone()));
+        }
+
+        let mut member = BigUint::default();
+        for comp in prefix_components {
+            member = (member << n_base_bits) | comp;
+        }
+        for _ in prefix_components.len()..component_count {
+            let comp = self.sorted_base_values.choose(rng).expect("S_base_values cannot be empty due to earlier check");
+            member = (member << n_base_bits) | comp;
+        }
+        Ok(self.split_layout(member, n_target_bits, n_base_bits))
+    }
+
+    /// Returns an endless iterator of random members of S_N at `n_target_bits`, drawn like
+    /// `generate_random_s_n_member` from `rng`, which the iterator owns. Members may repeat.
+    ///
+    /// The sequence is reproducible for a seeded `rng`, and can be paused and resumed by
+    /// simply holding on to the iterator. The iterator is `Send` whenever `R` is.
+    ///
+    /// # Errors
+    /// Returns the same errors as `generate_random_s_n_member`, checked once up front.
+    pub fn random_member_stream<'a, R: Rng + 'a>(&'a self, n_target_bits: usize, mut rng: R) -> Result<impl Iterator<Item = BigUint> + 'a, HierarchyError> {
+        self.validate_random_generation(n_target_bits)?;
+        let level_weights = match self.rule {
+            MembershipRule::BothHalves => None,
+            _ => Some(self.generation_weights(n_target_bits, true)?),
+        };
+        Ok(core::iter::repeat_with(move || {
+            let member = match &level_weights {
+                None => self._generate_random_recursive(n_target_bits, &mut rng),
+                Some(level_weights) => self._generate_random_by_rule(level_weights.len() - 1, true, level_weights, &mut rng),
+            };
+            self.split_layout(member, n_target_bits, self.initial_pattern.n_base_bits)
+        }))
+    }
+
+    /// Returns, for every level up to `n_target_bits`, the weights `_generate_random_by_rule`
+    /// draws the number of member parts with, when drawing a member (index 1) or a
+    /// non-member (index 0) of that level. The base level has no weights.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError::NoMembers` or `SetIsComplete` if there is no value of the
+    /// requested kind (a member if `member` is `true`) at `n_target_bits`.
+    fn generation_weights(&self, n_target_bits: usize, member: bool) -> Result<Vec<[Vec<BigUint>; 2]>, HierarchyError> {
+        let member_counts = self.member_counts(self.level_depth(n_target_bits)?);
+        let count = member_counts.last().expect("there is a count for the base level");
+        if member && *count == BigUint::default() {
+            return Err(HierarchyError::NoMembers { n_bits: n_target_bits });
+        }
+        if !member && *count == BigUint::one() << n_target_bits {
+            return Err(HierarchyError::SetIsComplete { n_base_bits: self.initial_pattern.n_base_bits });
+        }
+
+        let mut level_weights = vec![[Vec::new(), Vec::new()]];
+        for (depth, part_members) in (1..member_counts.len()).zip(&member_counts) {
+            let n_part_bits = self.initial_pattern.n_base_bits * self.arity.pow(depth as u32 - 1);
+            let part_non_members = (BigUint::one() << n_part_bits) - part_members;
+            // `binomial(m, k) * c^k * u^(m - k)` values have exactly `k` member parts.
+            let mut weights = [Vec::with_capacity(self.arity + 1), Vec::with_capacity(self.arity + 1)];
+            let mut binomial = BigUint::one();
+            for member_parts in 0..=self.arity {
+                let weight = &binomial * Pow::pow(part_members, member_parts) * Pow::pow(&part_non_members, self.arity - member_parts);
+                let accepted = self.rule.accepts(member_parts, self.arity);
+                weights[usize::from(accepted)].push(weight);
+                weights[usize::from(!accepted)].push(BigUint::default());
+                binomial = binomial * (self.arity - member_parts) / (member_parts + 1);
+            }
+            level_weights.push(weights);
+        }
+        Ok(level_weights)
+    }
+
+    /// Draws a value uniformly at random among the members, or if `member` is `false` the
+    /// non-members, of the level `n_base_bits * m^depth`, given the weights of every level
+    /// up to it from `generation_weights`. There must be at least one such value.
+    fn _generate_random_by_rule<R: Rng + ?Sized>(&self, depth: usize, member: bool, level_weights: &[[Vec<BigUint>; 2]], rng: &mut R) -> BigUint {
+        if depth == 0 {
+            return if member {
+                self.sorted_base_values.choose(rng).expect("S_base_values cannot be empty due to earlier check").clone()
+            } else {
+                self.random_non_base_value(rng)
+            };
+        }
+
+        let n_part_bits = self.initial_pattern.n_base_bits * self.arity.pow(depth as u32 - 1);
+        let weights = &level_weights[depth][usize::from(member)];
+        let total: BigUint = weights.iter().sum();
+        let mut pick = rng.gen_biguint_below(&total);
+        let member_parts = weights
+            .iter()
+            .position(|weight| {
+                if pick < *weight {
+                    return true;
+                }
+                pick -= weight;
+                false
+            })
+            .expect("the pick is below the total weight");
+
+        let mut is_member_part = vec![false; self.arity];
+        for index in rand::seq::index::sample(rng, self.arity, member_parts) {
+            is_member_part[index] = true;
+        }
+        is_member_part.into_iter().fold(BigUint::default(), |value, is_member| {
+            (value << n_part_bits) | self._generate_random_by_rule(depth - 1, is_member, level_weights, rng)
+        })
+    }
+
+    /// Draws a value uniformly from outside S_base. S_base must not be complete.
+    fn random_non_base_value<R: Rng + ?Sized>(&self, rng: &mut R) -> BigUint {
+        let non_base_count = (BigUint::one() << self.initial_pattern.n_base_bits) - self.sorted_base_values.len();
+        // Map a uniform index among the non-base values to the value itself by stepping
+        // over every S_base value at or below it.
+        let mut value = rng.gen_biguint_below(&non_base_count);
+        for base_value in &self.sorted_base_values {
+            if *base_value > value {
+                break;
+            }
+            value += 1u32;
+        }
+        value
+    }
+
+    /// Checks that random members can be generated at `target_n_bits`: the level must be
+    /// valid and S_base non-empty.
+    fn validate_random_generation(&self, target_n_bits: usize) -> Result<(), HierarchyError> {
+        if !self.is_valid_hierarchical_level(target_n_bits) {
+            return Err(HierarchyError::InvalidHierarchicalLevel {
+                target_n_bits,
+                base_n_bits: self.initial_pattern.n_base_bits,
+            });
+        }
+        if self.initial_pattern.s_base_values.is_empty() {
+            return Err(HierarchyError::EmptySBaseForRandomGeneration);
+        }
+        Ok(())
+    }
+
+    /// Generates `count` distinct random members of the selected set S_N at `n_target_bits`,
+    /// in random order. Unlike repeated calls to `generate_random_s_n_member`, the result
+    /// never contains duplicates, and it is reproducible for a seeded `rng`.
+    ///
+    /// When `count` is at least half of the member count, all members are enumerated and a
+    /// random subset is taken by shuffling, which avoids long rejection loops. Otherwise
+    /// members are drawn with `generate_random_s_n_member` and duplicates are rejected.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `count` exceeds `count_members(n_target_bits)` (`NotEnoughDistinctMembers`).
+    pub fn generate_distinct_random_members<R: Rng + ?Sized>(&self, n_target_bits: usize, count: usize, rng: &mut R) -> Result<Vec<BigUint>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let available = self.count_members(n_target_bits)?;
+        if BigUint::from(count) > available {
+            return Err(HierarchyError::NotEnoughDistinctMembers { requested: count, available });
+        }
+
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        if available <= BigUint::from(count) * 2u32 {
+            let mut members: Vec<BigUint> = self.high_low_members(n_target_bits).collect();
+            let (chosen, _) = members.partial_shuffle(rng, count);
+            return Ok(chosen.iter().map(|member| self.split_layout(member.clone(), n_target_bits, n_base_bits)).collect());
+        }
+
+        let mut seen = HashSet::with_capacity(count);
+        let mut members = Vec::with_capacity(count);
+        while members.len() < count {
+            let candidate = self.split_layout(self._generate_random_recursive(n_target_bits, rng), n_target_bits, n_base_bits);
+            if seen.insert(candidate.clone()) {
+                members.push(candidate);
+            }
+        }
+        Ok(members)
+    }
+
+    /// Draws `count` distinct members of S_N at `n_target_bits` uniformly at random without
+    /// replacement, in random order.
+    ///
+    /// Indices are drawn uniformly below `count_members(n_target_bits)` and mapped to
+    /// members with `nth_member`, so uniformity holds by construction, however large S_N
+    /// is. Indices that were already drawn are redrawn; when `count` is close to the
+    /// member count this needs noticeably more draws than `count`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * The membership rule is not `BothHalves` (`DecompositionUndefinedForRule`).
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * `count` exceeds `count_members(n_target_bits)` (`NotEnoughDistinctMembers`).
+    pub fn sample_members<R: Rng + ?Sized>(&self, n_target_bits: usize, count: usize, rng: &mut R) -> Result<Vec<BigUint>, HierarchyError> {
+        self.require_decomposable_rule()?;
+        let available = self.count_members(n_target_bits)?;
+        if BigUint::from(count) > available {
+            return Err(HierarchyError::NotEnoughDistinctMembers { requested: count, available });
+        }
+
+        let mut drawn = HashSet::with_capacity(count);
+        let mut members = Vec::with_capacity(count);
+        while members.len() < count {
+            let index = rng.gen_biguint_below(&available);
+            if drawn.insert(index.clone()) {
+                members.push(self.nth_member(n_target_bits, &index)?);
+            }
+        }
+        Ok(members)
+    }
+
+    /// Generates a random value at `n_target_bits` that is not a member of S_N, for use as
+    /// negative test data. Under `BothHalves` a random member is drawn like
+    /// `generate_random_s_n_member`, then one S_base component chosen uniformly at random is
+    /// replaced by a value drawn uniformly from outside S_base. Under the other rules the
+    /// non-member is drawn uniformly, level by level as in `generate_random_s_n_member`.
+    ///
+    /// # Errors
+    /// Returns `HierarchyError` if:
+    /// * `n_target_bits` is not a valid hierarchical level (`InvalidHierarchicalLevel`).
+    /// * Every value at `n_target_bits` is a member (`SetIsComplete`), which requires S_base
+    ///   to hold all `2^n_base_bits` values.
+    pub fn generate_random_non_member<R: Rng + ?Sized>(&self, n_target_bits: usize, rng: &mut R) -> Result<BigUint, HierarchyError> {
+        self.validate_random_generation(n_target_bits)?;
+        let n_base_bits = self.initial_pattern.n_base_bits;
+        if self.rule != MembershipRule::BothHalves {
+            let level_weights = self.generation_weights(n_target_bits, false)?;
+            let non_member = self._generate_random_by_rule(level_weights.len() - 1, false, &level_weights, rng);
+            return Ok(self.split_layout(non_member, n_target_bits, n_base_bits));
+        }
+        if BigUint::from(self.sorted_base_values.len()) == BigUint::one() << n_base_bits {
+            return Err(HierarchyError::SetIsComplete { n_base_bits });
+        }
+
+        let replacement = self.random_non_base_value(rng);
+        let member = self._generate_random_recursive(n_target_bits, rng);
+        let shift = rng.gen_range(0..n_target_bits / n_base_bits) * n_base_bits;
+        let mask = (BigUint::one() << n_base_bits) - 1u32;
+        let original = (&member >> shift) & mask;
+        Ok(self.split_layout(member ^ ((original ^ replacement) << shift), n_target_bits, n_base_bits))
+    }
+
+    fn _generate_random_recursive<R: Rng + ?Sized>(&self, current_n_bits: usize, rng: &mut R) -> BigUint {
+        if current_n_bits == self.initial_pattern.n_base_bits {
+            // Choosing from the sorted values keeps the result reproducible for a seeded rng.
+            return self.sorted_base_values.choose(rng).expect("S_base_values cannot be empty due to earlier check").clone();
+        }
+
+        let n_part_bits = current_n_bits / self.arity;
+        let mut value = BigUint::default();
+        for _ in 0..self.arity {
+            value = (value << n_part_bits) | self._generate_random_recursive(n_part_bits, rng);
+        }
+        value
+    }
 }
\ No newline at end of file
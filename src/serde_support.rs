@@ -0,0 +1,111 @@
+//! Helpers for serializing `BigUint` values.
+//!
+//! `BigUint` has no stable numeric form across serde formats (JSON numbers, for
+//! instance, lose precision beyond 2^53). Human-readable formats get base-10 strings;
+//! binary formats get compact big-endian byte arrays.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use num_bigint::BigUint;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserializer, Serialize, Serializer};
+
+/// (De)serializes a single `BigUint` as a decimal string, or as big-endian bytes in
+/// binary formats.
+pub(crate) mod biguint {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&value.to_str_radix(10))
+        } else {
+            serializer.serialize_bytes(&value.to_bytes_be())
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BigUintVisitor)
+        } else {
+            deserializer.deserialize_bytes(BigUintVisitor)
+        }
+    }
+}
+
+/// (De)serializes a sequence of `BigUint` values, each encoded as by `biguint`.
+pub(crate) mod biguint_vec {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(values: &[BigUint], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&BigUintRef(value))?;
+        }
+        seq.end()
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BigUint>, D::Error> {
+        deserializer.deserialize_seq(BigUintSeqVisitor)
+    }
+}
+
+struct BigUintVisitor;
+
+impl Visitor<'_> for BigUintVisitor {
+    type Value = BigUint;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a non-negative integer as a decimal string or big-endian bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<BigUint, E> {
+        BigUint::from_str(s).map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<BigUint, E> {
+        Ok(BigUint::from_bytes_be(bytes))
+    }
+}
+
+/// The most values `BigUintSeqVisitor` reserves room for up front.
+const MAX_PREALLOCATED_VALUES: usize = 4096;
+
+struct BigUintSeqVisitor;
+
+impl<'de> Visitor<'de> for BigUintSeqVisitor {
+    type Value = Vec<BigUint>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of non-negative integers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<BigUint>, A::Error> {
+        // The hint comes from the input, so it is capped rather than trusted: a crafted
+        // length must not force a huge allocation before any element is read.
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(MAX_PREALLOCATED_VALUES));
+        while let Some(value) = seq.next_element::<BigUintOwned>()? {
+            values.push(value.0);
+        }
+        Ok(values)
+    }
+}
+
+/// A borrowed `BigUint` serialized by `biguint`, used for elements of `biguint_vec`.
+struct BigUintRef<'a>(&'a BigUint);
+
+impl Serialize for BigUintRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        biguint::serialize(self.0, serializer)
+    }
+}
+
+/// A `BigUint` deserialized by `biguint`, used for elements of `biguint_vec`.
+struct BigUintOwned(BigUint);
+
+impl<'de> de::Deserialize<'de> for BigUintOwned {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        biguint::deserialize(deserializer).map(BigUintOwned)
+    }
+}
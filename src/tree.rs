@@ -0,0 +1,171 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use num_bigint::BigUint;
+
+/// An S_base component of an S_N member together with its position in the member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentAt {
+    /// The component's value.
+    pub value: BigUint,
+    /// Position of the component among the member's S_base components
+    /// (0 = most significant).
+    pub leaf_index: usize,
+    /// Offset of the component's least significant bit within the member, so the member
+    /// is the OR of every `value << bit_offset`.
+    pub bit_offset: usize,
+}
+
+/// A node of the decomposition hierarchy of an S_N member.
+///
+/// The root holds the full value; every internal node holds a sub-block and its
+/// upper and lower halves. Nodes at `n_base_bits` are leaves and hold S_base
+/// components. Every node carries its own value and bit-width, so the tree is fully
+/// reconstructable. Trees are only built for arity 2, since
+/// `Propagator::decompose_to_tree` returns `HierarchyError::UnsupportedArity`
+/// otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecompNode {
+    /// An S_base component at `n_base_bits`.
+    Leaf {
+        /// The component's value.
+        value: BigUint,
+        /// The bit-width of the component, i.e. `n_base_bits`.
+        n_bits: usize,
+    },
+    /// A sub-block above the base level together with its two halves.
+    Internal {
+        /// The value of this sub-block.
+        value: BigUint,
+        /// The bit-width of this sub-block.
+        n_bits: usize,
+        /// The upper half of this sub-block.
+        upper: Box<DecompNode>,
+        /// The lower half of this sub-block.
+        lower: Box<DecompNode>,
+    },
+}
+
+impl DecompNode {
+    /// Creates a leaf node holding an S_base component.
+    pub(crate) fn leaf(value: BigUint, n_bits: usize) -> Self {
+        Self::Leaf { value, n_bits }
+    }
+
+    /// Creates an internal node whose value is the concatenation of its two halves.
+    pub(crate) fn join(upper: DecompNode, lower: DecompNode) -> Self {
+        let value = (upper.value() << lower.n_bits()) | lower.value();
+        let n_bits = upper.n_bits() + lower.n_bits();
+        Self::Internal { value, n_bits, upper: Box::new(upper), lower: Box::new(lower) }
+    }
+
+    /// Builds a tree bottom-up from its leaves, most significant first, by joining
+    /// neighbouring pairs level by level. `leaves.len()` must be a non-zero power of 2.
+    pub(crate) fn from_leaves(leaves: Vec<DecompNode>) -> Self {
+        let mut level = leaves;
+        while level.len() > 1 {
+            let mut parents = Vec::with_capacity(level.len() / 2);
+            let mut nodes = level.into_iter();
+            while let (Some(upper), Some(lower)) = (nodes.next(), nodes.next()) {
+                parents.push(DecompNode::join(upper, lower));
+            }
+            level = parents;
+        }
+        level.pop().expect("a decomposition tree has at least one leaf")
+    }
+
+    /// Returns the value of this sub-block.
+    pub fn value(&self) -> &BigUint {
+        match self {
+            Self::Leaf { value, .. } | Self::Internal { value, .. } => value,
+        }
+    }
+
+    /// Returns a mutable reference to the value of this sub-block.
+    pub(crate) fn value_mut(&mut self) -> &mut BigUint {
+        match self {
+            Self::Leaf { value, .. } | Self::Internal { value, .. } => value,
+        }
+    }
+
+    /// Returns the bit-width of this sub-block.
+    pub fn n_bits(&self) -> usize {
+        match self {
+            Self::Leaf { n_bits, .. } | Self::Internal { n_bits, .. } => *n_bits,
+        }
+    }
+
+    /// Returns `true` if this node is at the base level.
+    pub fn is_leaf(&self) -> bool {
+        matches!(self, Self::Leaf { .. })
+    }
+
+    /// Returns the upper and lower halves of this sub-block, or `None` for a leaf.
+    pub fn children(&self) -> Option<(&DecompNode, &DecompNode)> {
+        match self {
+            Self::Leaf { .. } => None,
+            Self::Internal { upper, lower, .. } => Some((upper, lower)),
+        }
+    }
+
+    /// Returns the upper half of this sub-block, or `None` for a leaf.
+    pub fn upper(&self) -> Option<&DecompNode> {
+        self.children().map(|(upper, _)| upper)
+    }
+
+    /// Returns the lower half of this sub-block, or `None` for a leaf.
+    pub fn lower(&self) -> Option<&DecompNode> {
+        self.children().map(|(_, lower)| lower)
+    }
+
+    /// Renders the tree as a Graphviz DOT digraph, e.g. for `dot -Tpng`.
+    ///
+    /// Each node is labeled with its decimal value and bit-width, leaves are drawn as
+    /// boxes, and edges are labeled `upper` or `lower`. Nodes get sequential IDs in
+    /// pre-order, so repeated values still produce distinct nodes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph decomposition {\n");
+        let mut next_id = 0usize;
+        // Each entry is a node to declare and the edge leading to it from its parent.
+        let mut stack: Vec<(&DecompNode, Option<(usize, &str)>)> = vec![(self, None)];
+        while let Some((node, parent_edge)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+
+            let shape = if node.is_leaf() { ", shape=box" } else { "" };
+            writeln!(dot, "    n{} [label=\"{} ({} bits)\"{}];", id, node.value(), node.n_bits(), shape)
+                .expect("writing to a String cannot fail");
+            if let Some((parent_id, side)) = parent_edge {
+                writeln!(dot, "    n{} -> n{} [label=\"{}\"];", parent_id, id, side)
+                    .expect("writing to a String cannot fail");
+            }
+
+            if let Some((upper, lower)) = node.children() {
+                stack.push((lower, Some((id, "lower"))));
+                stack.push((upper, Some((id, "upper"))));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the leaf values in order, most significant first.
+    /// For a tree built by `Propagator::decompose_to_tree` this equals the output of
+    /// `Propagator::decompose_to_base`.
+    pub fn leaves(&self) -> Vec<BigUint> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                DecompNode::Internal { upper, lower, .. } => {
+                    stack.push(lower);
+                    stack.push(upper);
+                }
+                DecompNode::Leaf { value, .. } => leaves.push(value.clone()),
+            }
+        }
+        leaves
+    }
+}
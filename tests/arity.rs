@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, Propagator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Membership by the definition with `arity` equal parts per level.
+fn is_member_by_definition_with_arity(base: &[u64], n_base_bits: usize, arity: usize, x: u64, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(&x);
+    }
+    let part_bits = n_bits / arity;
+    (0..arity).all(|i| is_member_by_definition_with_arity(base, n_base_bits, arity, (x >> (part_bits * i)) & ((1 << part_bits) - 1), part_bits))
+}
+
+#[test]
+fn membership_with_arity_three_and_four_matches_brute_force() {
+    let base = [1u64, 2];
+    for arity in [3usize, 4] {
+        let p = Propagator::with_arity(pattern(&base, 2), arity).unwrap();
+        let mut cached = p.clone().with_cache(None);
+        let n_bits = 2 * arity;
+        let mut count = 0u64;
+        for x in 0..1u64 << n_bits {
+            let expected = is_member_by_definition_with_arity(&base, 2, arity, x, n_bits);
+            assert_eq!(p.is_member(&big(x), n_bits).unwrap(), expected, "arity {arity}: {x}");
+            assert_eq!(cached.is_member(&big(x), n_bits).unwrap(), expected);
+            count += u64::from(expected);
+        }
+        assert_eq!(p.count_members(n_bits).unwrap(), big(count));
+        assert_eq!(p.iter_members(n_bits).unwrap().count() as u64, count);
+    }
+}
+
+#[test]
+fn levels_grow_by_powers_of_the_arity() {
+    let p = Propagator::with_arity(pattern(&[1, 2], 2), 3).unwrap();
+    assert!(p.is_member(&big(0b01_10_01), 6).unwrap());
+    assert!(p.is_member(&big(0), 18).is_ok());
+    assert_eq!(p.is_member(&big(0), 4), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 4, base_n_bits: 2 }));
+    assert_eq!(p.is_member(&big(0), 12), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 12, base_n_bits: 2 }));
+}
+
+#[test]
+fn decomposition_and_composition_split_into_arity_parts() {
+    let mut rng = StdRng::seed_from_u64(19);
+    for arity in [3usize, 4] {
+        let p = Propagator::with_arity(pattern(&[1, 2], 2), arity).unwrap();
+        let n_bits = 2 * arity * arity;
+        for _ in 0..50 {
+            let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+            assert!(p.is_member(&member, n_bits).unwrap());
+            let components = p.decompose_to_base(&member, n_bits).unwrap();
+            assert_eq!(components.len(), arity * arity);
+            assert_eq!(p.compose_from_base(&components).unwrap(), (member.clone(), n_bits));
+            let parts = p.decompose_to_level(&member, n_bits, 2 * arity).unwrap();
+            assert_eq!(parts.len(), arity);
+            assert_eq!(p.compose_from_level(&parts, 2 * arity).unwrap(), (member, n_bits));
+        }
+        assert_eq!(p.count_members(n_bits).unwrap(), BigUint::from(2u32).pow((arity * arity) as u32));
+        assert_eq!(p.compose_from_base(&bigs(&[1, 1])), Err(HierarchyError::InvalidComponentCount(2)));
+    }
+}
+
+#[test]
+fn arity_must_be_at_least_two() {
+    assert_eq!(Propagator::with_arity(pattern(&[1], 2), 1).unwrap_err(), HierarchyError::InvalidArity(1));
+    assert!(matches!(
+        Propagator::with_arity(pattern(&[1, 2], 2), 3).unwrap().decompose_to_tree(&big(0b01_01_01), 6),
+        Err(HierarchyError::UnsupportedArity(3))
+    ));
+}
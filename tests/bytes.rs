@@ -0,0 +1,64 @@
+mod common;
+
+use common::*;
+use paired_binary::HierarchyError;
+
+#[test]
+fn is_member_bytes_matches_is_member() {
+    // 12 bits is not a multiple of 8, so the top byte holds only 4 bits.
+    let p = propagator(&[1, 2, 5], 3);
+    for x in 0u64..1 << 12 {
+        let bytes = [(x >> 8) as u8, x as u8];
+        assert_eq!(p.is_member_bytes(&bytes, 12), p.is_member(&big(x), 12), "{x}");
+    }
+}
+
+#[test]
+fn leading_zero_bytes_are_ignored() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = 0b001_010_101_001u16.to_be_bytes();
+    assert_eq!(p.is_member_bytes(&member, 12), Ok(true));
+    assert_eq!(p.is_member_bytes(&[0, 0, 0, member[0], member[1]], 12), Ok(true));
+    assert_eq!(p.is_member_bytes(&[member[1]], 12), p.is_member(&big(u64::from(member[1])), 12));
+    assert_eq!(p.is_member_bytes(&[], 12), Ok(false));
+}
+
+#[test]
+fn set_bits_above_the_level_are_rejected() {
+    let p = propagator(&[1, 2, 5], 3);
+    // Bit 12 is set within the top byte.
+    assert_eq!(
+        p.is_member_bytes(&[0x10, 0x00], 12),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(0x1000), n_bits: 12 })
+    );
+    assert_eq!(
+        p.is_member_bytes(&[1, 0, 0], 12),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(0x1_0000), n_bits: 12 })
+    );
+}
+
+#[test]
+fn decompose_to_base_bytes_pads_components() {
+    // 12-bit components take two bytes each.
+    let p = propagator(&[0x001, 0xABC, 0xFFF], 12);
+    let components = p.decompose_to_base_bytes(&[0xAB, 0xC0, 0x01], 24).unwrap();
+    assert_eq!(components, vec![vec![0x0A, 0xBC], vec![0x00, 0x01]]);
+    // 3-bit components take one byte each.
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.decompose_to_base_bytes(&[0b1010], 6).unwrap(), vec![vec![1], vec![2]]);
+}
+
+#[test]
+fn decompose_to_base_bytes_matches_decompose_to_base() {
+    let p = propagator(&[1, 2, 5], 3);
+    for member in p.iter_members(12).unwrap() {
+        let bytes: Vec<Vec<u8>> = p.decompose_to_base(&member, 12).unwrap().iter().map(|c| c.to_bytes_be()).collect();
+        assert_eq!(p.decompose_to_base_bytes(&member.to_bytes_be(), 12).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn decompose_to_base_bytes_rejects_non_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.decompose_to_base_bytes(&[0b111_001], 6), Err(HierarchyError::NotAMember(big(0b111_001))));
+}
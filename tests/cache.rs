@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use paired_binary::CacheStats;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn cached_membership_matches_plain_membership() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let p = propagator(&[2, 0, 5, 7], 3);
+    let mut cached = p.clone().with_cache(None);
+    for _ in 0..2000 {
+        let x = if rng.gen_bool(0.5) { p.generate_random_s_n_member(12, &mut rng).unwrap() } else { big(rng.gen_range(0..1 << 12)) };
+        assert_eq!(cached.is_member(&x, 12).unwrap(), p.is_member(&x, 12).unwrap(), "{x}");
+    }
+}
+
+#[test]
+fn bounded_cache_never_exceeds_its_capacity() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let p = propagator(&[2, 0, 5, 7], 3);
+    let mut cached = p.clone().with_cache(Some(10));
+    for _ in 0..500 {
+        let x = big(rng.gen_range(0..1 << 24));
+        assert_eq!(cached.is_member(&x, 24).unwrap(), p.is_member(&x, 24).unwrap());
+        assert!(cached.cache_len() <= 10);
+    }
+}
+
+#[test]
+fn cached_membership_reports_the_same_errors() {
+    let p = propagator(&[2, 0, 5], 3);
+    let mut cached = p.clone().with_cache(Some(10));
+    assert_eq!(cached.is_member(&big(0), 9), p.is_member(&big(0), 9));
+    assert_eq!(cached.is_member(&big(1 << 6), 6), p.is_member(&big(1 << 6), 6));
+}
+
+#[test]
+fn cache_stats_count_hits_and_misses() {
+    let mut cached = propagator(&[1, 2], 2).with_cache(None);
+    // 0b0101_0101 splits into two identical halves: the first is computed, the second hits.
+    assert!(cached.is_member(&big(0b0101_0101), 8).unwrap());
+    assert_eq!(cached.cache_stats(), CacheStats { hits: 1, misses: 2 });
+    assert!(cached.is_member(&big(0b0101_0101), 8).unwrap());
+    assert_eq!(cached.cache_stats(), CacheStats { hits: 2, misses: 2 });
+    cached.reset_stats();
+    assert_eq!(cached.cache_stats(), CacheStats::default());
+}
+
+#[test]
+fn changing_the_pattern_clears_the_cache() {
+    let mut cached = propagator(&[1, 2], 2).with_cache(None);
+    assert!(cached.is_member(&big(0b0101), 4).unwrap());
+    assert_eq!(cached.cache_len(), 1);
+    cached.set_initial_pattern(pattern(&[3], 2));
+    assert_eq!(cached.cache_len(), 0);
+    assert!(!cached.is_member(&big(0b0101), 4).unwrap());
+    assert!(cached.is_member(&big(0b1111), 4).unwrap());
+}
+
+#[test]
+fn randomized_queries_agree_with_and_without_the_cache() {
+    let mut rng = StdRng::seed_from_u64(6);
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let mut cached = p.clone().with_cache(Some(64));
+    for _ in 0..1000 {
+        let member = p.generate_random_s_n_member(64, &mut rng).unwrap();
+        let x = if rng.gen_bool(0.5) { member } else { member ^ (big(1) << rng.gen_range(0..64)) };
+        assert_eq!(cached.is_member(&x, 64).unwrap(), p.is_member(&x, 64).unwrap());
+    }
+    let stats = cached.cache_stats();
+    assert!(stats.hits > 0 && stats.misses > 0);
+}
@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use num_bigint::BigUint;
+use paired_binary::{InitialPattern, Propagator};
+use std::collections::HashMap;
+
+/// Builds an `InitialPattern` from `u64` values, panicking on invalid input.
+pub fn pattern(values: &[u64], n_base_bits: usize) -> InitialPattern {
+    InitialPattern::from_u64_values(values, n_base_bits).unwrap()
+}
+
+/// Builds a default `Propagator` over `pattern(values, n_base_bits)`.
+pub fn propagator(values: &[u64], n_base_bits: usize) -> Propagator {
+    Propagator::new(pattern(values, n_base_bits))
+}
+
+pub fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+pub fn bigs(values: &[u64]) -> Vec<BigUint> {
+    values.iter().copied().map(BigUint::from).collect()
+}
+
+/// Membership by the recursive definition: the value is a member at `n_bits`
+/// if it is in `base` at `n_base_bits`, or if both its halves are members.
+pub fn is_member_by_definition(base: &[u64], n_base_bits: usize, x: u64, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(&x);
+    }
+    let half = n_bits / 2;
+    is_member_by_definition(base, n_base_bits, x >> half, half) && is_member_by_definition(base, n_base_bits, x & ((1 << half) - 1), half)
+}
+
+/// Every member at `n_bits` in ascending order, found by checking each value
+/// against the definition.
+pub fn members_by_definition(base: &[u64], n_base_bits: usize, n_bits: usize) -> Vec<u64> {
+    (0..1u64 << n_bits).filter(|&x| is_member_by_definition(base, n_base_bits, x, n_bits)).collect()
+}
+
+/// The 99.9th percentile of the chi-squared distribution with 8 degrees of freedom.
+pub const CHI_SQUARED_8_DOF_999: f64 = 26.12;
+
+/// Pearson's chi-squared statistic of `counts` against a uniform distribution of `draws`
+/// over the observed values.
+pub fn chi_squared<K>(counts: &HashMap<K, u32>, draws: u32) -> f64 {
+    let expected = f64::from(draws) / counts.len() as f64;
+    counts.values().map(|&count| (f64::from(count) - expected).powi(2) / expected).sum()
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::*;
+use paired_binary::{HierarchyError, PadSide};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn compose_from_level_inverts_decompose_to_level() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let p = propagator(&[1, 2, 3], 4);
+    let member = p.generate_random_s_n_member(64, &mut rng).unwrap();
+    for level in [4, 8, 16, 32, 64] {
+        let components = p.decompose_to_level(&member, 64, level).unwrap();
+        assert_eq!(p.compose_from_level(&components, level).unwrap(), (member.clone(), 64));
+    }
+}
+
+#[test]
+fn compose_from_level_rejects_invalid_components() {
+    let p = propagator(&[1, 2, 3], 4);
+    let block = big(0x12);
+    assert_eq!(p.compose_from_level(&[block.clone(), block.clone(), block.clone()], 8), Err(HierarchyError::InvalidComponentCount(3)));
+    assert_eq!(p.compose_from_level(&[block.clone(), big(0x10)], 8), Err(HierarchyError::NotAMember(big(0x10))));
+    assert!(matches!(p.compose_from_level(&[block.clone(), block], 12), Err(HierarchyError::InvalidHierarchicalLevel { .. })));
+}
+
+#[test]
+fn compose_from_iter_matches_compose_from_base() {
+    for (base, n_base_bits) in [(&[1u64, 2, 5][..], 3), (&[0xdead_beef1, 7, 0x1_0000_0000][..], 37), (&[0, 1][..], 1), (&[5, 9][..], 32)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 8 * n_base_bits, 64 * n_base_bits] {
+            let count = p.count_members(n_bits).unwrap();
+            for index in [0u32, 1, 12345] {
+                let member = p.nth_member(n_bits, &(big(u64::from(index)) % &count)).unwrap();
+                let components = p.decompose_to_base(&member, n_bits).unwrap();
+                assert_eq!(p.compose_from_base(&components).unwrap(), (member.clone(), n_bits));
+                assert_eq!(p.compose_from_iter(components.into_iter()).unwrap(), (member, n_bits));
+            }
+        }
+    }
+}
+
+#[test]
+fn compose_from_iter_validates_count_and_components() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.compose_from_iter(bigs(&[1, 1, 1]).into_iter()), Err(HierarchyError::InvalidComponentCount(3)));
+    assert_eq!(p.compose_from_iter(Vec::new().into_iter()), Err(HierarchyError::InvalidComponentCount(0)));
+    assert_eq!(p.compose_from_iter(bigs(&[1, 3]).into_iter()), Err(HierarchyError::InvalidBaseComponent(big(3))));
+}
+
+#[test]
+fn validate_components_reports_every_invalid_component() {
+    let p = propagator(&[1, 2, 3], 4);
+    let validation = p.validate_components(&bigs(&[1, 16, 2, 7, 3, 0x1F]));
+    assert_eq!(
+        validation.invalid_indices,
+        vec![
+            (1, HierarchyError::ValueExceedsNBaseBits { value: big(16), n_bits: 4, max_val: big(15) }),
+            (3, HierarchyError::InvalidBaseComponent(big(7))),
+            (5, HierarchyError::ValueExceedsNBaseBits { value: big(0x1F), n_bits: 4, max_val: big(15) }),
+        ]
+    );
+    assert!(!validation.count_ok);
+    assert!(!validation.is_valid());
+}
+
+#[test]
+fn validate_components_checks_the_count() {
+    let p = propagator(&[1, 2, 3], 4);
+    for (count, count_ok) in [(0, false), (1, true), (2, true), (3, false), (4, true), (6, false), (8, true)] {
+        let validation = p.validate_components(&vec![big(1); count]);
+        assert!(validation.invalid_indices.is_empty());
+        assert_eq!(validation.count_ok, count_ok, "{count} components");
+        assert_eq!(validation.is_valid(), count_ok, "{count} components");
+    }
+}
+
+#[test]
+fn validate_components_agrees_with_compose_from_base() {
+    let p = propagator(&[1, 2, 3], 4);
+    for components in [bigs(&[1, 2, 3, 1]), bigs(&[1, 9]), bigs(&[1, 2, 3]), bigs(&[20, 1])] {
+        assert_eq!(p.validate_components(&components).is_valid(), p.compose_from_base(&components).is_ok(), "{components:?}");
+    }
+}
+
+#[test]
+fn padded_compose_fills_up_to_the_next_power_of_two() {
+    let p = propagator(&[1, 2, 3], 4);
+    let components = bigs(&[1, 2, 3, 1, 2, 3]);
+    assert_eq!(p.compose_from_base_padded(&components, &big(2), PadSide::Leading), Ok((big(0x2212_3123), 32, 2)));
+    assert_eq!(p.compose_from_base_padded(&components, &big(2), PadSide::Trailing), Ok((big(0x1231_2322), 32, 2)));
+}
+
+#[test]
+fn padded_compose_adds_nothing_to_a_valid_count() {
+    let p = propagator(&[1, 2, 3], 4);
+    for components in [bigs(&[3]), bigs(&[1, 2]), bigs(&[1, 2, 3, 1])] {
+        let (value, n_bits) = p.compose_from_base(&components).unwrap();
+        assert_eq!(p.compose_from_base_padded(&components, &big(1), PadSide::Trailing), Ok((value, n_bits, 0)));
+    }
+}
+
+#[test]
+fn padded_compose_yields_members() {
+    let p = propagator(&[1, 2, 3], 4);
+    for count in 1..=17 {
+        let (value, n_bits, padding) = p.compose_from_base_padded(&vec![big(3); count], &big(1), PadSide::Leading).unwrap();
+        assert_eq!(count + padding, n_bits / 4);
+        assert!(p.is_member(&value, n_bits).unwrap());
+    }
+}
+
+#[test]
+fn padded_compose_validates_the_fill_and_components() {
+    let p = propagator(&[1, 2, 3], 4);
+    assert_eq!(p.compose_from_base_padded(&[], &big(1), PadSide::Leading), Err(HierarchyError::InvalidComponentCount(0)));
+    assert_eq!(
+        p.compose_from_base_padded(&bigs(&[1, 2, 3]), &big(7), PadSide::Leading),
+        Err(HierarchyError::InvalidBaseComponent(big(7)))
+    );
+    assert_eq!(
+        p.compose_from_base_padded(&bigs(&[1, 9, 3]), &big(1), PadSide::Trailing),
+        Err(HierarchyError::InvalidBaseComponent(big(9)))
+    );
+}
+
+#[test]
+fn compose_checked_matches_compose_from_base() {
+    let mut rng = StdRng::seed_from_u64(28);
+    let p = propagator(&[1, 2, 3], 4);
+    for n_bits in [4, 8, 64, 1024] {
+        let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+        let components = p.decompose_to_base(&member, n_bits).unwrap();
+        assert_eq!(p.compose_checked(&components), p.compose_from_base(&components));
+        assert_eq!(p.compose_checked(&components), Ok((member, n_bits)));
+    }
+}
+
+#[test]
+fn compose_checked_keeps_the_usual_validation() {
+    let p = propagator(&[1, 2, 3], 4);
+    assert_eq!(p.compose_checked(&bigs(&[1, 2, 3])), Err(HierarchyError::InvalidComponentCount(3)));
+    assert_eq!(p.compose_checked(&bigs(&[1, 7])), Err(HierarchyError::InvalidBaseComponent(big(7))));
+}
+
+#[test]
+fn unchecked_compose_matches_compose_from_base() {
+    let p = propagator(&[1, 2, 5], 3);
+    for components in [bigs(&[5]), bigs(&[1, 2]), bigs(&[5, 1, 2, 2, 1, 5, 5, 1])] {
+        assert_eq!(Ok(p.compose_from_base_unchecked(&components)), p.compose_from_base(&components));
+    }
+    // Components outside S_base are composed without complaint.
+    assert_eq!(p.compose_from_base_unchecked(&bigs(&[7, 0])), (big(0b111_000), 6));
+}
+
+#[test]
+#[should_panic(expected = "cannot compose zero components")]
+fn unchecked_compose_rejects_zero_components() {
+    propagator(&[1, 2, 5], 3).compose_from_base_unchecked(&[]);
+}
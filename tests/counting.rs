@@ -0,0 +1,98 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::HierarchyError;
+
+#[test]
+fn count_members_matches_brute_force() {
+    for (base, n_base_bits) in [(&[2u64, 0, 5][..], 3), (&[1, 2][..], 2), (&[0, 1][..], 1), (&[3][..], 2)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 4 * n_base_bits] {
+            let members = members_by_definition(base, n_base_bits, n_bits);
+            assert_eq!(p.count_members(n_bits).unwrap(), big(members.len() as u64), "{base:?} at {n_bits}");
+        }
+    }
+}
+
+#[test]
+fn count_members_is_exact_at_deep_levels() {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    // 4096 bits is 1024 leaves, so there are 5^1024 members.
+    assert_eq!(p.count_members(4096).unwrap(), BigUint::from(5u32).pow(1024));
+    assert_eq!(p.count_members(4 << 16).unwrap(), BigUint::from(5u32).pow(1 << 16));
+}
+
+#[test]
+fn count_members_rejects_invalid_levels() {
+    let p = propagator(&[1, 3], 4);
+    for n_bits in [0, 2, 12, 24] {
+        assert_eq!(
+            p.count_members(n_bits),
+            Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: n_bits, base_n_bits: 4 })
+        );
+    }
+}
+
+#[test]
+fn count_members_uses_the_closed_form() {
+    // Three 2-bit values at 8 bits: 4 leaves, so 3^4 members.
+    let p = propagator(&[0, 1, 3], 2);
+    assert_eq!(p.count_members(8).unwrap(), big(81));
+}
+
+#[test]
+fn count_members_at_the_base_level_is_the_pattern_size() {
+    let p = propagator(&[0, 1, 3], 2);
+    assert_eq!(p.count_members(2).unwrap(), big(3));
+}
+
+#[test]
+fn count_members_in_range_matches_brute_force() {
+    for (base, n_base_bits) in [(&[1u64, 2, 5][..], 3), (&[0, 3][..], 2)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 4 * n_base_bits] {
+            let members = members_by_definition(base, n_base_bits, n_bits);
+            let max = (1u64 << n_bits) - 1;
+            for (low, high) in [(0, max), (0, 0), (max, max), (5, 40), (17, 200), (3, 3), (max / 3, max / 2)] {
+                let (low, high) = (low.min(max), high.min(max));
+                let expected = members.iter().filter(|&&m| low <= m && m <= high).count();
+                assert_eq!(
+                    p.count_members_in_range(&big(low), &big(high), n_bits),
+                    Ok(big(expected as u64)),
+                    "[{low}, {high}] at {n_bits}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn count_members_in_range_is_zero_for_reversed_bounds() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.count_members_in_range(&big(40), &big(5), 12), Ok(big(0)));
+}
+
+#[test]
+fn count_members_in_range_covers_the_whole_level() {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let max = (BigUint::from(1u32) << 4096) - 1u32;
+    assert_eq!(p.count_members_in_range(&big(0), &max, 4096), p.count_members(4096));
+}
+
+#[test]
+fn count_members_in_range_validates_the_bounds() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.count_members_in_range(&big(0), &big(64), 6),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 })
+    );
+    assert_eq!(
+        p.count_members_in_range(&big(64), &big(0), 6),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 })
+    );
+    assert_eq!(
+        p.count_members_in_range(&big(0), &big(1), 9),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+}
@@ -0,0 +1,364 @@
+mod common;
+
+use common::*;
+use num_bigint::{BigUint, RandBigInt};
+use paired_binary::{ChunkDiff, ComponentAt, DecompNode, HierarchyError, InitialPattern, MembershipRule, Propagator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::BTreeSet;
+use std::ops::ControlFlow;
+
+#[test]
+fn decompose_to_level_returns_members_of_the_intermediate_level() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let p = propagator(&[1, 2, 3], 4);
+    let member = p.generate_random_s_n_member(64, &mut rng).unwrap();
+    let halves = p.decompose_to_level(&member, 64, 32).unwrap();
+    assert_eq!(halves.len(), 2);
+    let quarters = p.decompose_to_level(&member, 64, 16).unwrap();
+    assert_eq!(quarters.len(), 4);
+    for quarter in &quarters {
+        assert!(p.is_member(quarter, 16).unwrap());
+    }
+    assert_eq!(halves[0], (&quarters[0] << 16) | &quarters[1]);
+    assert_eq!(p.decompose_to_level(&member, 64, 64).unwrap(), vec![member]);
+}
+
+#[test]
+fn decompose_to_level_rejects_invalid_stop_levels() {
+    let p = propagator(&[1, 2, 3], 4);
+    let member = p.nth_member(64, &big(0)).unwrap();
+    assert_eq!(
+        p.decompose_to_level(&member, 64, 2),
+        Err(HierarchyError::TargetNBitsTooSmall { target_n_bits: 2, base_n_bits: 4 })
+    );
+    assert_eq!(
+        p.decompose_to_level(&member, 64, 12),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 12, base_n_bits: 4 })
+    );
+    assert_eq!(
+        p.decompose_to_level(&member, 64, 128),
+        Err(HierarchyError::StopLevelTooLarge { stop_n_bits: 128, target_n_bits: 64 })
+    );
+    // The levels are checked before membership, so a non-member gets the same error.
+    assert_eq!(
+        p.decompose_to_level(&big(0), 64, 128),
+        Err(HierarchyError::StopLevelTooLarge { stop_n_bits: 128, target_n_bits: 64 })
+    );
+    assert_eq!(p.decompose_to_level(&big(0), 64, 16), Err(HierarchyError::NotAMember(big(0))));
+}
+
+#[test]
+fn decompose_to_tree_keeps_every_intermediate_half() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = big(0b101_001_010_010);
+    let tree = p.decompose_to_tree(&member, 12).unwrap();
+    assert_eq!((tree.value(), tree.n_bits()), (&member, 12));
+    let (upper, lower) = tree.children().unwrap();
+    assert_eq!((upper.value(), upper.n_bits()), (&big(0b101_001), 6));
+    assert_eq!((lower.value(), lower.n_bits()), (&big(0b010_010), 6));
+    let leaf = upper.upper().unwrap();
+    assert_eq!(leaf, &DecompNode::Leaf { value: big(0b101), n_bits: 3 });
+    assert!(leaf.children().is_none());
+    assert_eq!(tree.leaves(), p.decompose_to_base(&member, 12).unwrap());
+}
+
+#[test]
+fn decompose_to_tree_handles_deep_hierarchies() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let p = propagator(&[1, 2], 2);
+    let n_bits = 1 << 16;
+    let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+    let tree = p.decompose_to_tree(&member, n_bits).unwrap();
+    assert_eq!(tree.leaves(), p.decompose_to_base(&member, n_bits).unwrap());
+}
+
+#[test]
+fn decompose_to_level_at_the_base_level_is_decompose_to_base() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    for _ in 0..20 {
+        let member = p.generate_random_s_n_member(64, &mut rng).unwrap();
+        assert_eq!(p.decompose_to_level(&member, 64, 4).unwrap(), p.decompose_to_base(&member, 64).unwrap());
+    }
+}
+
+#[test]
+fn decompose_to_level_splits_a_64_bit_member_into_16_bit_blocks() {
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let member = big(0x1369_c631_9999_1111);
+    assert_eq!(p.decompose_to_level(&member, 64, 16).unwrap(), bigs(&[0x1369, 0xc631, 0x9999, 0x1111]));
+    assert_eq!(p.decompose_to_level(&(member + 1u32), 64, 16).unwrap_err(), HierarchyError::NotAMember(big(0x1369_c631_9999_1112)));
+}
+
+#[test]
+fn streaming_decomposition_emits_components_in_order() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = p.nth_member(24, &big(1234)).unwrap();
+    let mut streamed = Vec::new();
+    let emitted = p
+        .decompose_to_base_streaming(&member, 24, |component| {
+            streamed.push(component.clone());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert_eq!(emitted, 8);
+    assert_eq!(streamed, p.decompose_to_base(&member, 24).unwrap());
+}
+
+#[test]
+fn streaming_decomposition_stops_when_the_sink_breaks() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = p.nth_member(24, &big(1234)).unwrap();
+    let mut streamed = Vec::new();
+    let emitted = p
+        .decompose_to_base_streaming(&member, 24, |component| {
+            streamed.push(component.clone());
+            if streamed.len() == 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        })
+        .unwrap();
+    assert_eq!(emitted, 3);
+    assert_eq!(streamed, p.decompose_to_base(&member, 24).unwrap()[..3]);
+}
+
+#[test]
+fn streaming_decomposition_of_a_non_member_emits_nothing() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut emitted = 0;
+    let result = p.decompose_to_base_streaming(&big(0b001_000), 6, |_| {
+        emitted += 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(result, Err(HierarchyError::NotAMember(big(0b001_000))));
+    assert_eq!(emitted, 0);
+}
+
+#[test]
+fn decompose_with_offsets_matches_decompose_to_base() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = p.nth_member(24, &big(4321)).unwrap();
+    let components = p.decompose_with_offsets(&member, 24).unwrap();
+    let values: Vec<BigUint> = components.iter().map(|c| c.value.clone()).collect();
+    assert_eq!(values, p.decompose_to_base(&member, 24).unwrap());
+    assert_eq!(components.iter().map(|c| c.leaf_index).collect::<Vec<_>>(), (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn shifted_components_or_back_to_the_member() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let p = propagator(&[1, 2, 5], 3);
+    for _ in 0..50 {
+        let member = p.generate_random_s_n_member(48, &mut rng).unwrap();
+        let components = p.decompose_with_offsets(&member, 48).unwrap();
+        let rebuilt = components.iter().fold(big(0), |acc, c| acc | (&c.value << c.bit_offset));
+        assert_eq!(rebuilt, member);
+    }
+}
+
+#[test]
+fn offsets_count_from_the_least_significant_bit() {
+    let p = propagator(&[1, 2, 3], 4);
+    assert_eq!(
+        p.decompose_with_offsets(&big(0x1231), 16).unwrap(),
+        vec![
+            ComponentAt { value: big(1), leaf_index: 0, bit_offset: 12 },
+            ComponentAt { value: big(2), leaf_index: 1, bit_offset: 8 },
+            ComponentAt { value: big(3), leaf_index: 2, bit_offset: 4 },
+            ComponentAt { value: big(1), leaf_index: 3, bit_offset: 0 },
+        ]
+    );
+}
+
+#[test]
+fn decompose_with_offsets_rejects_non_members() {
+    let p = propagator(&[1, 2, 3], 4);
+    assert_eq!(p.decompose_with_offsets(&big(0x1F21), 16), Err(HierarchyError::NotAMember(big(0x1F21))));
+}
+
+#[test]
+fn decompositions_reject_huge_levels_before_allocating() {
+    let p = propagator(&[1, 2, 3], 4);
+    let invalid = HierarchyError::InvalidHierarchicalLevel { target_n_bits: usize::MAX - 3, base_n_bits: 4 };
+    assert_eq!(p.decompose_to_base(&big(1), usize::MAX - 3), Err(invalid.clone()));
+    assert_eq!(p.decompose_to_base_bytes(&[1], usize::MAX - 3), Err(invalid.clone()));
+    assert_eq!(p.decompose_with_offsets(&big(1), usize::MAX - 3), Err(invalid.clone()));
+    let mut out = bigs(&[1, 2]);
+    assert_eq!(p.decompose_to_base_into(&big(1), usize::MAX - 3, &mut out), Err(invalid));
+    assert!(out.is_empty());
+}
+
+#[test]
+fn tree_accessors_return_the_halves() {
+    let p = propagator(&[1, 2, 5], 3);
+    let tree = p.decompose_to_tree(&big(0b101_001_010_010), 12).unwrap();
+    let upper = tree.upper().unwrap();
+    let lower = tree.lower().unwrap();
+    assert_eq!((upper.value(), lower.value()), (&big(0b101_001), &big(0b010_010)));
+    assert!(!tree.is_leaf());
+    let leaf = lower.lower().unwrap();
+    assert!(leaf.is_leaf());
+    assert_eq!((leaf.upper(), leaf.lower()), (None, None));
+}
+
+#[test]
+fn every_internal_node_joins_its_halves() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let p = propagator(&[1, 2, 5], 3);
+    let member = p.generate_random_s_n_member(96, &mut rng).unwrap();
+    let mut stack = vec![p.decompose_to_tree(&member, 96).unwrap()];
+    while let Some(node) = stack.pop() {
+        if let (Some(upper), Some(lower)) = (node.upper(), node.lower()) {
+            assert_eq!(upper.n_bits() + lower.n_bits(), node.n_bits());
+            assert_eq!((upper.value() << lower.n_bits()) | lower.value(), *node.value());
+            stack.extend([upper.clone(), lower.clone()]);
+        } else {
+            assert_eq!(node.n_bits(), 3);
+        }
+    }
+}
+
+#[test]
+fn decompose_to_tree_rejects_non_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.decompose_to_tree(&big(0b111_001), 6), Err(HierarchyError::NotAMember(big(0b111_001))));
+}
+
+#[test]
+fn to_dot_declares_every_node_and_edge() {
+    let p = propagator(&[1, 2, 5], 3);
+    let dot = p.decompose_to_tree(&big(0b001_001_001_001), 12).unwrap().to_dot();
+    assert!(dot.starts_with("digraph decomposition {\n") && dot.ends_with("}\n"));
+    // 7 nodes and 6 edges; the repeated leaf value 1 still gets four distinct nodes.
+    let nodes = dot.lines().filter(|line| line.contains("[label=") && !line.contains("->")).count();
+    let edges = dot.lines().filter(|line| line.contains("->")).count();
+    assert_eq!((nodes, edges), (7, 6));
+    assert_eq!(dot.matches("label=\"1 (3 bits)\", shape=box").count(), 4);
+    assert_eq!(dot.matches("[label=\"upper\"]").count(), 3);
+    assert_eq!(dot.matches("[label=\"lower\"]").count(), 3);
+}
+
+#[test]
+fn to_dot_renders_a_small_tree_exactly() {
+    let p = propagator(&[1, 2, 5], 3);
+    let dot = p.decompose_to_tree(&big(0b101_010), 6).unwrap().to_dot();
+    let expected = "digraph decomposition {\n    \
+        n0 [label=\"42 (6 bits)\"];\n    \
+        n1 [label=\"5 (3 bits)\", shape=box];\n    \
+        n0 -> n1 [label=\"upper\"];\n    \
+        n2 [label=\"2 (3 bits)\", shape=box];\n    \
+        n0 -> n2 [label=\"lower\"];\n\
+        }\n";
+    assert_eq!(dot, expected);
+}
+
+#[test]
+fn decompose_into_matches_decompose_to_base() {
+    let mut rng = StdRng::seed_from_u64(18);
+    let p = propagator(&[1, 3, 6, 9, 12], 4);
+    let mut out = Vec::new();
+    // Alternate levels so the buffer both grows and shrinks.
+    for n_bits in [64, 16, 256, 4, 64] {
+        let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+        assert_eq!(p.decompose_to_base_into(&member, n_bits, &mut out), Ok(n_bits / 4));
+        assert_eq!(out, p.decompose_to_base(&member, n_bits).unwrap());
+    }
+}
+
+#[test]
+fn decompose_into_overwrites_existing_contents() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut out = bigs(&[7, 7, 7, 7, 7]);
+    assert_eq!(p.decompose_to_base_into(&big(0b001_101), 6, &mut out), Ok(2));
+    assert_eq!(out, bigs(&[1, 5]));
+}
+
+#[test]
+fn decompose_into_leaves_the_buffer_empty_on_error() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut out = bigs(&[7, 7, 7]);
+    // The first component is valid, so the failure happens part-way through.
+    assert_eq!(p.decompose_to_base_into(&big(0b001_111), 6, &mut out), Err(HierarchyError::NotAMember(big(0b001_111))));
+    assert!(out.is_empty());
+    let mut out = bigs(&[7]);
+    assert!(p.decompose_to_base_into(&big(0), 9, &mut out).is_err());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn every_valid_level_is_decomposable() {
+    let p = propagator(&[1, 2, 5], 3);
+    for n_bits in p.levels_up_to(3 << 20) {
+        assert_eq!(p.verify_decomposability(n_bits), Ok(()));
+    }
+    assert_eq!(
+        p.verify_decomposability(9),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+}
+
+#[test]
+fn decomposability_requires_both_halves() {
+    let p = Propagator::with_rule(pattern(&[1, 2, 5], 3), MembershipRule::EitherHalf);
+    assert_eq!(p.verify_decomposability(6), Err(HierarchyError::DecompositionUndefinedForRule(MembershipRule::EitherHalf)));
+}
+
+#[test]
+fn self_test_passes_for_valid_propagators() {
+    let mut rng = StdRng::seed_from_u64(27);
+    for n_base_bits in [1, 3, 8, 40, 70] {
+        for size in [1, 2, 3, 5, 9] {
+            let base: BTreeSet<BigUint> = (0..size).map(|_| rng.gen_biguint(n_base_bits as u64)).collect();
+            let pattern = InitialPattern::new(base, n_base_bits).unwrap();
+            assert_eq!(Propagator::new(pattern.clone()).self_test(), Ok(()));
+            assert_eq!(Propagator::with_arity(pattern, 3).unwrap().self_test(), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn self_test_handles_large_patterns() {
+    let p = Propagator::new(InitialPattern::new((0u32..1 << 16).map(BigUint::from).collect(), 16).unwrap());
+    assert_eq!(p.self_test(), Ok(()));
+}
+
+#[test]
+fn unchecked_decompose_matches_decompose_to_base_for_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    for index in [0u64, 4, 80] {
+        let member = p.nth_member(12, &big(index)).unwrap();
+        assert_eq!(Ok(p.decompose_to_base_unchecked(&member, 12)), p.decompose_to_base(&member, 12));
+    }
+    // A non-member is split all the same.
+    assert_eq!(p.decompose_to_base_unchecked(&big(0b111_000), 6), bigs(&[7, 0]));
+}
+
+#[test]
+fn diff_members_reports_the_differing_components() {
+    let p = propagator(&[1, 2, 5], 3);
+    let (a, _) = p.compose_from_base(&bigs(&[1, 2, 5, 5])).unwrap();
+    let diff = |components: &[u64]| p.diff_members(&a, &p.compose_from_base(&bigs(components)).unwrap().0, 12).unwrap();
+    let chunk = |leaf_index, a_value, b_value| ChunkDiff { leaf_index, a_value: big(a_value), b_value: big(b_value) };
+    assert_eq!(diff(&[2, 2, 5, 5]), vec![chunk(0, 1, 2)]);
+    assert_eq!(diff(&[1, 2, 5, 1]), vec![chunk(3, 5, 1)]);
+    assert_eq!(diff(&[1, 5, 1, 5]), vec![chunk(1, 2, 5), chunk(2, 5, 1)]);
+    assert_eq!(diff(&[1, 2, 5, 5]), vec![]);
+}
+
+#[test]
+fn diff_members_requires_two_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = big(0b001_010);
+    assert_eq!(p.diff_members(&member, &big(0b001_011), 6), Err(HierarchyError::NotAMember(big(0b001_011))));
+    assert_eq!(p.diff_members(&big(0), &member, 6), Err(HierarchyError::NotAMember(big(0))));
+    assert!(p.diff_members(&member, &member, 9).is_err());
+}
+
+#[test]
+fn positional_component_diff_marks_matching_leaves() {
+    let p = propagator(&[1, 2, 5], 3);
+    let (a, _) = p.compose_from_base(&bigs(&[1, 2, 5, 5])).unwrap();
+    let (b, _) = p.compose_from_base(&bigs(&[1, 2, 1, 5])).unwrap();
+    assert_eq!(p.positional_component_diff(&a, &b, 12), Ok(vec![true, true, false, true]));
+    assert_eq!(p.positional_component_diff(&a, &a, 12), Ok(vec![true; 4]));
+    assert_eq!(p.positional_component_diff(&a, &big(0), 12), Err(HierarchyError::NotAMember(big(0))));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use paired_binary::HierarchyError;
+
+#[test]
+fn full_base_has_density_one() {
+    let p = propagator(&[0, 1], 1);
+    for n_bits in [1, 2, 64, 1 << 20] {
+        assert!((p.density(n_bits).unwrap() - 1.0).abs() < 1e-12, "{n_bits}");
+    }
+}
+
+#[test]
+fn density_matches_the_member_count_at_small_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    for n_bits in [3, 6, 12, 24, 48] {
+        let count = p.count_members(n_bits).unwrap().to_f64().unwrap();
+        let expected = count / 2f64.powi(n_bits as i32);
+        let density = p.density(n_bits).unwrap();
+        assert!((density - expected).abs() <= expected * 1e-9, "{density} vs {expected} at {n_bits}");
+    }
+}
+
+#[test]
+fn density_saturates_to_zero_at_deep_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    // (3/8)^(2^20) is far below the smallest positive f64.
+    assert_eq!(p.density(3 << 20), Ok(0.0));
+    let shallow = p.density(3 << 6).unwrap();
+    assert!(shallow > 0.0 && shallow < 1e-20);
+}
+
+#[test]
+fn density_rejects_invalid_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.density(9), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 }));
+}
+
+#[test]
+fn full_base_has_exact_density_one() {
+    let p = propagator(&[0, 1], 1);
+    for n_bits in [1, 2, 64, 256] {
+        let (numerator, denominator) = p.density_exact(n_bits).unwrap();
+        assert_eq!(numerator, denominator, "{n_bits}");
+        assert_eq!(denominator, BigUint::from(1u32) << n_bits);
+    }
+}
+
+#[test]
+fn exact_density_is_the_member_count_over_the_value_count() {
+    let p = propagator(&[1, 2, 5], 3);
+    for n_bits in [3, 6, 12, 24] {
+        let (numerator, denominator) = p.density_exact(n_bits).unwrap();
+        assert_eq!(numerator, p.count_members(n_bits).unwrap());
+        assert_eq!(denominator, BigUint::from(1u32) << n_bits);
+    }
+    assert_eq!(p.density_exact(6).unwrap(), (big(9), big(64)));
+}
+
+#[test]
+fn exact_density_survives_where_the_estimate_saturates() {
+    let p = propagator(&[1, 2, 5], 3);
+    let n_bits = 3 << 12;
+    assert_eq!(p.density(n_bits), Ok(0.0));
+    let (numerator, denominator) = p.density_exact(n_bits).unwrap();
+    assert!(!numerator.is_zero());
+    assert_eq!(numerator, BigUint::from(3u32).pow(1 << 12));
+    assert_eq!(denominator.bits(), n_bits as u64 + 1);
+}
+
+#[test]
+fn exact_density_rejects_invalid_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.density_exact(9), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 }));
+}
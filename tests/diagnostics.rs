@@ -0,0 +1,188 @@
+mod common;
+
+use common::*;
+use paired_binary::{ChunkFailure, HierarchyError, InitialPattern, MembershipExplanation, MembershipRule, Propagator};
+
+#[test]
+fn detailed_check_lists_every_failing_chunk() {
+    let p = propagator(&[1, 2, 3], 4);
+    // Chunks 0x1, 0xF, 0x2, 0xF: the second and fourth are not in S_base.
+    let report = p.check_membership_detailed(&big(0x1F2F), 16).unwrap();
+    assert!(!report.is_member);
+    assert_eq!(
+        report.failures,
+        vec![ChunkFailure { leaf_index: 1, value: big(0xF) }, ChunkFailure { leaf_index: 3, value: big(0xF) }]
+    );
+}
+
+#[test]
+fn detailed_check_of_a_member_has_no_failures() {
+    let p = propagator(&[1, 2, 3], 4);
+    let report = p.check_membership_detailed(&big(0x1232), 16).unwrap();
+    assert!(report.is_member);
+    assert!(report.failures.is_empty());
+}
+
+#[test]
+fn detailed_check_agrees_with_is_member() {
+    let p = propagator(&[1, 2], 2);
+    for x in 0..1 << 8 {
+        let report = p.check_membership_detailed(&big(x), 8).unwrap();
+        assert_eq!(report.is_member, p.is_member(&big(x), 8).unwrap());
+        assert_eq!(report.is_member, report.failures.is_empty());
+    }
+    assert!(p.check_membership_detailed(&big(0), 6).is_err());
+}
+
+#[test]
+fn invalid_chunk_count_is_zero_exactly_for_members() {
+    let p = propagator(&[1, 2], 2);
+    for x in 0..1 << 8 {
+        let count = p.invalid_chunk_count(&big(x), 8).unwrap();
+        assert_eq!(count == 0, p.is_member(&big(x), 8).unwrap());
+        assert_eq!(count, p.check_membership_detailed(&big(x), 8).unwrap().failures.len());
+    }
+    // Chunks 0x1, 0xF, 0x2, 0xF under S_base {1, 2, 3}.
+    assert_eq!(propagator(&[1, 2, 3], 4).invalid_chunk_count(&big(0x1F2F), 16), Ok(2));
+}
+
+#[test]
+fn invalid_chunk_count_rejects_values_wider_than_the_level() {
+    let p = propagator(&[1, 2], 2);
+    assert_eq!(p.invalid_chunk_count(&big(16), 4), Err(HierarchyError::ValueTooLargeForNBits { value: big(16), n_bits: 4 }));
+    assert!(matches!(p.invalid_chunk_count(&big(0), 6), Err(HierarchyError::InvalidHierarchicalLevel { .. })));
+}
+
+#[test]
+fn witness_records_the_components_and_verifies() {
+    let p = propagator(&[1, 2, 5], 3);
+    let member = big(0b101_001_010_010);
+    let witness = p.membership_witness(&member, 12).unwrap();
+    assert_eq!((witness.value.clone(), witness.n_bits), (member.clone(), 12));
+    assert_eq!(witness.components, p.decompose_to_base(&member, 12).unwrap());
+    assert_eq!(witness.pattern_fingerprint, p.initial_pattern().fingerprint());
+    assert_eq!(p.verify_witness(&witness), Ok(true));
+}
+
+#[test]
+fn tampered_witnesses_fail_verification() {
+    let p = propagator(&[1, 2, 5], 3);
+    let witness = p.membership_witness(&big(0b101_001_010_010), 12).unwrap();
+
+    let mut other_value = witness.clone();
+    other_value.value = big(0b101_001_010_001);
+    let mut other_component = witness.clone();
+    other_component.components[1] = big(3);
+    let mut missing_component = witness.clone();
+    missing_component.components.pop();
+    let mut other_fingerprint = witness.clone();
+    other_fingerprint.pattern_fingerprint ^= 1;
+
+    for tampered in [other_value, other_component, missing_component, other_fingerprint] {
+        assert_eq!(p.verify_witness(&tampered), Ok(false), "{tampered:?}");
+    }
+}
+
+#[test]
+fn witnesses_do_not_verify_against_another_pattern() {
+    let witness = propagator(&[1, 2, 5], 3).membership_witness(&big(0b001_010), 6).unwrap();
+    // The value is still a member here, but the pattern differs.
+    assert_eq!(propagator(&[1, 2], 3).verify_witness(&witness), Ok(false));
+}
+
+#[test]
+fn fingerprint_depends_only_on_the_pattern_contents() {
+    assert_eq!(pattern(&[1, 2, 5], 3).fingerprint(), pattern(&[5, 1, 2], 3).fingerprint());
+    assert_ne!(pattern(&[1, 2, 5], 3).fingerprint(), pattern(&[1, 2, 5], 4).fingerprint());
+    assert_ne!(pattern(&[1, 2, 5], 3).fingerprint(), pattern(&[1, 2, 6], 3).fingerprint());
+}
+
+#[test]
+fn witnesses_require_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.membership_witness(&big(0b111_001), 6), Err(HierarchyError::NotAMember(big(0b111_001))));
+    let mut witness = p.membership_witness(&big(0b001_010), 6).unwrap();
+    witness.n_bits = 9;
+    assert_eq!(
+        p.verify_witness(&witness),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+}
+
+#[test]
+fn explanation_leads_to_an_offending_lower_quarter() {
+    let p = propagator(&[1, 2, 3], 4);
+    // Chunks 0x1, 0x2, 0x3, 0xF: only the lowest quarter is not in S_base.
+    assert_eq!(
+        p.explain_membership(&big(0x123F), 16),
+        Ok(MembershipExplanation::NotMember { value: big(0xF), n_bits: 4, path: vec![1, 1] })
+    );
+}
+
+#[test]
+fn explanation_follows_the_first_failing_part() {
+    let p = propagator(&[1, 2, 3], 4);
+    // Chunks 0x1, 0xF, 0x2, 0xF: the upper half fails first, at its lower quarter.
+    assert_eq!(
+        p.explain_membership(&big(0x1F2F), 16),
+        Ok(MembershipExplanation::NotMember { value: big(0xF), n_bits: 4, path: vec![0, 1] })
+    );
+    assert_eq!(
+        p.explain_membership(&big(0xF), 4),
+        Ok(MembershipExplanation::NotMember { value: big(0xF), n_bits: 4, path: vec![] })
+    );
+}
+
+#[test]
+fn explanation_agrees_with_is_member() {
+    let p = propagator(&[1, 2], 2);
+    for x in 0..1 << 8 {
+        let explanation = p.explain_membership(&big(x), 8).unwrap();
+        assert_eq!(explanation == MembershipExplanation::IsMember, p.is_member(&big(x), 8).unwrap(), "{x}");
+        if let MembershipExplanation::NotMember { value, n_bits, path } = explanation {
+            assert_eq!((n_bits, path.len()), (2, 2));
+            assert!(!p.is_member(&value, 2).unwrap());
+        }
+    }
+    assert!(p.explain_membership(&big(0), 6).is_err());
+}
+
+#[test]
+fn trace_evaluates_every_part_of_a_non_member() {
+    let p = propagator(&[1, 2, 3], 4);
+    // Chunks 0x1, 0xF, 0x2, 0xF: both halves fail, each because of their lower chunk.
+    let trace = p.trace_membership(&big(0x1F2F), 16).unwrap();
+    assert_eq!((trace.value.clone(), trace.n_bits, trace.passed), (big(0x1F2F), 16, false));
+    let halves: Vec<(u64, bool)> = trace.children.iter().map(|c| (c.value.to_u64_digits()[0], c.passed)).collect();
+    assert_eq!(halves, vec![(0x1F, false), (0x2F, false)]);
+    let leaves: Vec<bool> = trace.children.iter().flat_map(|c| c.children.iter().map(|leaf| leaf.passed)).collect();
+    assert_eq!(leaves, vec![true, false, true, false]);
+    assert!(trace.children.iter().flat_map(|c| &c.children).all(|leaf| leaf.is_leaf() && leaf.n_bits == 4));
+    assert_eq!(trace.failed_leaves(), p.check_membership_detailed(&big(0x1F2F), 16).unwrap().failures);
+}
+
+#[test]
+fn trace_root_agrees_with_is_member() {
+    let base = pattern(&[1, 2], 2);
+    for p in [
+        Propagator::new(base.clone()),
+        Propagator::with_arity(base.clone(), 4).unwrap(),
+        Propagator::with_rule(base.clone(), MembershipRule::EitherHalf),
+        Propagator::with_rule(base, MembershipRule::ExactlyOne),
+    ] {
+        for x in 0..1 << 8 {
+            let trace = p.trace_membership(&big(x), 8).unwrap();
+            assert_eq!(trace.passed, p.is_member(&big(x), 8).unwrap());
+            assert_eq!(trace.children.len(), p.arity());
+        }
+    }
+}
+
+#[test]
+fn trace_at_the_base_level_is_a_single_leaf() {
+    let p = Propagator::new(InitialPattern::from_u64_values(&[1, 2], 2).unwrap());
+    let trace = p.trace_membership(&big(3), 2).unwrap();
+    assert!(trace.is_leaf() && !trace.passed);
+    assert_eq!(trace.failed_leaves(), vec![ChunkFailure { leaf_index: 0, value: big(3) }]);
+    assert!(matches!(p.trace_membership(&big(0), 6), Err(HierarchyError::InvalidHierarchicalLevel { .. })));
+}
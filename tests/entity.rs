@@ -0,0 +1,253 @@
+mod common;
+
+use common::*;
+use num_bigint::{BigUint, RandBigInt};
+use paired_binary::{hamming_distance, HierarchyError, PairedEntity};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeSet, HashSet};
+
+#[test]
+fn display_pads_both_values_to_the_entity_width() {
+    let e = PairedEntity::new(big(3), 4).unwrap();
+    assert_eq!(e.to_string(), "PairedEntity(n=4, x=0011 (3), x'=1100 (12))");
+    assert_eq!(e.to_binary_string(), ("0011".to_string(), "1100".to_string()));
+}
+
+#[test]
+fn binary_strings_are_not_truncated_for_wide_entities() {
+    let e = PairedEntity::new(big(1), 200).unwrap();
+    let (x, x_prime) = e.to_binary_string();
+    assert_eq!((x.len(), x_prime.len()), (200, 200));
+    assert_eq!(x, format!("{}1", "0".repeat(199)));
+    assert_eq!(x_prime, format!("{}0", "1".repeat(199)));
+    assert!(e.to_string().contains(&x));
+}
+
+#[test]
+fn pair_returns_both_values() {
+    let e = PairedEntity::new(big(0b0110), 4).unwrap();
+    assert_eq!(e.pair(), (&big(0b0110), &big(0b1001)));
+}
+
+#[test]
+fn complement_of_flips_exactly_n_bits() {
+    let e = PairedEntity::new(big(0), 4).unwrap();
+    assert_eq!(e.complement_of(&big(0b0110)).unwrap(), big(0b1001));
+    assert_eq!(e.complement_of(&big(0)).unwrap(), big(0b1111));
+    assert_eq!(e.complement_of(&big(0b1111)).unwrap(), big(0));
+    assert_eq!(
+        e.complement_of(&big(16)),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(16), n_bits: 4 })
+    );
+}
+
+#[test]
+fn hamming_weight_of_all_zeros_and_all_ones() {
+    let zeros = PairedEntity::new(big(0), 8).unwrap();
+    let ones = PairedEntity::new(big(0xFF), 8).unwrap();
+    assert_eq!(zeros.hamming_weight(), 0);
+    assert_eq!(ones.hamming_weight(), 8);
+    assert_eq!(hamming_distance(&zeros.x, &ones.x), 8);
+    assert_eq!(hamming_distance(&zeros.x, &zeros.x), 0);
+}
+
+#[test]
+fn weights_of_x_and_its_complement_sum_to_n_bits() {
+    for x in 0..1 << 6 {
+        let e = PairedEntity::new(big(x), 6).unwrap();
+        assert_eq!(e.hamming_weight() + e.x_prime.count_ones(), 6);
+        assert_eq!(hamming_distance(&e.x, &e.x_prime), 6);
+    }
+}
+
+#[test]
+fn from_hex_parses_the_x_value() {
+    let e = PairedEntity::from_hex("ff", 8).unwrap();
+    assert_eq!(e.x_prime.to_str_radix(16), "0");
+    assert_eq!(format!("{:02x}", e.x_prime), "00");
+    assert_eq!(PairedEntity::from_hex("0A", 4).unwrap(), PairedEntity::new(big(10), 4).unwrap());
+}
+
+#[test]
+fn from_hex_rejects_bad_digits_and_oversized_values() {
+    assert_eq!(PairedEntity::from_hex("zz", 8), Err(HierarchyError::InvalidHexString("zz".to_string())));
+    assert_eq!(
+        PairedEntity::from_hex("1ff", 8),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(0x1ff), n_bits: 8 })
+    );
+}
+
+#[test]
+fn from_u64_matches_new() {
+    assert_eq!(PairedEntity::from_u64(0b101, 3), PairedEntity::new(big(0b101), 3));
+    assert_eq!(PairedEntity::from_u64(u64::MAX, 64).unwrap().x_prime, big(0));
+    assert_eq!(
+        PairedEntity::from_u64(8, 3),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(8), n_bits: 3 })
+    );
+}
+
+#[test]
+fn bytes_round_trip_for_random_entities() {
+    let mut rng = StdRng::seed_from_u64(19);
+    for _ in 0..500 {
+        let n_bits = rng.gen_range(1..200);
+        let e = PairedEntity::new(rng.gen_biguint(n_bits as u64), n_bits).unwrap();
+        let bytes = e.to_bytes();
+        assert_eq!(bytes.len(), 4 + n_bits.div_ceil(8));
+        assert_eq!(PairedEntity::from_bytes(&bytes).unwrap(), e);
+    }
+}
+
+#[test]
+fn bytes_are_padded_to_the_entity_width() {
+    let e = PairedEntity::new(big(5), 12).unwrap();
+    assert_eq!(e.to_bytes(), vec![12, 0, 0, 0, 0, 5]);
+}
+
+#[test]
+fn malformed_bytes_are_rejected() {
+    assert_eq!(PairedEntity::from_bytes(&[1, 0]), Err(HierarchyError::MalformedBytes { expected_len: 4, actual_len: 2 }));
+    assert_eq!(PairedEntity::from_bytes(&[9, 0, 0, 0, 1]), Err(HierarchyError::MalformedBytes { expected_len: 6, actual_len: 5 }));
+    assert_eq!(
+        PairedEntity::from_bytes(&[4, 0, 0, 0, 0xff]),
+        Err(HierarchyError::ValueTooLargeForNBits { value: big(0xff), n_bits: 4 })
+    );
+}
+
+#[test]
+fn bitwise_combinators_act_on_x() {
+    let a = PairedEntity::new(big(0b0011), 4).unwrap();
+    let b = PairedEntity::new(big(0b0101), 4).unwrap();
+    assert_eq!(a.and(&b).unwrap(), PairedEntity::new(big(0b0001), 4).unwrap());
+    assert_eq!(a.or(&b).unwrap(), PairedEntity::new(big(0b0111), 4).unwrap());
+    assert_eq!(a.xor(&b).unwrap(), PairedEntity::new(big(0b0110), 4).unwrap());
+}
+
+#[test]
+fn xor_with_the_complement_is_all_ones() {
+    for x in 0..1 << 5 {
+        let e = PairedEntity::new(big(x), 5).unwrap();
+        let complement = PairedEntity::new(e.x_prime.clone(), 5).unwrap();
+        // All ones is the larger value of its pair, so the canonical result has x == 0.
+        let all_ones = e.xor(&complement).unwrap();
+        assert_eq!((all_ones.x, all_ones.x_prime), (big(0), big(0b11111)));
+    }
+}
+
+#[test]
+fn bitwise_combinators_require_equal_widths() {
+    let a = PairedEntity::new(big(1), 4).unwrap();
+    let b = PairedEntity::new(big(1), 5).unwrap();
+    let mismatch = HierarchyError::MismatchedNBits { lhs: 4, rhs: 5 };
+    assert_eq!(a.and(&b), Err(mismatch.clone()));
+    assert_eq!(a.or(&b), Err(mismatch.clone()));
+    assert_eq!(a.xor(&b), Err(mismatch));
+}
+
+#[test]
+fn bitwise_combinators_return_canonical_entities() {
+    let a = PairedEntity::new(big(0b1100), 4).unwrap();
+    let b = PairedEntity::new(big(0b1010), 4).unwrap();
+    // 0b1110 is the larger value of its pair, so x becomes its complement 0b0001.
+    let or = a.or(&b).unwrap();
+    assert!(or.x <= or.x_prime);
+    assert_eq!((or.x, or.x_prime), (big(0b0001), big(0b1110)));
+}
+
+fn canonical(x: u64, n_bits: usize) -> PairedEntity {
+    PairedEntity::new_canonical_from_x(big(x), n_bits).unwrap()
+}
+
+#[test]
+fn sorting_groups_by_width_then_value() {
+    let mut entities = [canonical(5, 4), canonical(1, 8), canonical(2, 4), canonical(0, 8), canonical(1, 3)];
+    entities.sort();
+    let keys: Vec<(usize, BigUint)> = entities.iter().map(|e| (e.n_bits, e.x.clone())).collect();
+    assert_eq!(keys, vec![(3, big(1)), (4, big(2)), (4, big(5)), (8, big(0)), (8, big(1))]);
+}
+
+#[test]
+fn non_canonical_entities_compare_raw_x() {
+    // 12 and 3 are the same pair at 4 bits, but `new` keeps x as given.
+    assert!(PairedEntity::new(big(3), 4).unwrap() < PairedEntity::new(big(12), 4).unwrap());
+    assert_ne!(PairedEntity::new(big(12), 4).unwrap(), canonical(12, 4));
+}
+
+#[test]
+fn entities_work_as_set_and_map_keys() {
+    let entities = [canonical(5, 4), canonical(10, 4), canonical(5, 8), canonical(2, 4)];
+    let hashed: HashSet<PairedEntity> = entities.iter().cloned().collect();
+    let ordered: BTreeSet<PairedEntity> = entities.iter().cloned().collect();
+    // 5 and 10 canonicalize to the same entity at 4 bits.
+    assert_eq!(hashed.len(), 3);
+    assert_eq!(ordered.into_iter().collect::<Vec<_>>(), vec![canonical(2, 4), canonical(5, 4), canonical(5, 8)]);
+}
+
+#[test]
+fn already_canonical_entity_is_unchanged() {
+    let e = PairedEntity::new(big(0b0011), 4).unwrap();
+    assert!(e.is_canonical());
+    assert_eq!(e.clone().into_canonical(), e);
+}
+
+#[test]
+fn non_canonical_entity_is_swapped_into_canonical_form() {
+    let e = PairedEntity::new(big(0b1100), 4).unwrap();
+    assert!(!e.is_canonical());
+    let c = e.into_canonical();
+    assert!(c.is_canonical());
+    assert_eq!((c.x, c.x_prime), (big(0b0011), big(0b1100)));
+    assert_eq!(c.n_bits, 4);
+}
+
+#[test]
+fn into_canonical_matches_the_canonical_constructor() {
+    let mut rng = StdRng::seed_from_u64(39);
+    for n_bits in [1usize, 7, 64, 130] {
+        for _ in 0..20 {
+            let x = rng.gen_biguint(n_bits as u64);
+            let e = PairedEntity::new(x.clone(), n_bits).unwrap();
+            assert_eq!(e.into_canonical(), PairedEntity::new_canonical_from_x(x, n_bits).unwrap());
+        }
+    }
+}
+
+#[test]
+fn reverse_bits_reverses_within_the_entity_width() {
+    let e = PairedEntity::new(big(0b0011), 4).unwrap();
+    let r = e.reverse_bits();
+    assert_eq!((r.x, r.x_prime, r.n_bits), (big(0b1100), big(0b0011), 4));
+    let r = PairedEntity::new(big(0b1011), 4).unwrap().reverse_bits();
+    assert_eq!((r.x, r.x_prime), (big(0b1101), big(0b0010)));
+    let r = PairedEntity::new(big(0b0001), 4).unwrap().reverse_bits();
+    assert_eq!(r.x, big(0b1000));
+}
+
+#[test]
+fn rotations_wrap_within_the_entity_width() {
+    let e = PairedEntity::new(big(0b0011), 4).unwrap();
+    for (k, left, right) in [(0, 0b0011, 0b0011), (1, 0b0110, 0b1001), (2, 0b1100, 0b1100), (3, 0b1001, 0b0110), (4, 0b0011, 0b0011), (5, 0b0110, 0b1001)] {
+        let l = e.rotate_left(k);
+        assert_eq!((l.x, l.x_prime), (big(left), big(left ^ 0b1111)), "left {k}");
+        let r = e.rotate_right(k);
+        assert_eq!((r.x, r.x_prime), (big(right), big(right ^ 0b1111)), "right {k}");
+    }
+}
+
+#[test]
+fn reversal_and_rotation_invert_on_wide_entities() {
+    let mut rng = StdRng::seed_from_u64(40);
+    for n_bits in [1usize, 63, 64, 65, 130] {
+        let e = PairedEntity::new(rng.gen_biguint(n_bits as u64), n_bits).unwrap();
+        assert_eq!(e.reverse_bits().reverse_bits(), e);
+        for _ in 0..10 {
+            let k = rng.gen_range(0..3 * n_bits);
+            let rotated = e.rotate_left(k);
+            assert!(rotated.x.bits() <= n_bits as u64);
+            assert_eq!(rotated.x.count_ones(), e.x.count_ones());
+            assert_eq!(rotated.rotate_right(k), e);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, InitialPattern};
+
+#[test]
+fn iter_members_yields_every_member_in_ascending_order() {
+    for (base, n_base_bits) in [(&[2u64, 0, 5][..], 3), (&[1, 2][..], 2), (&[1][..], 1)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 4 * n_base_bits] {
+            let members: Vec<BigUint> = p.iter_members(n_bits).unwrap().collect();
+            assert_eq!(members, bigs(&members_by_definition(base, n_base_bits, n_bits)), "{base:?} at {n_bits}");
+        }
+    }
+}
+
+#[test]
+fn iter_members_at_the_base_level_yields_the_sorted_base_values() {
+    let p = propagator(&[5, 2, 0], 3);
+    assert_eq!(p.iter_members(3).unwrap().collect::<Vec<_>>(), bigs(&[0, 2, 5]));
+}
+
+#[test]
+fn iter_members_rejects_invalid_levels() {
+    let p = propagator(&[2, 0, 5], 3);
+    assert_eq!(p.iter_members(9).err(), Some(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 }));
+}
+
+#[test]
+fn taking_from_iter_members_yields_the_smallest_members() {
+    let p = propagator(&[1, 2], 2);
+    assert_eq!(p.iter_members(4).unwrap().take(3).collect::<Vec<_>>(), bigs(&[5, 6, 9]));
+    assert_eq!(p.iter_members(4).unwrap().take(10).count(), 4);
+}
+
+#[test]
+fn member_iter_size_hint_is_exact() {
+    let p = propagator(&[2, 0, 5], 3);
+    let mut members = p.iter_members(12).unwrap();
+    assert_eq!(members.size_hint(), (81, Some(81)));
+    members.next();
+    assert_eq!(members.size_hint(), (80, Some(80)));
+    assert_eq!(members.count(), 80);
+}
+
+#[test]
+fn single_value_pattern_has_one_member_per_level() {
+    let p = propagator(&[4], 3);
+    for n_bits in [3, 6, 24] {
+        assert_eq!(p.iter_members(n_bits).unwrap().count(), 1);
+    }
+    assert_eq!(p.iter_members(6).unwrap().next(), Some(big(0b100_100)));
+}
+
+#[test]
+fn iter_members_matching_agrees_with_filtering_the_full_enumeration() {
+    let p = propagator(&[2, 0, 5], 3);
+    let constraints = [(0, big(5)), (2, big(0))];
+    let expected: Vec<BigUint> = p
+        .iter_members(12)
+        .unwrap()
+        .filter(|member| {
+            let components = p.decompose_to_base(member, 12).unwrap();
+            constraints.iter().all(|(leaf_index, value)| &components[*leaf_index] == value)
+        })
+        .collect();
+    let matching: Vec<BigUint> = p.iter_members_matching(12, &constraints).unwrap().collect();
+    assert_eq!(matching.len(), 9);
+    assert_eq!(matching, expected);
+}
+
+#[test]
+fn iter_members_matching_without_constraints_is_iter_members() {
+    let p = propagator(&[2, 0, 5], 3);
+    let all: Vec<BigUint> = p.iter_members(6).unwrap().collect();
+    assert_eq!(p.iter_members_matching(6, &[]).unwrap().collect::<Vec<_>>(), all);
+}
+
+#[test]
+fn iter_members_matching_rejects_bad_constraints() {
+    let p = propagator(&[2, 0, 5], 3);
+    assert_eq!(p.iter_members_matching(6, &[(0, big(3))]).err(), Some(HierarchyError::InvalidBaseComponent(big(3))));
+    assert_eq!(p.iter_members_matching(6, &[(1, big(2)), (1, big(5))]).err(), Some(HierarchyError::DuplicateLeafConstraint(1)));
+    assert_eq!(
+        p.iter_members_matching(6, &[(2, big(2))]).err(),
+        Some(HierarchyError::LeafIndexOutOfRange { leaf_index: 2, component_count: 2 })
+    );
+    assert_eq!(
+        p.iter_members_matching(7, &[]).err(),
+        Some(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 7, base_n_bits: 3 })
+    );
+}
+
+#[test]
+fn min_and_max_members_are_the_ends_of_the_enumeration() {
+    for (values, n_base_bits) in [(&[2u64, 0, 5][..], 3), (&[6, 1, 9, 3][..], 4), (&[1][..], 1)] {
+        let p = propagator(values, n_base_bits);
+        for depth in 0..3 {
+            let n_bits = n_base_bits << depth;
+            let members: Vec<BigUint> = p.iter_members(n_bits).unwrap().collect();
+            assert_eq!(p.min_member(n_bits).as_ref(), Ok(members.first().unwrap()), "{values:?} at {n_bits}");
+            assert_eq!(p.max_member(n_bits).as_ref(), Ok(members.last().unwrap()), "{values:?} at {n_bits}");
+        }
+    }
+}
+
+#[test]
+fn min_and_max_members_repeat_the_extreme_base_values() {
+    // 5 needs 3 bits, so {1, 5} is not a 2-bit pattern.
+    assert!(InitialPattern::from_u64_values(&[1, 5], 2).is_err());
+    let p = propagator(&[1, 5], 3);
+    assert_eq!(p.min_member(6), Ok(big(0b001_001)));
+    assert_eq!(p.max_member(6), Ok(big(0b101_101)));
+    assert_eq!(p.max_member(12), Ok(big(0b101_101_101_101)));
+}
+
+#[test]
+fn smallest_and_largest_members_match_min_and_max() {
+    let p = propagator(&[1, 5], 3);
+    for n_bits in [3, 6, 12, 24] {
+        assert_eq!(p.smallest_member(n_bits), p.min_member(n_bits));
+        assert_eq!(p.largest_member(n_bits), p.max_member(n_bits));
+    }
+    assert_eq!(p.smallest_member(6), Ok(big(0b001_001)));
+    assert_eq!(p.largest_member(6), Ok(big(0b101_101)));
+    let error = HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 };
+    assert_eq!(p.smallest_member(9), Err(error.clone()));
+    assert_eq!(p.largest_member(9), Err(error));
+}
+
+#[test]
+fn min_and_max_members_reject_invalid_levels() {
+    let p = propagator(&[2, 0, 5], 3);
+    let error = HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 };
+    assert_eq!(p.min_member(9), Err(error.clone()));
+    assert_eq!(p.max_member(9), Err(error));
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::HierarchyError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn distinct_random_members_are_distinct_members() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let p = propagator(&[1, 2, 3], 4);
+    // (16, 81) asks for every member, which goes through enumeration rather than rejection.
+    for (n_bits, count) in [(4, 3), (8, 9), (8, 5), (16, 10), (16, 81), (64, 100)] {
+        let members = p.generate_distinct_random_members(n_bits, count, &mut rng).unwrap();
+        assert_eq!(members.len(), count);
+        assert_eq!(members.iter().collect::<HashSet<&BigUint>>().len(), count);
+        for member in &members {
+            assert!(p.is_member(member, n_bits).unwrap());
+        }
+    }
+}
+
+#[test]
+fn distinct_random_members_cannot_exceed_the_member_count() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let p = propagator(&[1, 2, 3], 4);
+    assert_eq!(
+        p.generate_distinct_random_members(8, 10, &mut rng),
+        Err(HierarchyError::NotEnoughDistinctMembers { requested: 10, available: big(9) })
+    );
+}
+
+#[test]
+fn distinct_random_members_are_reproducible_for_a_seeded_rng() {
+    // Separately built propagators hold separately hashed sets of S_base values, so this
+    // also covers the draw order not depending on the set's iteration order.
+    let values = [1, 2, 3, 5, 8, 13, 14, 15];
+    for count in [5, 3000] {
+        let first = propagator(&values, 4).generate_distinct_random_members(16, count, &mut StdRng::seed_from_u64(14)).unwrap();
+        let second = propagator(&values, 4).generate_distinct_random_members(16, count, &mut StdRng::seed_from_u64(14)).unwrap();
+        assert_eq!(first, second);
+    }
+}
+
+#[test]
+fn asking_for_every_member_returns_a_permutation_of_s_n() {
+    let mut rng = StdRng::seed_from_u64(14);
+    let p = propagator(&[1, 2, 3], 4);
+    let mut members = p.generate_distinct_random_members(8, 9, &mut rng).unwrap();
+    members.sort();
+    assert_eq!(members, p.iter_members(8).unwrap().collect::<Vec<_>>());
+    assert!(p.generate_distinct_random_members(8, 0, &mut rng).unwrap().is_empty());
+}
+
+#[test]
+fn sampled_members_are_distinct_members() {
+    let mut rng = StdRng::seed_from_u64(15);
+    let p = propagator(&[1, 2, 5], 3);
+    for (n_bits, count) in [(6, 9), (12, 40), (3 << 10, 100)] {
+        let members = p.sample_members(n_bits, count, &mut rng).unwrap();
+        assert_eq!(members.iter().collect::<HashSet<&BigUint>>().len(), count);
+        assert!(members.iter().all(|m| p.is_member(m, n_bits).unwrap()));
+    }
+    assert_eq!(
+        p.sample_members(6, 10, &mut rng),
+        Err(HierarchyError::NotEnoughDistinctMembers { requested: 10, available: big(9) })
+    );
+}
+
+#[test]
+fn sampled_members_are_uniform() {
+    let mut rng = StdRng::seed_from_u64(15);
+    let p = propagator(&[1, 2, 5], 3);
+    let draws = 9000;
+    let mut counts = HashMap::new();
+    for _ in 0..draws {
+        let sample = p.sample_members(6, 2, &mut rng).unwrap();
+        *counts.entry(sample[0].clone()).or_insert(0u32) += 1;
+    }
+    assert_eq!(counts.len(), 9);
+    assert!(chi_squared(&counts, draws) < CHI_SQUARED_8_DOF_999, "{counts:?}");
+}
+
+#[test]
+fn uniform_members_are_uniform() {
+    let mut rng = StdRng::seed_from_u64(29);
+    let p = propagator(&[1, 2, 5], 3);
+    let draws = 9000;
+    let mut counts = HashMap::new();
+    for _ in 0..draws {
+        *counts.entry(p.generate_uniform_member(6, &mut rng).unwrap()).or_insert(0u32) += 1;
+    }
+    assert_eq!(counts.len(), 9);
+    assert!(counts.keys().all(|x| p.is_member(x, 6).unwrap()));
+    assert!(chi_squared(&counts, draws) < CHI_SQUARED_8_DOF_999, "{counts:?}");
+}
+
+#[test]
+fn uniform_members_are_reproducible_and_validated() {
+    let p = propagator(&[1, 2, 5], 3);
+    let draw = |seed| p.generate_uniform_member(3 << 6, &mut StdRng::seed_from_u64(seed)).unwrap();
+    assert_eq!(draw(30), draw(30));
+    assert!(p.is_member(&draw(31), 3 << 6).unwrap());
+    assert!(matches!(
+        p.generate_uniform_member(9, &mut StdRng::seed_from_u64(0)),
+        Err(HierarchyError::InvalidHierarchicalLevel { .. })
+    ));
+}
+
+#[test]
+fn random_member_stream_yields_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    let members: Vec<BigUint> = p.random_member_stream(24, StdRng::seed_from_u64(19)).unwrap().take(500).collect();
+    assert_eq!(members.len(), 500);
+    assert!(members.iter().all(|x| p.is_member(x, 24).unwrap()));
+}
+
+#[test]
+fn random_member_stream_matches_repeated_generation() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut rng = StdRng::seed_from_u64(20);
+    let expected: Vec<BigUint> = (0..50).map(|_| p.generate_random_s_n_member(12, &mut rng).unwrap()).collect();
+    let streamed: Vec<BigUint> = p.random_member_stream(12, StdRng::seed_from_u64(20)).unwrap().take(50).collect();
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn random_member_stream_can_pause_and_move_across_threads() {
+    let p = propagator(&[1, 2, 5], 3);
+    let all: Vec<BigUint> = p.random_member_stream(12, StdRng::seed_from_u64(21)).unwrap().take(20).collect();
+    let mut stream = p.random_member_stream(12, StdRng::seed_from_u64(21)).unwrap();
+    let first: Vec<BigUint> = stream.by_ref().take(10).collect();
+    let rest = std::thread::scope(|scope| scope.spawn(move || stream.take(10).collect::<Vec<_>>()).join().unwrap());
+    assert_eq!([first, rest].concat(), all);
+}
+
+#[test]
+fn random_member_stream_validates_the_level_up_front() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert!(matches!(
+        p.random_member_stream(9, StdRng::seed_from_u64(0)),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    ));
+}
+
+#[test]
+fn prefixed_members_keep_their_prefix() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let p = propagator(&[1, 2, 5], 3);
+    let prefix = bigs(&[5, 1, 2]);
+    for _ in 0..100 {
+        let member = p.generate_random_member_with_prefix(24, &prefix, &mut rng).unwrap();
+        assert!(p.is_member(&member, 24).unwrap());
+        assert_eq!(p.decompose_to_base(&member, 24).unwrap()[..3], prefix[..]);
+    }
+}
+
+#[test]
+fn prefixed_members_vary_in_the_free_components() {
+    let mut rng = StdRng::seed_from_u64(24);
+    let p = propagator(&[1, 2, 5], 3);
+    let members: HashSet<BigUint> =
+        (0..200).map(|_| p.generate_random_member_with_prefix(12, &bigs(&[2, 5]), &mut rng).unwrap()).collect();
+    // Two free components over three values.
+    assert_eq!(members.len(), 9);
+}
+
+#[test]
+fn prefixes_may_be_empty_or_cover_every_component() {
+    let mut rng = StdRng::seed_from_u64(25);
+    let p = propagator(&[1, 2, 5], 3);
+    let prefix = bigs(&[5, 1, 2, 2]);
+    let (expected, _) = p.compose_from_base(&prefix).unwrap();
+    assert_eq!(p.generate_random_member_with_prefix(12, &prefix, &mut rng), Ok(expected));
+    assert!(p.is_member(&p.generate_random_member_with_prefix(12, &[], &mut rng).unwrap(), 12).unwrap());
+}
+
+#[test]
+fn prefix_must_fit_and_hold_base_values() {
+    let mut rng = StdRng::seed_from_u64(26);
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.generate_random_member_with_prefix(6, &bigs(&[1, 2, 5]), &mut rng),
+        Err(HierarchyError::PrefixTooLong { prefix_len: 3, component_count: 2 })
+    );
+    assert_eq!(
+        p.generate_random_member_with_prefix(12, &bigs(&[1, 7]), &mut rng),
+        Err(HierarchyError::InvalidBaseComponent(big(7)))
+    );
+    assert!(matches!(
+        p.generate_random_member_with_prefix(9, &[], &mut rng),
+        Err(HierarchyError::InvalidHierarchicalLevel { .. })
+    ));
+}
+
+#[test]
+fn random_non_members_are_never_members() {
+    for seed in 0..200 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for (p, n_bits) in [(propagator(&[1, 2, 5], 3), 24), (propagator(&[0, 3], 2), 2), (propagator(&[0, 1, 2], 2), 64)] {
+            let x = p.generate_random_non_member(n_bits, &mut rng).unwrap();
+            assert!(!p.is_member(&x, n_bits).unwrap(), "seed {seed}: {x} at {n_bits} bits");
+        }
+    }
+}
+
+#[test]
+fn random_non_members_differ_from_a_member_in_one_component() {
+    let mut rng = StdRng::seed_from_u64(27);
+    let p = propagator(&[1, 2, 5], 3);
+    for _ in 0..100 {
+        let x = p.generate_random_non_member(12, &mut rng).unwrap();
+        let report = p.check_membership_detailed(&x, 12).unwrap();
+        assert_eq!(report.failures.len(), 1);
+    }
+}
+
+#[test]
+fn a_complete_s_base_has_no_non_members() {
+    let mut rng = StdRng::seed_from_u64(28);
+    let p = propagator(&[0, 1, 2, 3], 2);
+    assert_eq!(p.generate_random_non_member(8, &mut rng), Err(HierarchyError::SetIsComplete { n_base_bits: 2 }));
+}
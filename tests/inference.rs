@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, Propagator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn inferred_pattern_holds_the_distinct_chunks() {
+    let samples = bigs(&[0b001_010_001_010, 0b101_101_001_001]);
+    assert_eq!(Propagator::infer_base_pattern(&samples, 12, 3).unwrap().s_base_values, pattern(&[1, 2, 5], 3).s_base_values);
+}
+
+#[test]
+fn samples_are_members_under_the_inferred_pattern() {
+    let mut rng = StdRng::seed_from_u64(14);
+    let p = propagator(&[1, 2, 5, 6], 3);
+    let samples: Vec<BigUint> = (0..40).map(|_| p.generate_random_s_n_member(24, &mut rng).unwrap()).collect();
+    let inferred = Propagator::infer_base_pattern(&samples, 24, 3).unwrap();
+    assert!(inferred.s_base_values.is_subset(&p.initial_pattern().s_base_values));
+    let q = Propagator::new(inferred);
+    assert!(samples.iter().all(|x| q.is_member(x, 24).unwrap()));
+}
+
+#[test]
+fn inference_validates_the_widths_and_samples() {
+    let samples = bigs(&[0b001_010_001_010]);
+    assert_eq!(
+        Propagator::infer_base_pattern(&samples, 9, 3).err(),
+        Some(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+    assert_eq!(
+        Propagator::infer_base_pattern(&samples, 6, 3).err(),
+        Some(HierarchyError::ValueTooLargeForNBits { value: big(0b001_010_001_010), n_bits: 6 })
+    );
+    assert_eq!(
+        Propagator::infer_base_pattern(&samples, 2, 3).err(),
+        Some(HierarchyError::TargetNBitsTooSmall { target_n_bits: 2, base_n_bits: 3 })
+    );
+    assert_eq!(Propagator::infer_base_pattern(&samples, 12, 0).err(), Some(HierarchyError::NonPositiveNBits(0)));
+    assert_eq!(Propagator::infer_base_pattern(&[], 12, 3).err(), Some(HierarchyError::EmptySBaseValues));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::*;
+use paired_binary::{HierarchyError, MembershipRule, Propagator};
+
+#[test]
+fn levels_up_to_doubles_from_the_base_width() {
+    let p = propagator(&[1, 2], 3);
+    assert_eq!(p.levels_up_to(50).collect::<Vec<_>>(), vec![3, 6, 12, 24, 48]);
+    assert_eq!(p.levels_up_to(48).last(), Some(48));
+    assert_eq!(p.levels_up_to(3).collect::<Vec<_>>(), vec![3]);
+    assert_eq!(p.levels_up_to(2).count(), 0);
+}
+
+#[test]
+fn levels_up_to_stops_before_overflowing() {
+    let p = propagator(&[1, 2], 3);
+    // 3 * 2^62 is the last level that fits in a 64-bit usize.
+    let levels: Vec<usize> = p.levels_up_to(usize::MAX).collect();
+    assert_eq!(levels.len(), usize::BITS as usize - 1);
+    assert_eq!(levels.last(), Some(&(3usize << (usize::BITS - 2))));
+}
+
+#[test]
+fn level_index_inverts_levels_up_to() {
+    let p = propagator(&[1, 2], 3);
+    for (k, n_bits) in p.levels_up_to(usize::MAX).enumerate() {
+        assert_eq!(p.level_index(n_bits), Some(k as u32));
+        assert!(p.is_valid_level(n_bits));
+    }
+}
+
+#[test]
+fn invalid_levels_have_no_index() {
+    let p = propagator(&[1, 2], 3);
+    for n_bits in [0, 1, 2, 4, 9, 18, 30, usize::MAX] {
+        assert_eq!(p.level_index(n_bits), None, "{n_bits}");
+        assert!(!p.is_valid_level(n_bits), "{n_bits}");
+    }
+}
+
+#[test]
+fn levels_follow_the_arity() {
+    let p = Propagator::with_arity(pattern(&[1, 2], 3), 3).unwrap();
+    assert_eq!(p.levels_up_to(100).collect::<Vec<_>>(), vec![3, 9, 27, 81]);
+    assert_eq!(p.level_index(81), Some(3));
+    assert!(!p.is_valid_level(6));
+}
+
+#[test]
+fn valid_levels_up_to_lists_every_level_in_range() {
+    let p = propagator(&[1, 2], 3);
+    assert_eq!(p.valid_levels_up_to(30), vec![3, 6, 12, 24]);
+    assert_eq!(p.valid_levels_up_to(24), vec![3, 6, 12, 24]);
+    assert!(p.valid_levels_up_to(2).is_empty());
+}
+
+#[test]
+fn highest_valid_level_leq_snaps_down() {
+    let p = propagator(&[1, 2], 3);
+    for (max_n_bits, expected) in [(0, None), (2, None), (3, Some(3)), (5, Some(3)), (30, Some(24)), (48, Some(48))] {
+        assert_eq!(p.highest_valid_level_leq(max_n_bits), expected, "{max_n_bits}");
+    }
+    assert_eq!(p.highest_valid_level_leq(usize::MAX), Some(3usize << (usize::BITS - 2)));
+}
+
+#[test]
+fn component_count_is_the_number_of_base_components() {
+    let p = propagator(&[1, 6], 4);
+    assert_eq!(p.component_count(32), Ok(8));
+    assert_eq!(p.component_count(4), Ok(1));
+    let (x, _) = p.compose_from_base(&bigs(&[1, 6, 6, 1, 1, 1, 6, 6])).unwrap();
+    assert_eq!(p.decompose_to_base(&x, 32).unwrap().len(), p.component_count(32).unwrap());
+}
+
+#[test]
+fn component_count_rejects_invalid_levels() {
+    let p = propagator(&[1, 6], 4);
+    assert_eq!(p.component_count(24), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 24, base_n_bits: 4 }));
+    assert_eq!(p.component_count(2), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 2, base_n_bits: 4 }));
+}
+
+#[test]
+fn membership_levels_lists_every_level_containing_the_value() {
+    let p = propagator(&[0, 1, 2], 2);
+    // 1 is in S_base, and its leading zero chunks are in S_base too.
+    assert_eq!(p.membership_levels(&big(1), 16), Ok(vec![2, 4, 8, 16]));
+    // 0b0110 needs 4 bits, so the base level is skipped.
+    assert_eq!(p.membership_levels(&big(0b0110), 20), Ok(vec![4, 8, 16]));
+    assert_eq!(p.membership_levels(&big(1), 1), Ok(vec![]));
+}
+
+#[test]
+fn a_base_member_need_not_be_a_member_at_higher_levels() {
+    let p = propagator(&[1, 2], 2);
+    // At 4 bits and above, 1 has a zero upper chunk, which is not in S_base.
+    assert_eq!(p.membership_levels(&big(1), 8), Ok(vec![2]));
+    assert_eq!(p.membership_levels(&big(0b0101), 8), Ok(vec![4]));
+}
+
+#[test]
+fn propagators_compare_their_pattern_arity_and_rule() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p, propagator(&[5, 2, 1], 3));
+    assert_ne!(p, Propagator::with_arity(pattern(&[1, 2, 5], 3), 3).unwrap());
+    assert_ne!(p, Propagator::with_rule(pattern(&[1, 2, 5], 3), MembershipRule::EitherHalf));
+    assert_ne!(p, propagator(&[1, 2], 3));
+
+    let mut precomputed = p.clone();
+    precomputed.precompute_levels(12, 1 << 10).unwrap();
+    assert_eq!(precomputed, p);
+}
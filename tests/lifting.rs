@@ -0,0 +1,53 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, Propagator};
+
+#[test]
+fn lifted_pattern_holds_exactly_the_level_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    let lifted = p.lift_to_level(6, 9).unwrap();
+    assert_eq!(lifted.n_base_bits, 6);
+    let expected: Vec<BigUint> = members_by_definition(&[1, 2, 5], 3, 6).into_iter().map(big).collect();
+    assert_eq!(lifted.s_base_values, expected.into_iter().collect());
+}
+
+#[test]
+fn lifted_propagator_agrees_on_shared_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    let lifted = Propagator::new(p.lift_to_level(6, 9).unwrap());
+    for x in 0u64..1 << 12 {
+        let x = big(x);
+        if x < big(1 << 6) {
+            assert_eq!(p.is_member(&x, 6).unwrap(), lifted.is_member(&x, 6).unwrap(), "{x} at 6");
+        }
+        assert_eq!(p.is_member(&x, 12).unwrap(), lifted.is_member(&x, 12).unwrap(), "{x} at 12");
+    }
+    assert_eq!(p.count_members(96).unwrap(), lifted.count_members(96).unwrap());
+}
+
+#[test]
+fn lifting_respects_the_member_limit() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.lift_to_level(6, 8).err(), Some(HierarchyError::TooManyMembers { count: big(9), max_members: 8 }));
+    assert_eq!(p.lift_to_level(12, 80).err(), Some(HierarchyError::TooManyMembers { count: big(81), max_members: 80 }));
+    assert!(p.lift_to_level(12, 81).is_ok());
+}
+
+#[test]
+fn lifting_the_base_level_returns_the_same_pattern() {
+    let p = propagator(&[1, 2, 5], 3);
+    let lifted = p.lift_to_level(3, 3).unwrap();
+    assert_eq!(lifted.s_base_values, p.initial_pattern().s_base_values);
+    assert_eq!(lifted.n_base_bits, p.initial_pattern().n_base_bits);
+}
+
+#[test]
+fn lifting_rejects_invalid_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.lift_to_level(9, 100).err(),
+        Some(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use paired_binary::{HierarchyError, InitialPattern, Propagator};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn is_member_u64_matches_is_member() {
+    let mut rng = StdRng::seed_from_u64(16);
+    for (base, n_base_bits) in [(&[1u64, 2, 5][..], 3), (&[0x3C, 0xA5, 0xFF][..], 8), (&[1][..], 1)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in p.levels_up_to(64) {
+            for _ in 0..200 {
+                let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap().to_u64().unwrap();
+                let random = rng.gen::<u64>() >> (64 - n_bits);
+                for x in [member, random, member ^ 1] {
+                    assert_eq!(p.is_member_u64(x, n_bits), p.is_member(&big(x), n_bits), "{x} at {n_bits}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn decompose_and_compose_u64_match_the_big_uint_paths() {
+    let mut rng = StdRng::seed_from_u64(17);
+    let p = propagator(&[0x3C, 0xA5, 0xFF], 8);
+    for n_bits in [8, 16, 32, 64] {
+        for _ in 0..100 {
+            let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+            let x = member.to_u64().unwrap();
+            let components = p.decompose_to_base_u64(x, n_bits).unwrap();
+            let expected: Vec<u64> = p.decompose_to_base(&member, n_bits).unwrap().iter().map(|c| c.to_u64().unwrap()).collect();
+            assert_eq!(components, expected);
+            assert_eq!(p.compose_from_base_u64(&components), Ok((x, n_bits)));
+        }
+    }
+}
+
+#[test]
+fn u64_paths_reject_levels_above_64_bits() {
+    let p = propagator(&[1, 2, 5], 3);
+    let too_wide = HierarchyError::LevelExceedsMachineWord { n_bits: 96, max_bits: 64 };
+    assert_eq!(p.is_member_u64(0, 96), Err(too_wide.clone()));
+    assert_eq!(p.decompose_to_base_u64(0, 96), Err(too_wide.clone()));
+    assert_eq!(p.compose_from_base_u64(&[1; 32]), Err(too_wide));
+}
+
+#[test]
+fn u64_paths_keep_the_usual_validation() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.is_member_u64(64, 6), Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 }));
+    assert_eq!(p.is_member_u64(0, 9), Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 }));
+    assert_eq!(p.decompose_to_base_u64(0b111_001, 6), Err(HierarchyError::NotAMember(big(0b111_001))));
+    assert_eq!(p.compose_from_base_u64(&[1, 7]), Err(HierarchyError::InvalidBaseComponent(big(7))));
+    assert_eq!(p.compose_from_base_u64(&[1, 2, 5]), Err(HierarchyError::InvalidComponentCount(3)));
+}
+
+#[test]
+fn base_values_beyond_u64_are_not_machine_words() {
+    let wide = BigUint::from(1u32) << 70;
+    let p = Propagator::new(InitialPattern::new([wide].into_iter().collect(), 72).unwrap());
+    assert!(matches!(p.is_member_u64(0, 72), Err(HierarchyError::LevelExceedsMachineWord { .. })));
+}
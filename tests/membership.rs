@@ -0,0 +1,231 @@
+mod common;
+
+use common::*;
+use num_bigint::{BigUint, RandBigInt};
+use paired_binary::{EntityMembership, HierarchyError, InitialPattern, PairedEntity, Propagator};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+#[test]
+fn is_member_handles_very_deep_levels() {
+    // Base 1 bit, target 2^22 bits: 22 levels above the base, the deepest hierarchy
+    // that stays cheap to build. No level reachable in memory overflowed the default
+    // stack before, since the recursion depth is only log2(n_target_bits / n_base_bits),
+    // so the check runs on a thread with a 64 KiB stack to pin the stack usage instead.
+    let p = propagator(&[1], 1);
+    let n_bits = 1 << 22;
+    let member = (big(1) << n_bits) - 1u32;
+    let (member_result, non_member_result) = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || (p.is_member(&member, n_bits).unwrap(), p.is_member(&(member ^ big(1)), n_bits).unwrap()))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(member_result);
+    assert!(!non_member_result);
+}
+
+/// The recursive definition of membership, used as a reference for widths past `u64`.
+fn is_member_recursive(base: &BTreeSet<BigUint>, n_base_bits: usize, x: &BigUint, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(x);
+    }
+    let half = n_bits / 2;
+    let lower = x & ((BigUint::from(1u32) << half) - 1u32);
+    is_member_recursive(base, n_base_bits, &(x >> half), half) && is_member_recursive(base, n_base_bits, &lower, half)
+}
+
+#[test]
+fn is_member_matches_the_recursive_definition() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for n_base_bits in [1usize, 3, 5, 31, 32, 33, 40, 64, 70] {
+        let base: BTreeSet<BigUint> = (0..3).map(|_| rng.gen_biguint(n_base_bits as u64)).collect();
+        let p = Propagator::new(InitialPattern::new(base.clone(), n_base_bits).unwrap());
+        for depth in 0..4 {
+            let n_bits = n_base_bits << depth;
+            for _ in 0..30 {
+                let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+                let flipped = &member ^ (BigUint::from(1u32) << rng.gen_range(0..n_bits));
+                for x in [member, flipped] {
+                    assert_eq!(p.is_member(&x, n_bits).unwrap(), is_member_recursive(&base, n_base_bits, &x, n_bits), "{x} at {n_bits}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn is_members_matches_is_member_per_value() {
+    let p = propagator(&[1, 2, 5], 3);
+    let targets: Vec<BigUint> = (0..1 << 12).map(big).collect();
+    let results = p.is_members(&targets, 12).unwrap();
+    assert_eq!(results.len(), targets.len());
+    for (x, result) in targets.iter().zip(results) {
+        assert_eq!(result, p.is_member(x, 12));
+    }
+}
+
+#[test]
+fn is_members_reports_oversized_values_per_item() {
+    let p = propagator(&[1, 2, 5], 3);
+    let results = p.is_members(&bigs(&[0b001_010, 1 << 6, 0]), 6).unwrap();
+    assert_eq!(results[0], Ok(true));
+    assert_eq!(results[1], Err(HierarchyError::ValueTooLargeForNBits { value: big(1 << 6), n_bits: 6 }));
+    assert_eq!(results[2], Ok(false));
+    assert!(matches!(p.is_members(&[], 9), Err(HierarchyError::InvalidHierarchicalLevel { .. })));
+}
+
+#[test]
+fn is_entity_member_checks_both_values() {
+    // S_base = {1, 6, 5}: 1 and 6 pair up, 5's complement 2 is absent, 3 and 4 are absent.
+    let p = propagator(&[1, 6, 5], 3);
+    let entity = |x: u64| PairedEntity::new_canonical_from_x(big(x), 3).unwrap();
+    assert_eq!(p.is_entity_member(&entity(1), 3), Ok(EntityMembership::Both));
+    assert_eq!(p.is_entity_member(&entity(5), 3), Ok(EntityMembership::XPrimeMember));
+    assert_eq!(p.is_entity_member(&entity(3), 3), Ok(EntityMembership::Neither));
+    // `new` keeps x as given, so 5 stays on the x side.
+    assert_eq!(p.is_entity_member(&PairedEntity::new(big(5), 3).unwrap(), 3), Ok(EntityMembership::XMember));
+}
+
+#[test]
+fn is_entity_member_agrees_with_is_member_at_higher_levels() {
+    let p = propagator(&[1, 6, 5], 3);
+    for x in 0u64..64 {
+        let entity = PairedEntity::new(big(x), 6).unwrap();
+        let expected = match (p.is_member(&entity.x, 6).unwrap(), p.is_member(&entity.x_prime, 6).unwrap()) {
+            (true, true) => EntityMembership::Both,
+            (true, false) => EntityMembership::XMember,
+            (false, true) => EntityMembership::XPrimeMember,
+            (false, false) => EntityMembership::Neither,
+        };
+        assert_eq!(p.is_entity_member(&entity, 6), Ok(expected), "{x}");
+    }
+}
+
+#[test]
+fn is_entity_member_requires_matching_widths() {
+    let p = propagator(&[1, 6, 5], 3);
+    let entity = PairedEntity::new(big(3), 3).unwrap();
+    assert_eq!(p.is_entity_member(&entity, 6), Err(HierarchyError::EntityWidthMismatch { entity_n_bits: 3, target_n_bits: 6 }));
+    let entity = PairedEntity::new(big(3), 5).unwrap();
+    assert_eq!(
+        p.is_entity_member(&entity, 5),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 5, base_n_bits: 3 })
+    );
+}
+
+#[test]
+fn base_lookups_agree_with_the_value_set() {
+    let mut rng = StdRng::seed_from_u64(15);
+    // 8-bit bases of several sizes, including more than 64 values.
+    for size in [1, 2, 63, 64, 65, 200, 256] {
+        let base: BTreeSet<BigUint> = rand::seq::index::sample(&mut rng, 256, size).into_iter().map(|v| big(v as u64)).collect();
+        let p = Propagator::new(InitialPattern::new(base.clone(), 8).unwrap());
+        for x in 0u64..1 << 16 {
+            let x = big(x);
+            assert_eq!(p.is_member(&x, 16).unwrap(), is_member_recursive(&base, 8, &x, 16), "{x} with {size} values");
+        }
+    }
+}
+
+#[test]
+fn bit_set_lookups_agree_with_the_value_set() {
+    let mut rng = StdRng::seed_from_u64(16);
+    // Bases up to 16 bits are looked up in a bit-set, wider ones by binary search.
+    for n_base_bits in [1usize, 2, 6, 7, 15, 16, 17] {
+        let values: Vec<u64> = (0..20).map(|_| rng.gen_range(0..1u64 << n_base_bits)).collect();
+        let base: BTreeSet<BigUint> = values.iter().copied().map(big).collect();
+        let p = Propagator::new(InitialPattern::new(base.clone(), n_base_bits).unwrap());
+        for x in (0..1u64 << n_base_bits).step_by(1 + (1 << n_base_bits) / 5000) {
+            assert_eq!(p.is_member(&big(x), n_base_bits).unwrap(), base.contains(&big(x)), "{x} at {n_base_bits}");
+            assert_eq!(p.is_member_u64(x, n_base_bits).unwrap(), base.contains(&big(x)), "{x} at {n_base_bits}");
+        }
+        for x in values.iter().chain(&[0, 1 << n_base_bits, u64::MAX]) {
+            assert_eq!(p.validate_components(&[big(*x)]).is_valid(), base.contains(&big(*x)), "{x} at {n_base_bits}");
+        }
+    }
+}
+
+#[test]
+fn base_lookups_handle_values_on_both_sides_of_u64() {
+    // A 70-bit base mixing values that fit in a u64 with ones that do not.
+    let large = BigUint::from(u64::MAX) + 5u32;
+    let base: BTreeSet<BigUint> = [big(0), big(7), big(u64::MAX), large.clone()].into_iter().collect();
+    let p = Propagator::new(InitialPattern::new(base.clone(), 70).unwrap());
+    for x in [big(0), big(7), big(8), big(u64::MAX), large.clone(), &large + 1u32, &large - 1u32] {
+        assert_eq!(p.is_member(&x, 70).unwrap(), base.contains(&x), "{x}");
+    }
+    let joined = (&large << 70) | big(7);
+    assert!(p.is_member(&joined, 140).unwrap());
+    assert!(!p.is_member(&(joined + 1u32), 140).unwrap());
+}
+
+#[test]
+fn prefix_check_prunes_completions_early() {
+    let p = propagator(&[1, 2, 5], 3);
+    // The top 6 bits 111_001 hold 7, which is not in S_base, so no completion is a member.
+    assert_eq!(p.is_member_prefix(&big(0b111_001), 6, 12), Ok(false));
+    assert!((0..1u64 << 6).all(|low| !p.is_member(&big((0b111_001 << 6) | low), 12).unwrap()));
+    // A member prefix is necessary but not sufficient.
+    assert_eq!(p.is_member_prefix(&big(0b001_010), 6, 12), Ok(true));
+    assert!(p.is_member(&big(0b001_010_101_001), 12).unwrap());
+    assert!(!p.is_member(&big(0b001_010_000_000), 12).unwrap());
+}
+
+#[test]
+fn prefix_check_is_membership_at_the_prefix_level() {
+    let p = propagator(&[1, 2, 5], 3);
+    for prefix in 0u64..1 << 6 {
+        for target_n_bits in [6, 12, 24] {
+            assert_eq!(p.is_member_prefix(&big(prefix), 6, target_n_bits), p.is_member(&big(prefix), 6));
+        }
+    }
+}
+
+#[test]
+fn prefix_check_validates_both_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.is_member_prefix(&big(1), 6, 9),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+    assert_eq!(
+        p.is_member_prefix(&big(1), 5, 12),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 5, base_n_bits: 3 })
+    );
+    assert_eq!(p.is_member_prefix(&big(1), 12, 6), Err(HierarchyError::PrefixTooLong { prefix_len: 4, component_count: 2 }));
+    assert_eq!(p.is_member_prefix(&big(64), 6, 12), Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 }));
+}
+
+#[test]
+fn membership_from_components_matches_the_composed_value() {
+    let mut rng = StdRng::seed_from_u64(38);
+    let p = propagator(&[1, 2, 5], 3);
+    for num_components in [1usize, 2, 4, 8] {
+        for _ in 0..50 {
+            // Mix members and arbitrary 6-bit values so both outcomes occur.
+            let components: Vec<BigUint> = (0..num_components)
+                .map(|_| if rng.gen_bool(0.8) { p.generate_random_s_n_member(6, &mut rng).unwrap() } else { big(rng.gen_range(0..64)) })
+                .collect();
+            let composed = components.iter().fold(BigUint::from(0u32), |acc, c| (acc << 6) | c);
+            let n_target_bits = 6 * num_components;
+            assert_eq!(
+                p.is_member_from_components(&components, 6),
+                Ok((p.is_member(&composed, n_target_bits).unwrap(), n_target_bits))
+            );
+        }
+    }
+}
+
+#[test]
+fn membership_from_components_validates_its_input() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.is_member_from_components(&bigs(&[9, 9, 9]), 6), Err(HierarchyError::InvalidComponentCount(3)));
+    assert_eq!(p.is_member_from_components(&[], 6), Err(HierarchyError::InvalidComponentCount(0)));
+    assert_eq!(
+        p.is_member_from_components(&bigs(&[1, 1]), 9),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+    assert_eq!(p.is_member_from_components(&bigs(&[9, 64]), 6), Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 }));
+}
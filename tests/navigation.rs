@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use paired_binary::HierarchyError;
+
+#[test]
+fn next_and_prev_member_match_brute_force() {
+    for (base, n_base_bits) in [(&[1u64, 2, 5][..], 3), (&[0, 3][..], 2), (&[2][..], 2)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 4 * n_base_bits] {
+            let members = members_by_definition(base, n_base_bits, n_bits);
+            for x in 0..1u64 << n_bits {
+                let next = members.iter().find(|&&m| m > x).map(|&m| big(m));
+                let prev = members.iter().rev().find(|&&m| m < x).map(|&m| big(m));
+                assert_eq!(p.next_member(&big(x), n_bits), Ok(next), "next after {x} at {n_bits}");
+                assert_eq!(p.prev_member(&big(x), n_bits), Ok(prev), "prev before {x} at {n_bits}");
+            }
+        }
+    }
+}
+
+#[test]
+fn navigation_ends_past_the_extreme_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    let smallest = p.nth_member(12, &big(0)).unwrap();
+    let largest = p.nth_member(12, &(p.count_members(12).unwrap() - 1u32)).unwrap();
+    assert_eq!(p.prev_member(&smallest, 12), Ok(None));
+    assert_eq!(p.next_member(&largest, 12), Ok(None));
+    assert_eq!(p.next_member(&big(0xFFF), 12), Ok(None));
+    assert_eq!(p.prev_member(&big(0), 12), Ok(None));
+}
+
+#[test]
+fn navigation_works_at_deep_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    let n_bits = 3 << 10;
+    // 0b011 repeated is not a member; the next member replaces the first chunk with 5
+    // and fills the rest with the smallest value.
+    let x = (0..1024).fold(big(0), |acc, _| (acc << 3) | big(3));
+    let expected = (1..1024).fold(big(5), |acc, _| (acc << 3) | big(1));
+    assert_eq!(p.next_member(&x, n_bits), Ok(Some(expected)));
+}
+
+#[test]
+fn navigation_validates_the_level_and_bound() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.next_member(&big(0), 9),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 })
+    );
+    assert_eq!(p.prev_member(&big(64), 6), Err(HierarchyError::ValueTooLargeForNBits { value: big(64), n_bits: 6 }));
+}
+
+#[test]
+fn walking_next_member_visits_members_in_iter_members_order() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut walked = Vec::new();
+    let mut current = p.nth_member(12, &big(0)).ok();
+    while let Some(member) = current {
+        current = p.next_member(&member, 12).unwrap();
+        walked.push(member);
+    }
+    assert_eq!(walked, p.iter_members(12).unwrap().collect::<Vec<_>>());
+}
+
+#[test]
+fn walking_prev_member_visits_members_in_reverse() {
+    let p = propagator(&[0, 3], 2);
+    let mut walked = Vec::new();
+    let mut current = p.nth_member(8, &(p.count_members(8).unwrap() - 1u32)).ok();
+    while let Some(member) = current {
+        current = p.prev_member(&member, 8).unwrap();
+        walked.push(member);
+    }
+    walked.reverse();
+    assert_eq!(walked, p.iter_members(8).unwrap().collect::<Vec<_>>());
+}
@@ -0,0 +1,48 @@
+//! The core propagation logic, exercised without anything from `std` so it also runs
+//! against an `alloc`-only build:
+//! `cargo test --no-default-features --features alloc --test no_std`.
+
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, InitialPattern, PairedEntity, Propagator};
+use rand::rngs::mock::StepRng;
+
+fn propagator() -> Propagator {
+    Propagator::new(InitialPattern::new([1u32, 2, 5].into_iter().map(BigUint::from).collect(), 3).unwrap())
+}
+
+#[test]
+fn membership_and_counting() {
+    let p = propagator();
+    assert_eq!(p.is_member(&BigUint::from(0b001_010_101_001u32), 12), Ok(true));
+    assert_eq!(p.is_member(&BigUint::from(0b111_010_101_001u32), 12), Ok(false));
+    assert_eq!(p.count_members(12), Ok(BigUint::from(81u32)));
+    assert!((p.density(6).unwrap() - 9.0 / 64.0).abs() < 1e-12);
+}
+
+#[test]
+fn decomposition_round_trips() {
+    let p = propagator();
+    let member = BigUint::from(0b001_010_101_001u32);
+    let components = p.decompose_to_base(&member, 12).unwrap();
+    assert_eq!(p.compose_from_base(&components), Ok((member, 12)));
+}
+
+#[test]
+fn random_members_are_members() {
+    let p = propagator();
+    let mut rng = StepRng::new(0, 0x9E37_79B9_7F4A_7C15);
+    for _ in 0..20 {
+        let member = p.generate_random_s_n_member(24, &mut rng).unwrap();
+        assert_eq!(p.is_member(&member, 24), Ok(true));
+    }
+}
+
+#[test]
+fn entities_and_errors() {
+    let e = PairedEntity::new(BigUint::from(3u32), 4).unwrap();
+    assert_eq!(e.x_prime, BigUint::from(12u32));
+    assert_eq!(
+        InitialPattern::new([BigUint::from(8u32)].into_iter().collect(), 3).err(),
+        Some(HierarchyError::ValueExceedsNBaseBits { value: BigUint::from(8u32), n_bits: 3, max_val: BigUint::from(7u32) })
+    );
+}
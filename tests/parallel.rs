@@ -0,0 +1,55 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::HierarchyError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn par_is_members_matches_is_members() {
+    let p = propagator(&[1, 2, 5], 3);
+    let mut targets: Vec<BigUint> = (0..5000).map(big).collect();
+    targets.push(big(1 << 12));
+    assert_eq!(p.par_is_members(&targets, 12).unwrap(), p.is_members(&targets, 12).unwrap());
+}
+
+#[test]
+fn par_is_members_rejects_invalid_levels() {
+    let p = propagator(&[1, 2, 5], 3);
+    assert_eq!(
+        p.par_is_members(&[big(0)], 5),
+        Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 5, base_n_bits: 3 })
+    );
+}
+
+#[test]
+fn decompose_batch_matches_decompose_to_base_in_order() {
+    let mut rng = StdRng::seed_from_u64(13);
+    let p = propagator(&[1, 2, 5], 3);
+    let targets: Vec<(BigUint, usize)> = (0..2000)
+        .map(|i| {
+            let n_bits = 3 << (i % 6);
+            (p.generate_random_s_n_member(n_bits, &mut rng).unwrap(), n_bits)
+        })
+        .collect();
+    let sequential: Vec<_> = targets.iter().map(|(x, n_bits)| p.decompose_to_base(x, *n_bits)).collect();
+    assert_eq!(p.decompose_batch(&targets), sequential);
+}
+
+#[test]
+fn decompose_batch_reports_errors_per_entry() {
+    let p = propagator(&[1, 2, 5], 3);
+    let targets = [(big(0b001_010), 6), (big(0b111_001), 6), (big(0), 9), (big(0b101), 3)];
+    assert_eq!(
+        p.decompose_batch(&targets),
+        vec![
+            Ok(bigs(&[1, 2])),
+            Err(HierarchyError::NotAMember(big(0b111_001))),
+            Err(HierarchyError::InvalidHierarchicalLevel { target_n_bits: 9, base_n_bits: 3 }),
+            Ok(bigs(&[5])),
+        ]
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::*;
+use paired_binary::{HierarchyError, InitialPattern, InitialPatternBuilder};
+
+#[test]
+fn from_hex_parses_every_value() {
+    let p = InitialPattern::from_hex(&["0", "a", "F"], 4).unwrap();
+    assert_eq!(p.s_base_values, pattern(&[0, 10, 15], 4).s_base_values);
+}
+
+#[test]
+fn from_hex_keeps_the_usual_validation() {
+    assert_eq!(InitialPattern::from_hex(&["g"], 4).err(), Some(HierarchyError::InvalidHexString("g".to_string())));
+    assert!(matches!(InitialPattern::from_hex(&["10"], 4), Err(HierarchyError::ValueExceedsNBaseBits { .. })));
+    assert_eq!(InitialPattern::from_hex(&[], 4).err(), Some(HierarchyError::EmptySBaseValues));
+}
+
+#[test]
+fn from_u64_values_accepts_values_up_to_the_full_width() {
+    let p = InitialPattern::from_u64_values(&[0, u64::MAX], 64).unwrap();
+    assert_eq!(p.s_base_values, [big(0), big(u64::MAX)].into_iter().collect());
+    assert!(matches!(
+        InitialPattern::from_u64_values(&[3, 4], 2),
+        Err(HierarchyError::ValueExceedsNBaseBits { n_bits: 2, .. })
+    ));
+    assert_eq!(InitialPattern::from_u64_values(&[], 2).err(), Some(HierarchyError::EmptySBaseValues));
+}
+
+#[test]
+fn complement_closed_pattern_has_no_missing_complements() {
+    let p = pattern(&[0, 7, 2, 5], 3);
+    assert!(p.is_complement_closed());
+    assert!(p.missing_complements().is_empty());
+}
+
+#[test]
+fn missing_complements_lists_unpaired_values_in_order() {
+    let p = pattern(&[0, 7, 6, 2, 3], 3);
+    assert!(!p.is_complement_closed());
+    // 6, 2 and 3 lack 1, 5 and 4; 0 and 7 pair up.
+    assert_eq!(p.missing_complements(), bigs(&[2, 3, 6]));
+}
+
+#[test]
+fn one_bit_values_complement_each_other() {
+    assert!(!pattern(&[0], 1).is_complement_closed());
+    assert!(pattern(&[0, 1], 1).is_complement_closed());
+}
+
+#[test]
+fn new_complement_closed_adds_the_missing_complements() {
+    let p = InitialPattern::new_complement_closed(bigs(&[3]).into_iter().collect(), 4).unwrap();
+    assert_eq!(p.s_base_values, pattern(&[3, 12], 4).s_base_values);
+    assert!(p.is_complement_closed());
+}
+
+#[test]
+fn new_complement_closed_keeps_complete_pairs_unchanged() {
+    let p = InitialPattern::new_complement_closed(bigs(&[0, 7, 2]).into_iter().collect(), 3).unwrap();
+    assert_eq!(p.s_base_values, pattern(&[0, 7, 2, 5], 3).s_base_values);
+}
+
+#[test]
+fn new_complement_closed_keeps_the_usual_validation() {
+    assert_eq!(
+        InitialPattern::new_complement_closed(bigs(&[16]).into_iter().collect(), 4).err(),
+        Some(HierarchyError::ValueExceedsNBaseBits { value: big(16), n_bits: 4, max_val: big(15) })
+    );
+    assert_eq!(InitialPattern::new_complement_closed(Default::default(), 4).err(), Some(HierarchyError::EmptySBaseValues));
+}
+
+#[test]
+fn builder_matches_new() {
+    let built = InitialPattern::builder()
+        .with_n_base_bits(4)
+        .add_value(3u32)
+        .add_value(12u64)
+        .add_values(bigs(&[5, 3]))
+        .build()
+        .unwrap();
+    assert_eq!(built.s_base_values, pattern(&[3, 5, 12], 4).s_base_values);
+    assert_eq!(built.n_base_bits, 4);
+}
+
+#[test]
+fn builder_requires_n_base_bits() {
+    assert_eq!(InitialPatternBuilder::new().add_value(1u32).build().err(), Some(HierarchyError::MissingNBaseBits));
+}
+
+#[test]
+fn builder_keeps_the_usual_validation() {
+    assert_eq!(InitialPattern::builder().with_n_base_bits(4).build().err(), Some(HierarchyError::EmptySBaseValues));
+    assert!(matches!(
+        InitialPattern::builder().with_n_base_bits(4).add_value(16u32).build(),
+        Err(HierarchyError::ValueExceedsNBaseBits { .. })
+    ));
+}
+
+#[test]
+fn patterns_with_the_same_values_are_equal_whatever_the_insertion_order() {
+    assert_eq!(pattern(&[5, 1, 2], 3), pattern(&[1, 2, 5, 2], 3));
+    assert_eq!(pattern(&[5, 1, 2], 3), InitialPattern::builder().with_n_base_bits(3).add_value(2u32).add_value(5u32).add_value(1u32).build().unwrap());
+    assert_ne!(pattern(&[1, 2], 3), pattern(&[1, 2], 4));
+    assert_ne!(pattern(&[1, 2], 3), pattern(&[1, 2, 5], 3));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::HierarchyError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn precomputed_checks_match_plain_checks() {
+    let mut rng = StdRng::seed_from_u64(22);
+    let plain = propagator(&[1, 3, 6, 9, 12], 4);
+    for up_to_level in [8, 16, 32] {
+        let mut precomputed = plain.clone();
+        assert_eq!(precomputed.precompute_levels(up_to_level, 1 << 20), Ok(up_to_level));
+        for n_bits in [4, 8, 16, 64, 1024] {
+            for _ in 0..50 {
+                let member = plain.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+                let flipped = &member ^ (BigUint::from(1u32) << rng.gen_range(0..n_bits));
+                for x in [member, flipped] {
+                    assert_eq!(precomputed.is_member(&x, n_bits), plain.is_member(&x, n_bits), "{x} at {n_bits}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn precompute_snaps_down_to_a_valid_level() {
+    let mut p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.precompute_levels(20, 1000), Ok(12));
+    assert_eq!(p.precompute_levels(3, 1000), Ok(3));
+}
+
+#[test]
+fn precompute_refuses_large_tables() {
+    let mut p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.precompute_levels(12, 80), Err(HierarchyError::TooManyMembers { count: big(81), max_members: 80 }));
+    assert_eq!(p.precompute_levels(12, 81), Ok(12));
+}
+
+#[test]
+fn precompute_validates_the_level() {
+    let mut p = propagator(&[1, 2, 5], 3);
+    assert_eq!(p.precompute_levels(2, 1000), Err(HierarchyError::TargetNBitsTooSmall { target_n_bits: 2, base_n_bits: 3 }));
+    assert_eq!(p.precompute_levels(96, 1000), Err(HierarchyError::LevelExceedsMachineWord { n_bits: 96, max_bits: 64 }));
+}
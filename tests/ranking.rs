@@ -0,0 +1,68 @@
+mod common;
+
+use common::*;
+use num_bigint::RandBigInt;
+use paired_binary::HierarchyError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn nth_member_follows_iter_members_order() {
+    let p = propagator(&[2, 0, 5], 3);
+    for n_bits in [3, 6, 12] {
+        for (index, member) in p.iter_members(n_bits).unwrap().enumerate() {
+            assert_eq!(p.nth_member(n_bits, &big(index as u64)).unwrap(), member);
+        }
+    }
+}
+
+#[test]
+fn nth_member_rejects_indices_past_the_count() {
+    let p = propagator(&[2, 0, 5], 3);
+    assert_eq!(p.nth_member(6, &big(9)), Err(HierarchyError::IndexOutOfRange { index: big(9), count: big(9) }));
+    assert!(p.nth_member(6, &big(8)).is_ok());
+    assert!(matches!(p.nth_member(9, &big(0)), Err(HierarchyError::InvalidHierarchicalLevel { .. })));
+}
+
+#[test]
+fn index_of_member_inverts_nth_member() {
+    let p = propagator(&[2, 0, 5], 3);
+    for n_bits in [3, 6, 12] {
+        for (index, member) in p.iter_members(n_bits).unwrap().enumerate() {
+            assert_eq!(p.index_of_member(&member, n_bits).unwrap(), big(index as u64));
+        }
+    }
+}
+
+#[test]
+fn index_of_member_rejects_non_members() {
+    let p = propagator(&[2, 0, 5], 3);
+    assert_eq!(p.index_of_member(&big(1), 6), Err(HierarchyError::NotAMember(big(1))));
+}
+
+#[test]
+fn nth_member_reads_the_index_most_significant_component_first() {
+    // Sorted S_base is [0, 2, 5], so the digits of index 1 = (0, 1) pick (0, 2),
+    // and index 3 = (1, 0) picks (2, 0).
+    let p = propagator(&[2, 0, 5], 3);
+    assert_eq!(p.nth_member(6, &big(0)).unwrap(), big(0b000_000));
+    assert_eq!(p.nth_member(6, &big(1)).unwrap(), big(0b000_010));
+    assert_eq!(p.nth_member(6, &big(3)).unwrap(), big(0b010_000));
+    assert_eq!(p.nth_member(6, &big(8)).unwrap(), big(0b101_101));
+}
+
+#[test]
+fn ranking_round_trips_across_patterns_and_levels() {
+    let mut rng = StdRng::seed_from_u64(6);
+    for (base, n_base_bits) in [(&[1u64, 2, 5][..], 3), (&[0xdead_beef1, 7, 0x1_0000_0000][..], 37), (&[0, 1][..], 1), (&[9][..], 4)] {
+        let p = propagator(base, n_base_bits);
+        for n_bits in [n_base_bits, 2 * n_base_bits, 8 * n_base_bits, 64 * n_base_bits] {
+            let count = p.count_members(n_bits).unwrap();
+            for _ in 0..20 {
+                let index = rng.gen_biguint_below(&count);
+                let member = p.nth_member(n_bits, &index).unwrap();
+                assert_eq!(p.index_of_member(&member, n_bits).unwrap(), index);
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::*;
+use paired_binary::RepairStrategy;
+
+#[test]
+fn nearest_member_hamming_is_a_closest_member() {
+    let p = propagator(&[0b0011, 0b1100], 4);
+    let members: Vec<_> = p.iter_members(8).unwrap().collect();
+    for x in 0..1 << 8 {
+        let x = big(x);
+        let (nearest, distance) = p.nearest_member_hamming(&x, 8).unwrap();
+        assert!(p.is_member(&nearest, 8).unwrap());
+        assert_eq!((&nearest ^ &x).count_ones(), distance);
+        let best = members.iter().map(|m| (m ^ &x).count_ones()).min().unwrap();
+        assert_eq!(distance, best, "{x}");
+    }
+}
+
+#[test]
+fn nearest_member_hamming_breaks_ties_towards_the_smaller_value() {
+    // 0b0000 is two bits from both 0b0011 and 0b1100.
+    let p = propagator(&[0b0011, 0b1100], 4);
+    assert_eq!(p.nearest_member_hamming(&big(0), 8).unwrap(), (big(0b0011_0011), 4));
+}
+
+#[test]
+fn nearest_member_of_a_member_is_itself() {
+    let p = propagator(&[0b0011, 0b1100], 4);
+    assert_eq!(p.nearest_member_hamming(&big(0b1100_0011), 8).unwrap(), (big(0b1100_0011), 0));
+    assert!(p.nearest_member_hamming(&big(0), 12).is_err());
+}
+
+#[test]
+fn distance_to_set_is_zero_exactly_for_members() {
+    for p in [propagator(&[0b0011, 0b1100], 4), propagator(&[1, 4, 6], 3)] {
+        let n_bits = 2 * p.initial_pattern().n_base_bits;
+        for x in 0..1u64 << n_bits {
+            let x = big(x);
+            let distance = p.hamming_distance_to_set(&x, n_bits).unwrap();
+            assert_eq!(distance == 0, p.is_member(&x, n_bits).unwrap(), "{x}");
+            assert_eq!(distance, p.nearest_member_hamming(&x, n_bits).unwrap().1);
+        }
+    }
+}
+
+#[test]
+fn chunk_distances_sum_to_the_distance_to_set() {
+    let p = propagator(&[0b0011, 0b1100], 4);
+    // 0b0000 and 0b0111 are two and one bits from their nearest S_base values.
+    let x = big(0b0011_0000_0111_1100);
+    assert_eq!(p.chunk_hamming_distances(&x, 16).unwrap(), vec![0, 2, 1, 0]);
+    assert_eq!(p.hamming_distance_to_set(&x, 16), Ok(3));
+    assert!(p.chunk_hamming_distances(&x, 12).is_err());
+}
+
+#[test]
+fn repair_replaces_only_invalid_chunks() {
+    let p = propagator(&[1, 4, 6], 3);
+    // 0b111 is not in S_base; 0b100 is.
+    let x = big(0b111_100);
+    assert_eq!(p.repair(&x, 6, RepairStrategy::NearestHamming).unwrap(), (big(0b110_100), 1));
+    assert_eq!(p.repair(&x, 6, RepairStrategy::NearestNumeric).unwrap(), (big(0b110_100), 1));
+    assert_eq!(p.repair(&x, 6, RepairStrategy::SmallestBaseValue).unwrap(), (big(0b001_100), 1));
+}
+
+#[test]
+fn repair_strategies_pick_different_replacements() {
+    let p = propagator(&[1, 4, 6], 3);
+    // 0b101 is one bit from 1 and 4 (Hamming prefers 1) and numerically closest to 4 and 6
+    // (numeric prefers 4). Every strategy maps 0b000 to 1.
+    assert_eq!(p.repair(&big(0b101_000), 6, RepairStrategy::NearestHamming).unwrap(), (big(0b001_001), 2));
+    assert_eq!(p.repair(&big(0b101_000), 6, RepairStrategy::NearestNumeric).unwrap(), (big(0b100_001), 2));
+    assert_eq!(p.repair(&big(0), 6, RepairStrategy::SmallestBaseValue).unwrap(), (big(0b001_001), 2));
+}
+
+#[test]
+fn repaired_values_are_always_members() {
+    let p = propagator(&[1, 4, 6], 3);
+    for strategy in [RepairStrategy::NearestHamming, RepairStrategy::NearestNumeric, RepairStrategy::SmallestBaseValue] {
+        for x in 0..1 << 12 {
+            let (repaired, replaced) = p.repair(&big(x), 12, strategy).unwrap();
+            assert!(p.is_member(&repaired, 12).unwrap());
+            assert_eq!(replaced == 0, repaired == big(x));
+        }
+    }
+    assert_eq!(p.repair(&big(0b100_001), 6, RepairStrategy::NearestHamming).unwrap(), (big(0b100_001), 0));
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{HierarchyError, MembershipExplanation, MembershipRule, Propagator, RepairStrategy};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+#[test]
+fn new_uses_both_halves() {
+    assert_eq!(propagator(&[1, 2], 2).rule(), MembershipRule::BothHalves);
+    assert_eq!(MembershipRule::default(), MembershipRule::BothHalves);
+}
+
+#[test]
+fn either_half_membership_matches_the_definition() {
+    let base = [1, 2];
+    let p = Propagator::with_rule(pattern(&base, 2), MembershipRule::EitherHalf);
+    for n_bits in [2, 4, 8] {
+        for x in 0..1u64 << n_bits {
+            let expected = is_member_under(MembershipRule::EitherHalf, &base, 2, x, n_bits);
+            assert_eq!(p.is_member(&big(x), n_bits).unwrap(), expected, "{x} at {n_bits}");
+        }
+    }
+}
+
+#[test]
+fn either_half_accepts_values_both_halves_rejects() {
+    let both = propagator(&[1, 2], 2);
+    let either = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::EitherHalf);
+    // Chunks 01 and 11: only the upper one is in S_base.
+    assert!(!both.is_member(&big(0b01_11), 4).unwrap());
+    assert!(either.is_member(&big(0b01_11), 4).unwrap());
+    assert!(!either.is_member(&big(0b00_11), 4).unwrap());
+}
+
+#[test]
+fn decomposition_is_unsupported_under_either_half() {
+    let p = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::EitherHalf);
+    let unsupported = Err(HierarchyError::DecompositionUndefinedForRule(MembershipRule::EitherHalf));
+    assert_eq!(p.decompose_to_base(&big(0b01_11), 4), unsupported);
+    assert_eq!(p.decompose_to_base(&big(0b01_01), 4), unsupported);
+}
+
+/// The 99.9th percentile of the chi-squared distribution with 7 degrees of freedom.
+const CHI_SQUARED_7_DOF_999: f64 = 24.32;
+
+const RULES: [MembershipRule; 3] = [MembershipRule::BothHalves, MembershipRule::EitherHalf, MembershipRule::ExactlyOne];
+
+/// Whether `x` is a member at `n_bits` under `rule`, straight from the definition.
+fn is_member_under(rule: MembershipRule, base: &[u64], n_base_bits: usize, x: u64, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(&x);
+    }
+    let half = n_bits / 2;
+    let upper = is_member_under(rule, base, n_base_bits, x >> half, half);
+    let lower = is_member_under(rule, base, n_base_bits, x & ((1 << half) - 1), half);
+    match rule {
+        MembershipRule::BothHalves => upper && lower,
+        MembershipRule::EitherHalf => upper || lower,
+        MembershipRule::ExactlyOne => upper != lower,
+    }
+}
+
+#[test]
+fn membership_matches_the_definition_under_every_rule() {
+    for (base, n_base_bits, levels) in [(&[1u64, 2][..], 2, &[2usize, 4, 8, 16][..]), (&[0, 5, 6][..], 3, &[3, 6, 12][..])] {
+        for rule in RULES {
+            let p = Propagator::with_rule(pattern(base, n_base_bits), rule);
+            for &n_bits in levels {
+                for x in 0..1u64 << n_bits {
+                    let expected = is_member_under(rule, base, n_base_bits, x, n_bits);
+                    assert_eq!(p.is_member(&big(x), n_bits).unwrap(), expected, "{rule:?}: {x} at {n_bits}");
+                    assert_eq!(p.is_member_u64(x, n_bits).unwrap(), expected, "{rule:?}: {x} at {n_bits}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn exactly_one_is_not_a_condition_on_the_chunks() {
+    let p = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::ExactlyOne);
+    // 01_00 has one member half, so it is a member at 4 bits; two of them side by side
+    // make both halves members at 8 bits, which fails, while 01_00 next to 00_00 passes.
+    assert!(p.is_member(&big(0b01_00), 4).unwrap());
+    assert!(!p.is_member(&big(0b01_00_01_00), 8).unwrap());
+    assert!(p.is_member(&big(0b01_00_00_00), 8).unwrap());
+    assert_eq!(p.is_member_from_components(&bigs(&[0b01_00, 0b01_00]), 4), Ok((false, 8)));
+    assert_eq!(p.is_member_from_components(&bigs(&[0b01, 0, 0, 0]), 2), Ok((true, 8)));
+}
+
+#[test]
+fn member_counts_match_enumeration_under_every_rule() {
+    for (base, n_base_bits, levels) in [(&[1u64, 2][..], 2, &[2usize, 4, 8, 16][..]), (&[0, 5, 6][..], 3, &[3, 6, 12][..])] {
+        for rule in RULES {
+            let p = Propagator::with_rule(pattern(base, n_base_bits), rule);
+            for &n_bits in levels {
+                let expected = (0..1u64 << n_bits).filter(|&x| is_member_under(rule, base, n_base_bits, x, n_bits)).count() as u64;
+                assert_eq!(p.count_members(n_bits), Ok(big(expected)), "{rule:?} at {n_bits}");
+                assert_eq!(p.density_exact(n_bits), Ok((big(expected), big(1 << n_bits))));
+                let density = expected as f64 / (1u64 << n_bits) as f64;
+                assert!((p.density(n_bits).unwrap() - density).abs() < 1e-12, "{rule:?} at {n_bits}");
+            }
+        }
+    }
+}
+
+#[test]
+fn either_half_counts_stay_exact_at_deep_levels() {
+    let p = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::EitherHalf);
+    // Only values made entirely of the non-member chunks 00 and 11 are excluded.
+    for n_bits in [32, 256, 4096] {
+        let non_members = BigUint::from(1u32) << (n_bits / 2);
+        assert_eq!(p.count_members(n_bits), Ok((BigUint::from(1u32) << n_bits) - non_members));
+    }
+}
+
+#[test]
+fn generated_values_respect_every_rule() {
+    let mut rng = StdRng::seed_from_u64(51);
+    let base = [0u64, 5, 6];
+    for rule in RULES {
+        let p = Propagator::with_rule(pattern(&base, 3), rule);
+        for n_bits in [3, 6, 12, 24] {
+            for _ in 0..200 {
+                let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+                assert!(p.is_member(&member, n_bits).unwrap(), "{rule:?}: {member} at {n_bits}");
+                let non_member = p.generate_random_non_member(n_bits, &mut rng).unwrap();
+                assert!(!p.is_member(&non_member, n_bits).unwrap(), "{rule:?}: {non_member} at {n_bits}");
+            }
+            let stream: Vec<BigUint> = p.random_member_stream(n_bits, StdRng::seed_from_u64(52)).unwrap().take(50).collect();
+            assert!(stream.iter().all(|member| p.is_member(member, n_bits).unwrap()));
+        }
+    }
+}
+
+#[test]
+fn generation_under_exactly_one_is_uniform() {
+    let p = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::ExactlyOne);
+    let mut rng = StdRng::seed_from_u64(53);
+    // 8 members and 8 non-members at 4 bits.
+    let draws = 16_000;
+    let mut members = HashMap::new();
+    let mut non_members = HashMap::new();
+    for _ in 0..draws {
+        *members.entry(p.generate_random_s_n_member(4, &mut rng).unwrap()).or_insert(0) += 1;
+        *non_members.entry(p.generate_random_non_member(4, &mut rng).unwrap()).or_insert(0) += 1;
+    }
+    assert_eq!((members.len(), non_members.len()), (8, 8));
+    assert!(chi_squared(&members, draws) < CHI_SQUARED_7_DOF_999, "{members:?}");
+    assert!(chi_squared(&non_members, draws) < CHI_SQUARED_7_DOF_999, "{non_members:?}");
+}
+
+#[test]
+fn generation_reports_levels_without_members_or_non_members() {
+    let mut rng = StdRng::seed_from_u64(54);
+    // With every 1-bit value in S_base, both halves are always members.
+    let exactly_one = Propagator::with_rule(pattern(&[0, 1], 1), MembershipRule::ExactlyOne);
+    assert_eq!(exactly_one.count_members(2), Ok(big(0)));
+    assert_eq!(exactly_one.generate_random_s_n_member(2, &mut rng), Err(HierarchyError::NoMembers { n_bits: 2 }));
+    assert!(exactly_one.random_member_stream(4, StdRng::seed_from_u64(0)).is_err());
+    assert_eq!(exactly_one.generate_random_non_member(1, &mut rng), Err(HierarchyError::SetIsComplete { n_base_bits: 1 }));
+    assert!(exactly_one.generate_random_non_member(2, &mut rng).is_ok());
+
+    let either = Propagator::with_rule(pattern(&[0, 1], 1), MembershipRule::EitherHalf);
+    assert_eq!(either.generate_random_non_member(4, &mut rng), Err(HierarchyError::SetIsComplete { n_base_bits: 1 }));
+}
+
+#[test]
+fn composition_is_unsupported_under_other_rules() {
+    for rule in [MembershipRule::EitherHalf, MembershipRule::ExactlyOne] {
+        let p = Propagator::with_rule(pattern(&[1, 2], 2), rule);
+        let unsupported = HierarchyError::DecompositionUndefinedForRule(rule);
+        assert_eq!(p.compose_from_base(&bigs(&[1, 2])), Err(unsupported.clone()));
+        assert_eq!(p.compose_from_iter(bigs(&[1, 2]).into_iter()), Err(unsupported.clone()));
+        assert_eq!(p.compose_from_base_u64(&[1, 2]), Err(unsupported.clone()));
+        assert_eq!(p.decompose_to_base(&big(0b01_10), 4), Err(unsupported.clone()));
+        assert_eq!(p.nth_member(4, &big(0)), Err(unsupported.clone()));
+        assert_eq!(p.min_member(4), Err(unsupported.clone()));
+        assert_eq!(p.max_member(4), Err(unsupported.clone()));
+        assert_eq!(p.count_members_in_range(&big(0), &big(15), 4), Err(unsupported.clone()));
+        assert_eq!(p.repair(&big(0b01_11), 4, RepairStrategy::NearestHamming), Err(unsupported.clone()));
+        assert_eq!(p.self_test(), Err(unsupported));
+    }
+}
+
+#[test]
+fn exactly_one_explanations_stop_at_blocks_with_several_member_parts() {
+    let p = Propagator::with_rule(pattern(&[1, 2], 2), MembershipRule::ExactlyOne);
+    // The upper half 01_10 has two member parts; the lower half 00_00 has none.
+    assert_eq!(
+        p.explain_membership(&big(0b01_10_00_00), 8),
+        Ok(MembershipExplanation::NotMember { value: big(0b01_10), n_bits: 4, path: vec![0] })
+    );
+    // Neither half of 00_11 has a member part, so the descent reaches a base chunk.
+    assert_eq!(
+        p.explain_membership(&big(0b00_11), 4),
+        Ok(MembershipExplanation::NotMember { value: big(0), n_bits: 2, path: vec![0] })
+    );
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use paired_binary::{HierarchyError, InitialPattern, MembershipRule, PairedEntity, Propagator};
+
+#[test]
+fn initial_pattern_round_trips_through_json() {
+    let p = pattern(&[2, 0, 1], 3);
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(json, r#"{"s_base_values":["0","1","2"],"n_base_bits":3}"#);
+    let back: InitialPattern = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.s_base_values, p.s_base_values);
+    assert_eq!(back.n_base_bits, p.n_base_bits);
+}
+
+#[test]
+fn initial_pattern_deserialization_is_validated() {
+    for json in [
+        r#"{"s_base_values":["9"],"n_base_bits":3}"#,
+        r#"{"s_base_values":[],"n_base_bits":3}"#,
+        r#"{"s_base_values":["x"],"n_base_bits":3}"#,
+        r#"{"s_base_values":["-1"],"n_base_bits":3}"#,
+    ] {
+        assert!(serde_json::from_str::<InitialPattern>(json).is_err(), "{json}");
+    }
+}
+
+#[test]
+fn paired_entity_round_trips_through_json() {
+    let e = PairedEntity::new(big(6), 3).unwrap();
+    let json = serde_json::to_string(&e).unwrap();
+    assert_eq!(json, r#"{"x":"6","x_prime":"1","n_bits":3}"#);
+    assert_eq!(serde_json::from_str::<PairedEntity>(&json).unwrap(), e);
+}
+
+#[test]
+fn hand_edited_non_complementary_pair_fails_to_deserialize() {
+    let error = serde_json::from_str::<PairedEntity>(r#"{"x":"6","x_prime":"2","n_bits":3}"#).unwrap_err();
+    assert_eq!(error.to_string(), HierarchyError::NonComplementaryPair { val1: big(6), val2_complement: big(2), n_bits: 3 }.to_string());
+    assert!(serde_json::from_str::<PairedEntity>(r#"{"x":"9","x_prime":"0","n_bits":3}"#).is_err());
+}
+
+#[test]
+fn propagator_round_trips_through_json() {
+    let p = Propagator::with_arity(pattern(&[0, 1, 2, 300], 9), 3).unwrap();
+    let json = serde_json::to_string(&p).unwrap();
+    assert!(json.starts_with(r#"{"initial_pattern":{"s_base_values":["0","1","2","300"],"n_base_bits":9},"arity":3"#), "{json}");
+    let back: Propagator = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+}
+
+#[test]
+fn propagator_round_trips_through_bincode() {
+    let p = Propagator::with_arity(pattern(&[0, 1, 2, 300], 9), 3).unwrap();
+    let bytes = bincode::serialize(&p).unwrap();
+    let back: Propagator = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(bincode::serialize(&back).unwrap(), bytes);
+}
+
+#[test]
+fn oversized_sequence_lengths_are_not_preallocated() {
+    let mut bytes = bincode::serialize(&pattern(&[1, 2], 3)).unwrap();
+    // Claim far more values than the input holds; the length prefix leads the encoding.
+    bytes[..8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+    assert!(bincode::deserialize::<InitialPattern>(&bytes).is_err());
+}
+
+#[test]
+fn binary_formats_encode_values_as_big_endian_bytes() {
+    let e = PairedEntity::new(big(300), 9).unwrap();
+    let bytes = bincode::serialize(&e).unwrap();
+    // x = 300 is written as a length-prefixed 0x01 0x2C rather than a decimal string.
+    assert_eq!(&bytes[..10], &[2, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x2C]);
+    assert_eq!(bincode::deserialize::<PairedEntity>(&bytes).unwrap(), e);
+}
+
+#[test]
+fn propagator_deserialization_is_validated() {
+    let error = serde_json::from_str::<Propagator>(r#"{"initial_pattern":{"s_base_values":["1"],"n_base_bits":3},"arity":1}"#)
+        .unwrap_err();
+    assert_eq!(error.to_string(), HierarchyError::InvalidArity(1).to_string());
+    let error = serde_json::from_str::<Propagator>(r#"{"initial_pattern":{"s_base_values":["9"],"n_base_bits":3},"arity":2}"#)
+        .unwrap_err();
+    assert!(error.to_string().starts_with(
+        &HierarchyError::ValueExceedsNBaseBits { value: big(9), n_bits: 3, max_val: big(7) }.to_string()
+    ));
+}
+
+#[test]
+fn propagator_config_round_trips_through_to_json() {
+    let p = Propagator::with_arity(pattern(&[300, 0, 2], 9), 3).unwrap();
+    let json = p.to_json();
+    assert_eq!(json, r#"{"n_base_bits":9,"s_base_values":["0","2","300"],"arity":3,"rule":"BothHalves","split_strategy":"HighLow"}"#);
+    let back = Propagator::from_json(&json).unwrap();
+    assert_eq!(back.to_json(), json);
+    assert!(back.is_member(&big(300 << 18 | 2 << 9), 27).unwrap());
+}
+
+#[test]
+fn propagator_config_defaults_the_arity_and_rule() {
+    let p = Propagator::from_json(r#"{ "n_base_bits": 3, "s_base_values": ["1", "2", "5"] }"#).unwrap();
+    assert_eq!((p.arity(), p.rule()), (2, MembershipRule::BothHalves));
+    assert_eq!(p.initial_pattern().s_base_values, pattern(&[1, 2, 5], 3).s_base_values);
+}
+
+#[test]
+fn propagator_config_errors_are_reported() {
+    assert!(matches!(Propagator::from_json("{"), Err(HierarchyError::JsonParseError(_))));
+    assert!(matches!(
+        Propagator::from_json(r#"{"n_base_bits":3,"s_base_values":[1]}"#),
+        Err(HierarchyError::JsonParseError(_))
+    ));
+    assert_eq!(
+        Propagator::from_json(r#"{"n_base_bits":3,"s_base_values":[]}"#).err(),
+        Some(HierarchyError::EmptySBaseValues)
+    );
+    assert_eq!(
+        Propagator::from_json(r#"{"n_base_bits":3,"s_base_values":["1"],"arity":1}"#).err(),
+        Some(HierarchyError::InvalidArity(1))
+    );
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::*;
+use paired_binary::{HierarchyError, MembershipRule, Propagator, SplitStrategy};
+
+#[test]
+fn union_holds_the_values_of_both_patterns() {
+    let lhs = pattern(&[1, 2, 5], 3);
+    let rhs = pattern(&[2, 6], 3);
+    assert_eq!(lhs.union(&rhs).unwrap().s_base_values, pattern(&[1, 2, 5, 6], 3).s_base_values);
+    assert_eq!(lhs.union(&lhs).unwrap().s_base_values, lhs.s_base_values);
+}
+
+#[test]
+fn intersection_holds_the_overlap() {
+    let lhs = pattern(&[1, 2, 5], 3);
+    assert_eq!(lhs.intersection(&pattern(&[2, 5, 6], 3)).unwrap().s_base_values, pattern(&[2, 5], 3).s_base_values);
+    assert_eq!(lhs.intersection(&pattern(&[0, 7], 3)).err(), Some(HierarchyError::EmptySBaseValues));
+}
+
+#[test]
+fn difference_removes_the_other_patterns_values() {
+    let lhs = pattern(&[1, 2, 5], 3);
+    assert_eq!(lhs.difference(&pattern(&[2, 6], 3)).unwrap().s_base_values, pattern(&[1, 5], 3).s_base_values);
+    assert_eq!(lhs.difference(&pattern(&[1, 2, 5, 7], 3)).err(), Some(HierarchyError::EmptySBaseValues));
+}
+
+#[test]
+fn union_widens_the_propagated_hierarchy() {
+    let lhs = pattern(&[1, 2], 2);
+    let rhs = pattern(&[3], 2);
+    let union = Propagator::new(lhs.union(&rhs).unwrap());
+    // Every member under either pattern is a member under their union.
+    for x in 0u64..256 {
+        let x = big(x);
+        let in_either = Propagator::new(lhs.clone()).is_member(&x, 8).unwrap()
+            || Propagator::new(rhs.clone()).is_member(&x, 8).unwrap();
+        assert!(!in_either || union.is_member(&x, 8).unwrap());
+    }
+    assert_eq!(union.count_members(8), Ok(big(81)));
+}
+
+#[test]
+fn intersected_propagator_requires_membership_under_both() {
+    let lhs = propagator(&[1, 2, 5, 6], 3);
+    let rhs = propagator(&[0, 2, 5, 7], 3);
+    let both = lhs.intersect(&rhs).unwrap();
+    for n_bits in [3, 6, 12] {
+        for x in 0u64..1 << n_bits {
+            let x = big(x);
+            let expected = lhs.is_member(&x, n_bits).unwrap() && rhs.is_member(&x, n_bits).unwrap();
+            assert_eq!(both.is_member(&x, n_bits).unwrap(), expected, "{x} at {n_bits} bits");
+        }
+    }
+}
+
+#[test]
+fn united_propagator_contains_both_and_keeps_the_arity() {
+    let lhs = Propagator::with_arity(pattern(&[1, 2], 2), 4).unwrap();
+    let union = lhs.union(&Propagator::with_arity(pattern(&[3], 2), 4).unwrap()).unwrap();
+    assert_eq!(union.arity(), 4);
+    assert_eq!(union.count_members(8), Ok(big(81)));
+    for x in 0u64..256 {
+        assert!(!lhs.is_member(&big(x), 8).unwrap() || union.is_member(&big(x), 8).unwrap());
+    }
+}
+
+#[test]
+fn propagator_set_algebra_validates_the_patterns() {
+    let lhs = propagator(&[1, 2], 2);
+    assert_eq!(lhs.intersect(&propagator(&[3], 2)).err(), Some(HierarchyError::EmptySBaseValues));
+    assert_eq!(lhs.union(&propagator(&[3], 3)).err(), Some(HierarchyError::MismatchedBaseBits { lhs: 2, rhs: 3 }));
+    assert_eq!(lhs.intersect(&propagator(&[1], 3)).err(), Some(HierarchyError::MismatchedBaseBits { lhs: 2, rhs: 3 }));
+}
+
+#[test]
+fn propagator_set_algebra_requires_the_same_configuration() {
+    let lhs = propagator(&[1, 2], 2);
+    let quaternary = Propagator::with_arity(pattern(&[1, 3], 2), 4).unwrap();
+    assert_eq!(lhs.intersect(&quaternary).err(), Some(HierarchyError::MismatchedArity { lhs: 2, rhs: 4 }));
+    assert_eq!(quaternary.union(&lhs).err(), Some(HierarchyError::MismatchedArity { lhs: 4, rhs: 2 }));
+
+    let either = Propagator::with_rule(pattern(&[1, 3], 2), MembershipRule::EitherHalf);
+    assert_eq!(
+        lhs.intersect(&either).err(),
+        Some(HierarchyError::MismatchedRule { lhs: MembershipRule::BothHalves, rhs: MembershipRule::EitherHalf })
+    );
+
+    let interleaved = Propagator::with_split_strategy(pattern(&[1, 3], 2), SplitStrategy::Interleaved);
+    assert_eq!(
+        lhs.union(&interleaved).err(),
+        Some(HierarchyError::MismatchedSplitStrategy { lhs: SplitStrategy::HighLow, rhs: SplitStrategy::Interleaved })
+    );
+}
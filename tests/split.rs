@@ -0,0 +1,242 @@
+mod common;
+
+use common::*;
+use num_bigint::BigUint;
+use paired_binary::{CachedPropagator, DecompNode, HierarchyError, MembershipTrace, Propagator, SplitStrategy};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn interleaved(values: &[u64], n_base_bits: usize) -> Propagator {
+    Propagator::with_split_strategy(pattern(values, n_base_bits), SplitStrategy::Interleaved)
+}
+
+/// Splits `x` into its odd-indexed bits (upper part) and even-indexed bits (lower part).
+fn deinterleave(x: u64, n_bits: usize) -> (u64, u64) {
+    let (mut odd, mut even) = (0, 0);
+    for i in 0..n_bits / 2 {
+        odd |= ((x >> (2 * i + 1)) & 1) << i;
+        even |= ((x >> (2 * i)) & 1) << i;
+    }
+    (odd, even)
+}
+
+/// Membership by the recursive definition, splitting into odd and even bits at each level.
+fn is_member_interleaved(base: &[u64], n_base_bits: usize, x: u64, n_bits: usize) -> bool {
+    if n_bits == n_base_bits {
+        return base.contains(&x);
+    }
+    let (odd, even) = deinterleave(x, n_bits);
+    is_member_interleaved(base, n_base_bits, odd, n_bits / 2) && is_member_interleaved(base, n_base_bits, even, n_bits / 2)
+}
+
+#[test]
+fn high_low_is_the_default() {
+    let p = propagator(&[1, 2], 2);
+    assert_eq!(p.split_strategy(), SplitStrategy::HighLow);
+    assert_eq!(Propagator::with_split_strategy(pattern(&[1, 2], 2), SplitStrategy::HighLow), p);
+    assert_ne!(interleaved(&[1, 2], 2), p);
+}
+
+#[test]
+fn interleaved_membership_matches_the_recursive_definition() {
+    let base = [1, 2];
+    let p = interleaved(&base, 2);
+    for n_bits in [2, 4, 8, 16] {
+        for x in 0u64..1 << n_bits {
+            assert_eq!(p.is_member(&big(x), n_bits).unwrap(), is_member_interleaved(&base, 2, x, n_bits), "{x} at {n_bits}");
+            assert_eq!(p.is_member_u64(x, n_bits).unwrap(), is_member_interleaved(&base, 2, x, n_bits), "{x} at {n_bits}");
+        }
+    }
+}
+
+#[test]
+fn interleaved_composition_puts_the_upper_part_on_odd_bits() {
+    let p = interleaved(&[1, 2, 3], 2);
+    // 0b10 on the odd bits and 0b01 on the even bits.
+    assert_eq!(p.compose_from_base(&bigs(&[0b10, 0b01])), Ok((big(0b1001), 4)));
+    assert_eq!(p.decompose_to_base(&big(0b1001), 4), Ok(bigs(&[0b10, 0b01])));
+    // The same components side by side under `HighLow`.
+    assert_eq!(propagator(&[1, 2, 3], 2).compose_from_base(&bigs(&[0b10, 0b01])), Ok((big(0b10_01), 4)));
+    assert_eq!(p.compose_from_base(&bigs(&[0b11, 0b01])), Ok((big(0b1011), 4)));
+}
+
+#[test]
+fn decompose_and_compose_round_trip_under_both_strategies() {
+    let mut rng = StdRng::seed_from_u64(51);
+    for strategy in [SplitStrategy::HighLow, SplitStrategy::Interleaved] {
+        let p = Propagator::with_split_strategy(pattern(&[1, 6, 5, 12], 4), strategy);
+        for n_bits in [4, 8, 16, 32, 64, 256] {
+            for _ in 0..20 {
+                let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+                let components = p.decompose_to_base(&member, n_bits).unwrap();
+                assert_eq!(p.compose_from_base(&components), Ok((member.clone(), n_bits)), "{strategy:?}");
+                assert_eq!(p.compose_from_base_unchecked(&components), (member.clone(), n_bits), "{strategy:?}");
+                assert_eq!(p.compose_from_iter(components.clone().into_iter()), Ok((member.clone(), n_bits)), "{strategy:?}");
+                if n_bits >= 16 {
+                    let halves = p.decompose_to_level(&member, n_bits, n_bits / 2).unwrap();
+                    assert_eq!(p.compose_from_level(&halves, n_bits / 2), Ok((member.clone(), n_bits)), "{strategy:?}");
+                }
+                if n_bits <= 64 {
+                    let word = member.iter_u64_digits().next().unwrap_or(0);
+                    let words = p.decompose_to_base_u64(word, n_bits).unwrap();
+                    assert_eq!(words, components.iter().map(|c| c.iter_u64_digits().next().unwrap_or(0)).collect::<Vec<_>>());
+                    assert_eq!(p.compose_from_base_u64(&words), Ok((word, n_bits)), "{strategy:?}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn compose_then_decompose_returns_the_components() {
+    let mut rng = StdRng::seed_from_u64(52);
+    let p = interleaved(&[1, 6, 5, 12], 4);
+    let base = bigs(&[1, 6, 5, 12]);
+    for count in [1usize, 2, 4, 8, 32] {
+        let components: Vec<BigUint> = (0..count).map(|_| base[rng.gen_range(0..base.len())].clone()).collect();
+        let (member, n_bits) = p.compose_from_base(&components).unwrap();
+        assert!(p.is_member(&member, n_bits).unwrap());
+        assert_eq!(p.decompose_to_base(&member, n_bits), Ok(components));
+    }
+}
+
+#[test]
+fn unchecked_decomposition_truncates_wide_values() {
+    let p = interleaved(&[1, 2, 3], 2);
+    assert_eq!(p.decompose_to_base_unchecked(&big(0b1_0000), 4), bigs(&[0, 0]));
+    assert_eq!(p.decompose_to_base_unchecked(&big(0b1_1001), 4), bigs(&[0b10, 0b01]));
+    let wide = (BigUint::from(1u32) << 63) | big(0b1001);
+    assert_eq!(p.decompose_to_base_unchecked(&wide, 32), p.decompose_to_base_unchecked(&big(0b1001), 32));
+}
+
+#[test]
+fn interleaved_generation_respects_membership() {
+    let mut rng = StdRng::seed_from_u64(53);
+    let base = [1, 2];
+    let p = interleaved(&base, 2);
+    for n_bits in [2, 4, 8, 16] {
+        for _ in 0..50 {
+            let member = p.generate_random_s_n_member(n_bits, &mut rng).unwrap();
+            let word = member.iter_u64_digits().next().unwrap_or(0);
+            assert!(is_member_interleaved(&base, 2, word, n_bits), "{member} at {n_bits}");
+            let non_member = p.generate_random_non_member(n_bits, &mut rng).unwrap();
+            let word = non_member.iter_u64_digits().next().unwrap_or(0);
+            assert!(!is_member_interleaved(&base, 2, word, n_bits), "{non_member} at {n_bits}");
+        }
+    }
+    for member in p.generate_distinct_random_members(8, 10, &mut rng).unwrap() {
+        assert!(p.is_member(&member, 8).unwrap());
+    }
+}
+
+#[test]
+fn ranking_by_components_follows_the_strategy() {
+    let high_low = propagator(&[1, 6, 5, 12], 4);
+    let p = interleaved(&[1, 6, 5, 12], 4);
+    let count = p.count_members(16).unwrap();
+    assert_eq!(count, high_low.count_members(16).unwrap());
+    let mut index = BigUint::from(0u32);
+    while index < count {
+        let member = p.nth_member(16, &index).unwrap();
+        assert_eq!(p.decompose_to_base(&member, 16), high_low.decompose_to_base(&high_low.nth_member(16, &index).unwrap(), 16));
+        index += 1u32;
+    }
+}
+
+#[test]
+fn traces_and_trees_hold_the_interleaved_parts() {
+    let p = interleaved(&[1, 2], 2);
+    let (member, _) = p.compose_from_base(&bigs(&[1, 2, 2, 1])).unwrap();
+
+    fn check_tree(node: &DecompNode) {
+        if let Some((upper, lower)) = node.children() {
+            let (odd, even) = deinterleave(node.value().iter_u64_digits().next().unwrap_or(0), node.n_bits());
+            assert_eq!((upper.value(), lower.value()), (&big(odd), &big(even)));
+            check_tree(upper);
+            check_tree(lower);
+        }
+    }
+    check_tree(&p.decompose_to_tree(&member, 8).unwrap());
+
+    fn check_trace(trace: &MembershipTrace) {
+        if let [upper, lower] = &trace.children[..] {
+            let (odd, even) = deinterleave(trace.value.iter_u64_digits().next().unwrap_or(0), trace.n_bits);
+            assert_eq!((&upper.value, &lower.value), (&big(odd), &big(even)));
+            check_trace(upper);
+            check_trace(lower);
+        }
+    }
+    let trace = p.trace_membership(&member, 8).unwrap();
+    assert!(trace.passed);
+    check_trace(&trace);
+    check_trace(&p.trace_membership(&(member ^ big(1)), 8).unwrap());
+}
+
+#[test]
+fn interleaved_precomputed_levels_and_lifting_agree() {
+    let base = [1, 2];
+    let plain = interleaved(&base, 2);
+    let mut precomputed = plain.clone();
+    assert_eq!(precomputed.precompute_levels(8, 1 << 10), Ok(8));
+    for x in 0u64..1 << 16 {
+        assert_eq!(precomputed.is_member(&big(x), 16), plain.is_member(&big(x), 16), "{x}");
+    }
+
+    let lifted = plain.lift_to_level(4, 16).unwrap();
+    let lifted = Propagator::with_split_strategy(lifted, SplitStrategy::Interleaved);
+    for x in 0u64..1 << 16 {
+        assert_eq!(lifted.is_member(&big(x), 16), plain.is_member(&big(x), 16), "{x}");
+    }
+}
+
+#[test]
+fn cached_propagator_follows_the_strategy() {
+    let plain = interleaved(&[1, 2], 2);
+    let mut cached = CachedPropagator::new(plain.clone(), None);
+    for x in 0u64..1 << 8 {
+        assert_eq!(cached.is_member(&big(x), 8), plain.is_member(&big(x), 8), "{x}");
+    }
+    cached.set_initial_pattern(pattern(&[1, 3], 2));
+    assert_eq!(cached.propagator().split_strategy(), SplitStrategy::Interleaved);
+}
+
+#[test]
+fn order_dependent_operations_require_high_low() {
+    let p = interleaved(&[1, 2], 2);
+    let unsupported = HierarchyError::UnsupportedSplitStrategy(SplitStrategy::Interleaved);
+    assert_eq!(p.iter_members(4).err(), Some(unsupported.clone()));
+    assert_eq!(p.iter_members_matching(4, &[]).err(), Some(unsupported.clone()));
+    assert_eq!(p.next_member(&big(0), 4), Err(unsupported.clone()));
+    assert_eq!(p.prev_member(&big(15), 4), Err(unsupported.clone()));
+    assert_eq!(p.is_member_prefix(&big(1), 2, 4), Err(unsupported.clone()));
+    assert_eq!(p.decompose_with_offsets(&big(0b0110), 4), Err(unsupported.clone()));
+    assert_eq!(p.count_members_in_range(&big(0), &big(15), 4), Err(unsupported));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn split_strategy_survives_serialization() {
+    let p = interleaved(&[1, 2], 2);
+    let json = p.to_json();
+    assert!(json.contains("Interleaved"), "{json}");
+    assert_eq!(Propagator::from_json(&json), Ok(p));
+    // Configurations written before the field existed keep the default.
+    let p = Propagator::from_json(r#"{"s_base_values": ["1", "2"], "n_base_bits": 2}"#).unwrap();
+    assert_eq!(p.split_strategy(), SplitStrategy::HighLow);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn interleaving_generalizes_to_higher_arities() {
+    let p = Propagator::from_json(r#"{"s_base_values": ["1", "2"], "n_base_bits": 2, "arity": 3, "split_strategy": "Interleaved"}"#).unwrap();
+    // Part j of each level takes the bits at positions congruent to 2 - j modulo 3.
+    let (member, n_bits) = p.compose_from_base(&bigs(&[1, 2, 2])).unwrap();
+    assert_eq!((member.clone(), n_bits), (big(0b011_100), 6));
+    assert_eq!(p.decompose_to_base(&member, 6), Ok(bigs(&[1, 2, 2])));
+    let mut rng = StdRng::seed_from_u64(54);
+    for _ in 0..20 {
+        let member = p.generate_random_s_n_member(54, &mut rng).unwrap();
+        let components = p.decompose_to_base(&member, 54).unwrap();
+        assert_eq!(p.compose_from_base(&components), Ok((member, 54)));
+    }
+}
@@ -0,0 +1,345 @@
+use wasm_bindgen::prelude::*;
+use paired_binary::{InitialPattern, Propagator, HierarchyError, PairedEntity};
+use num_bigint::BigUint;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+
+
+// Helper to convert Rust Result<T, HierarchyError> to JsValue Result<JsValue, JsValue>
+// This helper is for cases where the Ok variant should be a general JsValue
+fn to_js_result_generic<T, F>(rust_result: Result<T, HierarchyError>, success_converter: F) -> Result<JsValue, JsValue>
+where
+    F: FnOnce(T) -> Result<JsValue, JsValue>,
+{
+    match rust_result {
+        Ok(val) => success_converter(val),
+        Err(err) => Err(JsValue::from_str(&format!("HierarchyError: {:?}", err))),
+    }
+}
+
+/// Parses a comma-separated list of decimal values into an S_base set.
+fn parse_s_base(s_base_values_str: &str) -> Result<BTreeSet<BigUint>, JsValue> {
+    let mut s_base = BTreeSet::new();
+    for val_str in s_base_values_str.split(',') {
+        let val_trimmed = val_str.trim();
+        if val_trimmed.is_empty() { continue; }
+        match BigUint::from_str(val_trimmed) {
+            Ok(b_val) => { s_base.insert(b_val); }
+            Err(e) => return Err(JsValue::from_str(&format!("Invalid BigUint in s_base: '{}', error: {}", val_trimmed, e))),
+        }
+    }
+    Ok(s_base)
+}
+
+/// Converts a JS decimal string or `BigInt` into a `BigUint`.
+/// `what` names the value in error messages.
+fn biguint_from_js(value: &JsValue, what: &str) -> Result<BigUint, JsValue> {
+    let decimal: String = if let Some(text) = value.as_string() {
+        text
+    } else if let Some(bigint) = value.dyn_ref::<js_sys::BigInt>() {
+        bigint.to_string(10).map_err(JsValue::from)?.into()
+    } else {
+        return Err(JsValue::from_str(&format!("{} is not a string, a BigInt, or is undefined", what)));
+    };
+    BigUint::from_str(&decimal)
+        .map_err(|e| JsValue::from_str(&format!("Invalid BigUint string for {} '{}': {}", what, decimal, e)))
+}
+
+/// Converts a `BigUint` into a JS `BigInt`.
+fn biguint_to_js_bigint(value: &BigUint) -> Result<js_sys::BigInt, JsValue> {
+    js_sys::BigInt::from_str(&value.to_string()).map_err(JsValue::from)
+}
+
+/// A JS-facing handle owning an independent `Propagator`.
+/// Several handles with different configurations can be used side by side.
+#[wasm_bindgen]
+pub struct WasmPropagator {
+    propagator: Propagator,
+}
+
+#[wasm_bindgen]
+impl WasmPropagator {
+    /// Creates a propagator from a comma-separated list of decimal S_base values
+    /// and their bit-width.
+    #[wasm_bindgen(constructor)]
+    pub fn new(s_base_values_str: &str, n_base_bits: usize) -> Result<WasmPropagator, JsValue> {
+        let s_base = parse_s_base(s_base_values_str)?;
+        match InitialPattern::new(s_base, n_base_bits) {
+            Ok(pattern) => Ok(WasmPropagator { propagator: Propagator::new(pattern) }),
+            Err(e) => Err(JsValue::from_str(&format!("Error creating InitialPattern: {:?}", e))),
+        }
+    }
+
+    /// Creates a propagator from a JSON configuration, as read by `Propagator::from_json`.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<WasmPropagator, JsValue> {
+        Propagator::from_json(json)
+            .map(|propagator| WasmPropagator { propagator })
+            .map_err(|e| JsValue::from_str(&format!("Error loading Propagator: {:?}", e)))
+    }
+
+    /// Returns the JSON configuration of this propagator, as written by
+    /// `Propagator::to_json`.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        self.propagator.to_json()
+    }
+
+    /// Checks membership of `x_target`, given as a decimal string or a `BigInt`.
+    pub fn is_member(&self, x_target: JsValue, n_target_bits: usize) -> Result<bool, JsValue> {
+        let x_target = biguint_from_js(&x_target, "x_target")?;
+
+        match self.propagator.is_member(&x_target, n_target_bits) {
+            Ok(is_mem) => Ok(is_mem),
+            Err(e) => Err(JsValue::from_str(&format!("{:?}", e))),
+        }
+    }
+
+    /// Checks membership of every value of `xs`, given as decimal strings or `BigInt`s,
+    /// in a single call. Returns an array of booleans in the same order.
+    /// Fails on the first value that cannot be parsed or checked, naming its index.
+    pub fn is_member_batch(&self, xs: js_sys::Array, n_target_bits: usize) -> Result<js_sys::Array, JsValue> {
+        let mut targets = Vec::with_capacity(xs.length() as usize);
+        for i in 0..xs.length() {
+            targets.push(biguint_from_js(&xs.get(i), &format!("xs[{}]", i))?);
+        }
+
+        let results = self
+            .propagator
+            .is_members(&targets, n_target_bits)
+            .map_err(|err| JsValue::from_str(&format!("HierarchyError: {:?}", err)))?;
+        let js_array = js_sys::Array::new_with_length(results.len() as u32);
+        for (i, result) in results.into_iter().enumerate() {
+            let is_mem = result.map_err(|err| JsValue::from_str(&format!("HierarchyError at xs[{}]: {:?}", i, err)))?;
+            js_array.set(i as u32, JsValue::from_bool(is_mem));
+        }
+        Ok(js_array)
+    }
+
+    /// Decomposes an S_N member to its S_base components.
+    /// Returns a js_sys::Array of strings (decimal representation of BigUint components).
+    pub fn decompose_to_base(&self, x_target_str: &str, n_target_bits: usize) -> Result<js_sys::Array, JsValue> {
+        let x_target = BigUint::from_str(x_target_str)
+            .map_err(|e| JsValue::from_str(&format!("Invalid BigUint string for x_target: {}", e)))?;
+        self.decompose_to_js_array(&x_target, n_target_bits, |comp| Ok(JsValue::from_str(&comp.to_string())))
+    }
+
+    /// Decomposes an S_N member to its S_base components.
+    /// Returns a js_sys::Array of `BigInt`s.
+    pub fn decompose_to_base_bigint(&self, x_target: JsValue, n_target_bits: usize) -> Result<js_sys::Array, JsValue> {
+        let x_target = biguint_from_js(&x_target, "x_target")?;
+        self.decompose_to_js_array(&x_target, n_target_bits, |comp| Ok(biguint_to_js_bigint(comp)?.into()))
+    }
+
+    /// Composes an S_N member from an array of S_base components, given as decimal
+    /// strings or `BigInt`s.
+    /// Returns a JS object { value: string, n_bits: number }.
+    pub fn compose_from_base(&self, s_base_components_js_array: js_sys::Array) -> Result<JsValue, JsValue> {
+        self.compose_to_js_object(s_base_components_js_array, |composed_val| Ok(JsValue::from_str(&composed_val.to_string())))
+    }
+
+    /// Composes an S_N member from an array of S_base components, given as decimal
+    /// strings or `BigInt`s.
+    /// Returns a JS object { value: BigInt, n_bits: number }.
+    pub fn compose_from_base_bigint(&self, s_base_components_js_array: js_sys::Array) -> Result<JsValue, JsValue> {
+        self.compose_to_js_object(s_base_components_js_array, |composed_val| Ok(biguint_to_js_bigint(composed_val)?.into()))
+    }
+
+    /// Generates a random S_N member using a ChaCha8 generator seeded with `seed`,
+    /// so the same seed always yields the same member.
+    /// Returns the decimal string representation of the BigUint.
+    pub fn generate_random_member(&self, target_n_bits: usize, seed: u64) -> Result<String, JsValue> {
+        Ok(self.seeded_random_member(target_n_bits, seed)?.to_string())
+    }
+
+    /// Generates a random S_N member using a ChaCha8 generator seeded with `seed`,
+    /// so the same seed always yields the same member.
+    /// Returns the member as a `BigInt`.
+    pub fn generate_random_member_bigint(&self, target_n_bits: usize, seed: u64) -> Result<js_sys::BigInt, JsValue> {
+        biguint_to_js_bigint(&self.seeded_random_member(target_n_bits, seed)?)
+    }
+
+    /// Returns up to `limit` members of S_N at `n_target_bits` in ascending order, as an
+    /// array of decimal strings.
+    pub fn first_members(&self, n_target_bits: usize, limit: usize) -> Result<js_sys::Array, JsValue> {
+        self.first_members_to_js_array(n_target_bits, limit, |member| Ok(JsValue::from_str(&member.to_string())))
+    }
+
+    /// Returns up to `limit` members of S_N at `n_target_bits` in ascending order, as an
+    /// array of `BigInt`s.
+    pub fn first_members_bigint(&self, n_target_bits: usize, limit: usize) -> Result<js_sys::Array, JsValue> {
+        self.first_members_to_js_array(n_target_bits, limit, |member| Ok(biguint_to_js_bigint(member)?.into()))
+    }
+}
+
+impl WasmPropagator {
+    fn seeded_random_member(&self, target_n_bits: usize, seed: u64) -> Result<BigUint, JsValue> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        self.propagator
+            .generate_random_s_n_member(target_n_bits, &mut rng)
+            .map_err(|e| JsValue::from_str(&format!("{:?}", e)))
+    }
+
+    fn first_members_to_js_array(
+        &self,
+        n_target_bits: usize,
+        limit: usize,
+        convert: impl Fn(&BigUint) -> Result<JsValue, JsValue>,
+    ) -> Result<js_sys::Array, JsValue> {
+        let members = self
+            .propagator
+            .iter_members(n_target_bits)
+            .map_err(|err| JsValue::from_str(&format!("HierarchyError: {:?}", err)))?;
+        let js_array = js_sys::Array::new();
+        for member in members.take(limit) {
+            js_array.push(&convert(&member)?);
+        }
+        Ok(js_array)
+    }
+
+    fn decompose_to_js_array(
+        &self,
+        x_target: &BigUint,
+        n_target_bits: usize,
+        convert: impl Fn(&BigUint) -> Result<JsValue, JsValue>,
+    ) -> Result<js_sys::Array, JsValue> {
+        // Direct handling for this specific return type
+        match self.propagator.decompose_to_base(x_target, n_target_bits) {
+            Ok(components_biguint) => {
+                let js_array = js_sys::Array::new_with_length(components_biguint.len() as u32);
+                for (i, comp) in components_biguint.iter().enumerate() {
+                    js_array.set(i as u32, convert(comp)?);
+                }
+                Ok(js_array) // Directly return js_sys::Array
+            }
+            Err(err) => Err(JsValue::from_str(&format!("HierarchyError: {:?}", err))),
+        }
+    }
+
+    fn compose_to_js_object(
+        &self,
+        s_base_components_js_array: js_sys::Array,
+        convert: impl FnOnce(&BigUint) -> Result<JsValue, JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let mut s_base_components_biguint: Vec<BigUint> = Vec::new();
+        for i in 0..s_base_components_js_array.length() {
+            let js_val = s_base_components_js_array.get(i);
+            s_base_components_biguint.push(biguint_from_js(&js_val, "component")?);
+        }
+
+        // Using the generic helper here is fine as the return type is Result<JsValue, JsValue>
+        to_js_result_generic(self.propagator.compose_from_base(&s_base_components_biguint), |(composed_val, composed_n_bits)| {
+            let result_obj = js_sys::Object::new();
+            // Using .map_err for the Reflect::set operations to convert potential JS exceptions into our Result's Err type
+            js_sys::Reflect::set(&result_obj, &JsValue::from_str("value"), &convert(&composed_val)?)
+                .map_err(|e| JsValue::from_str(&format!("JS Reflect Error: {:?}", e)))?;
+            js_sys::Reflect::set(&result_obj, &JsValue::from_str("n_bits"), &JsValue::from(composed_n_bits as u32))
+                .map_err(|e| JsValue::from_str(&format!("JS Reflect Error: {:?}", e)))?;
+            Ok(JsValue::from(result_obj))
+        })
+    }
+}
+
+// --- Free-function API ---
+// These predate `WasmPropagator` and are kept for existing pages. They delegate to a
+// default handle configured by `setup_propagator`; new code should use `WasmPropagator`.
+// They are deprecated, and live in their own module so the code `wasm_bindgen`
+// generates for them may call them without tripping the deprecation lint.
+
+pub use free_functions::*;
+
+#[allow(deprecated)]
+mod free_functions {
+    use super::*;
+
+    thread_local! {
+        static DEFAULT_PROPAGATOR: RefCell<Option<WasmPropagator>> = const { RefCell::new(None) };
+    }
+
+    /// Runs `f` on the default handle, or fails with `not_initialized_msg` if
+    /// `setup_propagator` has not been called.
+    fn with_default_propagator<T>(
+        not_initialized_msg: &str,
+        f: impl FnOnce(&mut WasmPropagator) -> Result<T, JsValue>,
+    ) -> Result<T, JsValue> {
+        DEFAULT_PROPAGATOR.with(|cell| match cell.borrow_mut().as_mut() {
+            Some(handle) => f(handle),
+            None => Err(JsValue::from_str(not_initialized_msg)),
+        })
+    }
+
+    /// Configures the default handle used by the free functions below.
+    /// Prefer constructing a `WasmPropagator` instead.
+    #[deprecated(note = "use WasmPropagator::new")]
+    #[wasm_bindgen]
+    pub fn setup_propagator(s_base_values_str: &str, n_base_bits: usize) -> Result<(), JsValue> {
+        let handle = WasmPropagator::new(s_base_values_str, n_base_bits)?;
+        DEFAULT_PROPAGATOR.with(|cell| *cell.borrow_mut() = Some(handle));
+        Ok(())
+    }
+
+    /// Prefer `WasmPropagator::is_member`.
+    #[deprecated(note = "use WasmPropagator::is_member")]
+    #[wasm_bindgen]
+    pub fn is_member(x_target_str: &str, n_target_bits: usize) -> Result<bool, JsValue> {
+        with_default_propagator("Propagator not initialized. Call setup_propagator first.", |handle| {
+            handle.is_member(JsValue::from_str(x_target_str), n_target_bits)
+        })
+    }
+
+    /// Prefer `WasmPropagator::decompose_to_base`.
+    #[deprecated(note = "use WasmPropagator::decompose_to_base")]
+    #[wasm_bindgen]
+    pub fn decompose_to_base(x_target_str: &str, n_target_bits: usize) -> Result<js_sys::Array, JsValue> {
+        with_default_propagator("Propagator not initialized.", |handle| {
+            handle.decompose_to_base(x_target_str, n_target_bits)
+        })
+    }
+
+    /// Prefer `WasmPropagator::compose_from_base`.
+    #[deprecated(note = "use WasmPropagator::compose_from_base")]
+    #[wasm_bindgen]
+    pub fn compose_from_base(s_base_components_js_array: js_sys::Array) -> Result<JsValue, JsValue> {
+        with_default_propagator("Propagator not initialized.", |handle| {
+            handle.compose_from_base(s_base_components_js_array)
+        })
+    }
+
+    /// Prefer `WasmPropagator::generate_random_member`.
+    /// `seed` seeds the generator directly, so equal seeds yield equal members.
+    ///
+    /// **Breaking:** `seed` used to be an offset added to a seed stored with the default
+    /// propagator, so repeated calls with the same argument returned different members.
+    /// Callers that relied on that should pass a different seed on each call.
+    #[deprecated(note = "use WasmPropagator::generate_random_member")]
+    #[wasm_bindgen]
+    pub fn generate_random_member(target_n_bits: usize, seed: u32) -> Result<String, JsValue> {
+        with_default_propagator("Propagator not initialized.", |handle| {
+            handle.generate_random_member(target_n_bits, u64::from(seed))
+        })
+    }
+}
+
+/// Creates a PairedEntity and returns it as a JS object { x: string, x_prime: string, n_bits: number }.
+#[wasm_bindgen]
+pub fn create_paired_entity(x_str: &str, n_bits: usize) -> Result<JsValue, JsValue> {
+    let x_val = BigUint::from_str(x_str)
+        .map_err(|e| JsValue::from_str(&format!("Invalid BigUint string for x: {}", e)))?;
+    
+    // Using the generic helper here
+    to_js_result_generic(PairedEntity::new(x_val, n_bits), |pe| {
+        let result_obj = js_sys::Object::new();
+        js_sys::Reflect::set(&result_obj, &JsValue::from_str("x"), &JsValue::from_str(&pe.x.to_string()))
+             .map_err(|e| JsValue::from_str(&format!("JS Reflect Error: {:?}", e)))?;
+        js_sys::Reflect::set(&result_obj, &JsValue::from_str("x_prime"), &JsValue::from_str(&pe.x_prime.to_string()))
+             .map_err(|e| JsValue::from_str(&format!("JS Reflect Error: {:?}", e)))?;
+        js_sys::Reflect::set(&result_obj, &JsValue::from_str("n_bits"), &JsValue::from(pe.n_bits as u32))
+             .map_err(|e| JsValue::from_str(&format!("JS Reflect Error: {:?}", e)))?;
+        Ok(JsValue::from(result_obj))
+    })
+}
\ No newline at end of file
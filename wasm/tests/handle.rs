@@ -0,0 +1,134 @@
+//! Tests for the JavaScript-facing API. They need a JavaScript host:
+//! `wasm-pack test --node wasm`.
+#![cfg(target_arch = "wasm32")]
+
+use paired_binary_wasm::WasmPropagator;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn handles_with_different_patterns_are_independent() {
+    let small = WasmPropagator::new("1,2", 2).unwrap();
+    let large = WasmPropagator::new("5", 3).unwrap();
+    assert!(small.is_member("6", 4).unwrap());
+    assert!(!large.is_member("6", 6).unwrap());
+    assert!(large.is_member("45", 6).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn handle_decomposes_and_composes() {
+    let handle = WasmPropagator::new("1,2,5", 3).unwrap();
+    let components = handle.decompose_to_base("41", 6).unwrap();
+    assert_eq!(components.length(), 2);
+    assert_eq!(components.get(0).as_string().unwrap(), "5");
+    assert_eq!(components.get(1).as_string().unwrap(), "1");
+    let composed = handle.compose_from_base(components).unwrap();
+    let value = js_sys::Reflect::get(&composed, &JsValue::from_str("value")).unwrap();
+    let n_bits = js_sys::Reflect::get(&composed, &JsValue::from_str("n_bits")).unwrap();
+    assert_eq!(value.as_string().unwrap(), "41");
+    assert_eq!(n_bits.as_f64().unwrap(), 6.0);
+}
+
+#[wasm_bindgen_test]
+fn handle_reports_errors_to_javascript() {
+    assert!(WasmPropagator::new("9", 3).is_err());
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    assert!(handle.is_member("0", 3).is_err());
+    assert!(handle.decompose_to_base("0", 4).is_err());
+}
+
+#[wasm_bindgen_test]
+#[allow(deprecated)]
+fn free_functions_use_the_default_handle() {
+    paired_binary_wasm::setup_propagator("1,2", 2).unwrap();
+    assert!(paired_binary_wasm::is_member("6", 4).unwrap());
+    assert!(!paired_binary_wasm::is_member("0", 4).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn bigint_values_are_accepted_and_returned() {
+    let handle = WasmPropagator::new("1,2,5", 3).unwrap();
+    let member: JsValue = js_sys::BigInt::from(41u64).into();
+    assert!(handle.is_member(member.clone(), 6).unwrap());
+
+    let components = handle.decompose_to_base_bigint(member, 6).unwrap();
+    assert_eq!(components.get(0), JsValue::from(js_sys::BigInt::from(5u64)));
+    assert_eq!(components.get(1), JsValue::from(js_sys::BigInt::from(1u64)));
+
+    let composed = handle.compose_from_base_bigint(components).unwrap();
+    let value = js_sys::Reflect::get(&composed, &JsValue::from_str("value")).unwrap();
+    assert_eq!(value, JsValue::from(js_sys::BigInt::from(41u64)));
+}
+
+#[wasm_bindgen_test]
+fn bigint_values_past_u64_round_trip() {
+    // Sixteen 9-bit components of 511 make a 144-bit value of all ones.
+    let handle = WasmPropagator::new("511", 9).unwrap();
+    let member = "22300745198530623141535718272648361505980415";
+    assert!(handle.is_member(JsValue::from_str(member), 144).unwrap());
+    let components = handle.decompose_to_base_bigint(JsValue::from_str(member), 144).unwrap();
+    assert_eq!(components.length(), 16);
+    let composed = handle.compose_from_base_bigint(components).unwrap();
+    let value = js_sys::Reflect::get(&composed, &JsValue::from_str("value")).unwrap();
+    let value: js_sys::BigInt = value.into();
+    assert_eq!(String::from(value.to_string(10).unwrap()), member);
+}
+
+#[wasm_bindgen_test]
+fn non_numeric_inputs_are_rejected() {
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    assert!(handle.is_member(JsValue::from_f64(6.0), 4).is_err());
+    assert!(handle.is_member(JsValue::from_str("six"), 4).is_err());
+}
+
+#[wasm_bindgen_test]
+fn first_members_are_the_smallest_members() {
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    let members = handle.first_members(4, 3).unwrap();
+    let members: Vec<String> = members.iter().map(|member| member.as_string().unwrap()).collect();
+    assert_eq!(members, ["5", "6", "9"]);
+
+    // Only four members exist at 4 bits.
+    let members = handle.first_members_bigint(4, 10).unwrap();
+    assert_eq!(members.length(), 4);
+    assert_eq!(members.get(3), JsValue::from(js_sys::BigInt::from(10u64)));
+    assert_eq!(handle.first_members(4, 0).unwrap().length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn first_members_rejects_invalid_levels() {
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    let err = handle.first_members(6, 3).unwrap_err();
+    assert!(err.as_string().unwrap().contains("InvalidHierarchicalLevel"));
+}
+
+#[wasm_bindgen_test]
+fn batch_membership_matches_single_checks() {
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    let xs = js_sys::Array::new();
+    for x in ["6", "0", "9", "15"] {
+        xs.push(&JsValue::from_str(x));
+    }
+    xs.push(&js_sys::BigInt::from(10u64).into());
+    let results: Vec<bool> = handle.is_member_batch(xs, 4).unwrap().iter().map(|r| r.as_bool().unwrap()).collect();
+    assert_eq!(results, [true, false, true, false, true]);
+}
+
+#[wasm_bindgen_test]
+fn batch_membership_names_the_failing_entry() {
+    let handle = WasmPropagator::new("1,2", 2).unwrap();
+    let xs = js_sys::Array::of3(&JsValue::from_str("6"), &JsValue::from_str("six"), &JsValue::from_str("99"));
+    assert!(handle.is_member_batch(xs, 4).unwrap_err().as_string().unwrap().contains("xs[1]"));
+    let xs = js_sys::Array::of2(&JsValue::from_str("6"), &JsValue::from_str("99"));
+    assert!(handle.is_member_batch(xs, 4).unwrap_err().as_string().unwrap().contains("xs[1]"));
+    assert!(handle.is_member_batch(js_sys::Array::new(), 3).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[wasm_bindgen_test]
+fn handle_loads_and_writes_json_configurations() {
+    let handle = WasmPropagator::from_json(r#"{"n_base_bits":2,"s_base_values":["1","2"]}"#).unwrap();
+    assert!(handle.is_member("6", 4).unwrap());
+    assert_eq!(handle.to_json(), r#"{"n_base_bits":2,"s_base_values":["1","2"],"arity":2,"rule":"BothHalves"}"#);
+    assert!(WasmPropagator::from_json("{}").is_err());
+}
@@ -0,0 +1,43 @@
+//! The seeded generator returns plain strings, so these run natively.
+
+use num_bigint::BigUint;
+use paired_binary::{InitialPattern, Propagator};
+use paired_binary_wasm::WasmPropagator;
+use std::str::FromStr;
+
+#[test]
+fn equal_seeds_give_equal_members() {
+    let handle = WasmPropagator::new("1,2,5", 3).unwrap();
+    for seed in [0, 1, u64::MAX] {
+        assert_eq!(handle.generate_random_member(96, seed).unwrap(), handle.generate_random_member(96, seed).unwrap());
+    }
+}
+
+#[test]
+fn equal_seeds_give_equal_members_across_handles() {
+    // Separately built handles hold separately hashed sets of S_base values.
+    for seed in [0, 1, u64::MAX] {
+        let first = WasmPropagator::new("0,1,2,3,4,5,6,7", 3).unwrap();
+        let second = WasmPropagator::new("0,1,2,3,4,5,6,7", 3).unwrap();
+        assert_eq!(first.generate_random_member(96, seed).unwrap(), second.generate_random_member(96, seed).unwrap());
+    }
+}
+
+#[test]
+fn seeded_members_are_members() {
+    let handle = WasmPropagator::new("1,2,5", 3).unwrap();
+    let p = Propagator::new(InitialPattern::new([1u64, 2, 5].into_iter().map(BigUint::from).collect(), 3).unwrap());
+    for seed in 0..50 {
+        let member = BigUint::from_str(&handle.generate_random_member(96, seed).unwrap()).unwrap();
+        assert!(p.is_member(&member, 96).unwrap());
+    }
+}
+
+#[test]
+fn different_seeds_give_different_members() {
+    let handle = WasmPropagator::new("1,2,5", 3).unwrap();
+    let members: std::collections::HashSet<String> =
+        (0..20).map(|seed| handle.generate_random_member(96, seed).unwrap()).collect();
+    // 3^32 members at 96 bits make a collision among 20 draws vanishingly unlikely.
+    assert_eq!(members.len(), 20);
+}